@@ -0,0 +1,308 @@
+//! GitHub 账号分析：列出公开仓库，汇总语言和 topic 得到技术权重
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use techwall::analyze::{linguist_language, Analysis, Collector};
+use techwall::catalog::Catalog;
+use techwall::search::{search, MatchKind};
+
+use crate::http::{encode, Response, Transport};
+
+const API: &str = "https://api.github.com";
+const PER_PAGE: usize = 100;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Repo {
+    pub full_name: String,
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResult {
+    total_count: u64,
+}
+
+/// 最近一次响应里的限流信息
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug)]
+pub struct RateLimit {
+    pub remaining: Option<u64>,
+    /// 配额重置时间，unix 秒
+    pub reset: Option<u64>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub struct Client<T: Transport> {
+    transport: T,
+    base: String,
+    token: Option<String>,
+    pub limit: RateLimit,
+    /// 配额用完时最多等待多久，超过则直接报错
+    pub max_wait: Duration,
+    pub include_forks: bool,
+}
+
+impl<T: Transport> Client<T> {
+    pub fn new(transport: T) -> Client<T> {
+        Client {
+            transport,
+            base: API.to_string(),
+            token: None,
+            limit: RateLimit::default(),
+            max_wait: Duration::from_secs(15 * 60),
+            include_forks: false,
+        }
+    }
+
+    /// 指向本地 mock 服务或 GitHub Enterprise
+    pub fn with_base(mut self, base: &str) -> Self {
+        self.base = base.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    // 配额用完时等到重置时间
+    fn throttle(&self) -> Result<()> {
+        let (Some(0), Some(reset)) = (self.limit.remaining, self.limit.reset) else {
+            return Ok(());
+        };
+        let wait = Duration::from_secs(reset.saturating_sub(now()) + 1);
+        if wait > self.max_wait {
+            bail!("GitHub rate limit exhausted, resets in {}s", wait.as_secs());
+        }
+        eprintln!("rate limit reached, waiting {}s", wait.as_secs());
+        std::thread::sleep(wait);
+        Ok(())
+    }
+
+    fn update_limit(&mut self, response: &Response) {
+        let header = |name: &str| response.headers.get(name).and_then(|v| v.trim().parse().ok());
+        if let Some(remaining) = header("x-ratelimit-remaining") {
+            self.limit.remaining = Some(remaining);
+        }
+        if let Some(reset) = header("x-ratelimit-reset") {
+            self.limit.reset = Some(reset);
+        }
+    }
+
+    fn request<R: for<'de> Deserialize<'de>>(&mut self, path: &str) -> Result<R> {
+        let url = format!("{}{}", self.base, path);
+        let auth = self.token.as_ref().map(|t| format!("Bearer {}", t));
+        let mut headers = vec![
+            ("Accept", "application/vnd.github+json"),
+            ("User-Agent", "techwall-crawler"),
+        ];
+        if let Some(auth) = &auth {
+            headers.push(("Authorization", auth));
+        }
+        let mut retried = false;
+        loop {
+            self.throttle()?;
+            let response = self.transport.get(&url, &headers)?;
+            self.update_limit(&response);
+            let limited = matches!(response.status, 403 | 429) && self.limit.remaining == Some(0);
+            if limited && !retried {
+                retried = true;
+                continue;
+            }
+            if response.status != 200 {
+                bail!("GET {} returned {}: {}", path, response.status, response.body.trim());
+            }
+            return Ok(serde_json::from_str(&response.body)?);
+        }
+    }
+
+    /// 用户的公开仓库，自动翻页
+    pub fn repos(&mut self, user: &str) -> Result<Vec<Repo>> {
+        let mut repos = Vec::new();
+        for page in 1.. {
+            let path = format!("/users/{}/repos?per_page={}&page={}", user, PER_PAGE, page);
+            let batch: Vec<Repo> = self.request(&path)?;
+            let last = batch.len() < PER_PAGE;
+            repos.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(repos)
+    }
+
+    /// 仓库各语言的字节数
+    pub fn languages(&mut self, full_name: &str) -> Result<BTreeMap<String, u64>> {
+        self.request(&format!("/repos/{}/languages", full_name))
+    }
+
+    /// 带该 topic 的仓库数，与 Node 爬虫的 `searchGithub` 相同
+    pub fn topic_count(&mut self, topic: &str) -> Result<u64> {
+        let path = format!("/search/repositories?q=topic:{}&per_page=1", encode(&topic.to_lowercase()));
+        let rs: SearchResult = self.request(&path)?;
+        Ok(rs.total_count)
+    }
+
+    /// 汇总用户所有仓库：语言按字节数，topic 通过目录解析成技术名后按出现次数
+    pub fn analyze_user(&mut self, user: &str, catalog: &Catalog) -> Result<Analysis> {
+        let mut collector = Collector::default();
+        for repo in self.repos(user)? {
+            if repo.fork && !self.include_forks {
+                continue;
+            }
+            for (language, bytes) in self.languages(&repo.full_name)? {
+                if let Some(name) = linguist_language(&language) {
+                    collector.bytes(name, bytes);
+                }
+            }
+            for topic in &repo.topics {
+                if let Some(name) = resolve_topic(catalog, topic) {
+                    collector.mention(name, &repo.full_name);
+                }
+            }
+        }
+        Ok(collector.finish())
+    }
+}
+
+// 只接受精确或别名匹配，避免无关的 topic 被模糊匹配成图标
+fn resolve_topic<'a>(catalog: &'a Catalog, topic: &str) -> Option<&'a str> {
+    let hit = search(catalog, topic, 1).into_iter().next()?;
+    matches!(hit.kind, MatchKind::Exact | MatchKind::Alias).then_some(hit.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::http::FixtureTransport;
+
+    fn catalog() -> Catalog {
+        let names = ["rust", "typescript", "python", "docker", "postgresql", "react", "terraform", "django"];
+        let list: Vec<String> = names
+            .iter()
+            .map(|n| format!(r#"{{"name":"{0}","items":["{0}"]}}"#, n))
+            .collect();
+        Catalog::from_list_json(&format!("[{}]", list.join(","))).unwrap()
+    }
+
+    // 测试用的临时 fixture 目录
+    fn fixtures(name: &str, files: &[(String, String)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("techwall-github-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            std::fs::write(dir.join(file), content).unwrap();
+        }
+        dir
+    }
+
+    fn repo(name: &str, fork: bool) -> String {
+        format!(r#"{{"full_name":"paged/{}","fork":{},"topics":[]}}"#, name, fork)
+    }
+
+    fn recorded() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/github")
+    }
+
+    #[test]
+    fn skips_forks_by_default() {
+        let mut client = Client::new(FixtureTransport::new(recorded()));
+        let analysis = client.analyze_user("techwall-demo", &catalog()).unwrap();
+        assert!(analysis.get("rust").is_some());
+        assert!(analysis.get("docker").is_some());
+        assert!(analysis.get("postgresql").is_some());
+        // 只出现在 fork 的仓库里
+        assert!(analysis.get("python").is_none());
+        assert!(analysis.get("django").is_none());
+        assert_eq!(client.limit.remaining, Some(57));
+        assert_eq!(client.limit.reset, Some(1760000000));
+
+        let mut client = Client::new(FixtureTransport::new(recorded()));
+        client.include_forks = true;
+        let analysis = client.analyze_user("techwall-demo", &catalog()).unwrap();
+        assert!(analysis.get("python").is_some());
+        assert!(analysis.get("django").is_some());
+    }
+
+    #[test]
+    fn follows_pages() {
+        // 第一页满 100 个，只有一个不是 fork
+        let first: Vec<String> = (0..PER_PAGE).map(|i| repo(&format!("r{}", i), i != 0)).collect();
+        let dir = fixtures(
+            "pages",
+            &[
+                ("users_paged_repos_per_page_100_page_1.json".into(), format!("[{}]", first.join(","))),
+                ("users_paged_repos_per_page_100_page_2.json".into(), format!("[{}]", repo("last", false))),
+                ("repos_paged_r0_languages.json".into(), r#"{"Rust": 100}"#.into()),
+                ("repos_paged_last_languages.json".into(), r#"{"TypeScript": 300}"#.into()),
+            ],
+        );
+        let mut client = Client::new(FixtureTransport::new(&dir));
+        assert_eq!(client.repos("paged").unwrap().len(), PER_PAGE + 1);
+        let analysis = client.analyze_user("paged", &catalog()).unwrap();
+        assert_eq!(analysis.names(), ["typescript", "rust"]);
+        assert_eq!(analysis.get("rust").unwrap().bytes, 100);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn retries_after_rate_limit_reset() {
+        // 第一次 403 且配额为 0，重置时间已过，等待后重试成功
+        let dir = fixtures(
+            "retry",
+            &[
+                (
+                    "users_limited_repos_per_page_100_page_1.json".into(),
+                    r#"{"message":"API rate limit exceeded"}"#.into(),
+                ),
+                (
+                    "users_limited_repos_per_page_100_page_1.head".into(),
+                    "403\nx-ratelimit-remaining: 0\nx-ratelimit-reset: 0\n".into(),
+                ),
+                (
+                    "users_limited_repos_per_page_100_page_1@2.json".into(),
+                    format!("[{}]", repo("app", false)),
+                ),
+                (
+                    "users_limited_repos_per_page_100_page_1@2.head".into(),
+                    "200\nx-ratelimit-remaining: 4999\n".into(),
+                ),
+                ("repos_paged_app_languages.json".into(), r#"{"Rust": 10}"#.into()),
+            ],
+        );
+        let mut client = Client::new(FixtureTransport::new(&dir));
+        let analysis = client.analyze_user("limited", &catalog()).unwrap();
+        assert_eq!(analysis.names(), ["rust"]);
+        assert_eq!(client.limit.remaining, Some(4999));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn gives_up_when_reset_is_too_far() {
+        let reset = now() + 3600;
+        let dir = fixtures(
+            "exhausted",
+            &[
+                ("users_late_repos_per_page_100_page_1.json".into(), "{}".into()),
+                (
+                    "users_late_repos_per_page_100_page_1.head".into(),
+                    format!("429\nx-ratelimit-remaining: 0\nx-ratelimit-reset: {}\n", reset),
+                ),
+            ],
+        );
+        let mut client = Client::new(FixtureTransport::new(&dir));
+        let error = client.analyze_user("late", &catalog()).unwrap_err();
+        assert!(error.to_string().contains("rate limit exhausted"), "{}", error);
+        assert_eq!(client.limit.reset, Some(reset));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+//! 爬虫使用的 HTTP 传输层
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+pub struct Response {
+    pub status: u16,
+    /// 键为小写
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// 便于换成录制的 JSON 或本地 mock 服务
+pub trait Transport {
+    fn get(&mut self, url: &str, headers: &[(&str, &str)]) -> Result<Response>;
+}
+
+pub struct HttpTransport {
+    agent: ureq::Agent,
+}
+
+impl HttpTransport {
+    pub fn new() -> HttpTransport {
+        HttpTransport {
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(30)).build(),
+        }
+    }
+}
+
+impl Default for HttpTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for HttpTransport {
+    fn get(&mut self, url: &str, headers: &[(&str, &str)]) -> Result<Response> {
+        let mut request = self.agent.get(url);
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        // 4xx/5xx 也当作正常响应返回，由调用方根据状态码和限流头处理
+        let response = match request.call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(e.into()),
+        };
+        let status = response.status();
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.header(&name)?.to_string();
+                Some((name.to_lowercase(), value))
+            })
+            .collect();
+        let body = response.into_string()?;
+        Ok(Response { status, headers, body })
+    }
+}
+
+// "https://api.github.com/users/a/repos?page=1" -> "users_a_repos_page_1"，
+// 同一个 URL 的第 n (n > 1) 次请求加上 "@n"
+fn fixture_stem(url: &str, attempt: usize) -> String {
+    let path = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let path = path.split_once('/').map(|(_, rest)| rest).unwrap_or("");
+    let name: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let name = name.trim_matches('_');
+    if attempt > 1 {
+        format!("{}@{}", name, attempt)
+    } else {
+        name.to_string()
+    }
+}
+
+// `.head` 文件：第一行为状态码，其余每行一个 `name: value` 响应头
+fn parse_head(text: &str) -> Result<(u16, HashMap<String, String>)> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let status = lines.next().ok_or_else(|| anyhow!("missing status line"))?.parse()?;
+    let headers = lines
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim().to_lowercase(), value.trim().to_string()))
+        })
+        .collect();
+    Ok((status, headers))
+}
+
+// 只保留回放需要的限流头
+fn format_head(response: &Response) -> String {
+    let mut headers: Vec<_> = response
+        .headers
+        .iter()
+        .filter(|(name, _)| name.starts_with("x-ratelimit-") || *name == "retry-after")
+        .collect();
+    headers.sort();
+    let mut head = format!("{}\n", response.status);
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\n", name, value));
+    }
+    head
+}
+
+// 每个 URL 已经请求的次数
+fn next_attempt(calls: &mut HashMap<String, usize>, url: &str) -> usize {
+    let count = calls.entry(fixture_stem(url, 1)).or_insert(0);
+    *count += 1;
+    *count
+}
+
+/// 从目录读取录制好的响应，文件名由 URL 路径和查询参数生成。
+/// `<name>.json` 为响应体，可选的 `<name>.head` 记录状态码和响应头，没有时为 200；
+/// 同一个 URL 第 n 次请求时优先读取 `<name>@n.*`，用来回放限流后的重试
+pub struct FixtureTransport {
+    dir: PathBuf,
+    calls: HashMap<String, usize>,
+}
+
+impl FixtureTransport {
+    pub fn new(dir: impl Into<PathBuf>) -> FixtureTransport {
+        FixtureTransport {
+            dir: dir.into(),
+            calls: HashMap::new(),
+        }
+    }
+
+    // 第 `attempt` 次请求的文件，没有单独录制时用第一次的
+    fn path(&self, url: &str, attempt: usize, extension: &str) -> PathBuf {
+        let path = self.dir.join(format!("{}.{}", fixture_stem(url, attempt), extension));
+        if path.exists() {
+            path
+        } else {
+            self.dir.join(format!("{}.{}", fixture_stem(url, 1), extension))
+        }
+    }
+}
+
+impl Transport for FixtureTransport {
+    fn get(&mut self, url: &str, _headers: &[(&str, &str)]) -> Result<Response> {
+        let attempt = next_attempt(&mut self.calls, url);
+        let path = self.path(url, attempt, "json");
+        let body = std::fs::read_to_string(&path).map_err(|e| anyhow!("fixture {}: {}", path.display(), e))?;
+        let head = self.path(url, attempt, "head");
+        let (status, headers) = if head.exists() {
+            parse_head(&std::fs::read_to_string(&head)?).map_err(|e| anyhow!("fixture {}: {}", head.display(), e))?
+        } else {
+            (200, HashMap::new())
+        };
+        Ok(Response { status, headers, body })
+    }
+}
+
+/// 转发请求并把响应保存成 fixture，同一个 URL 的多次请求分别保存
+pub struct RecordingTransport<T: Transport> {
+    inner: T,
+    dir: PathBuf,
+    calls: HashMap<String, usize>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    pub fn new(inner: T, dir: impl Into<PathBuf>) -> RecordingTransport<T> {
+        RecordingTransport {
+            inner,
+            dir: dir.into(),
+            calls: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn get(&mut self, url: &str, headers: &[(&str, &str)]) -> Result<Response> {
+        let response = self.inner.get(url, headers)?;
+        let stem = fixture_stem(url, next_attempt(&mut self.calls, url));
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join(format!("{}.json", stem)), &response.body)?;
+        std::fs::write(self.dir.join(format!("{}.head", stem)), format_head(&response))?;
+        Ok(response)
+    }
+}
+
+/// 同 JS 的 `encodeURIComponent`
+pub fn encode(text: &str) -> String {
+    let mut rs = String::new();
+    for b in text.bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&b) {
+            rs.push(b as char);
+        } else {
+            rs.push_str(&format!("%{:02X}", b));
+        }
+    }
+    rs
+}
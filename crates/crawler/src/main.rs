@@ -1,9 +1,38 @@
-use std::{fs::{self}, io::Write};
+use std::{fs::{self}, io::{Read, Write}};
 
-use anyhow::{Ok, Result};
+use anyhow::{bail, Ok, Result};
 use flate2::Compression;
-use serde::Serialize;
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
+use github::Client;
+use http::{FixtureTransport, HttpTransport, RecordingTransport, Transport};
+use popularity::{GithubTopics, Limiter, Overrides, Pipeline, ScoreSource, StackTags};
+use techwall::analyze::Analysis;
+use techwall::background::{Background, Fit};
+use techwall::badge::{Badge, Badges, Style as BadgeStyle};
+use techwall::animate::encode_gif;
+use techwall::banner::{Arrange, Banner};
+use techwall::catalog::{Catalog, Category};
+use techwall::compose::Source;
+use techwall::container::{Container, Style};
+use techwall::frame::{Insets, Region};
+use techwall::import;
+use techwall::mask::ShapeMask;
+use techwall::meta::{Meta, MetaData};
+use techwall::optimize::{Objective, Optimize};
+use techwall::orbit::RingBy;
+use techwall::overlay::{Avatar, Outline, Text};
+use techwall::png::is_monochrome;
+use techwall::recolor::Recolor;
+use techwall::resolve::{Preference, Resolver, Tone};
+use techwall::search::search;
+use techwall::shelves::ShelfPack;
+use techwall::sizing::{Curve, Sizing};
+
+mod github;
+mod http;
+mod popularity;
+
 #[warn(dead_code)]
 fn get_path(name: &str) -> String {
     let home = std::env::home_dir().unwrap().display().to_string();
@@ -16,39 +45,739 @@ fn get_svg_bytes(name: &str) -> Result<Vec<u8>> {
     Ok(content)
 }
 
-#[derive(Serialize)]
-pub struct Point {
-    x: u32,
-    y: u32,
+fn resource_dir() -> String {
+    get_path("repo/techwall/front/public/resource")
+}
+
+// 前端自带的背景图按名字查找，例如 "blocks"、"textile-bg"，其余当作路径
+fn background_path(name: &str) -> String {
+    let builtin = get_path(&format!("repo/techwall/front/public/background/{}.jpg", name));
+    if !name.contains(['/', '.']) && fs::exists(&builtin).unwrap_or(false) {
+        return builtin;
+    }
+    name.to_string()
+}
+
+fn list_json_path() -> String {
+    get_path("repo/techwall/crawler/list.json")
+}
+
+fn load_meta() -> Result<MetaData> {
+    let content = std::fs::read(format!("{}/meta", resource_dir()))?;
+    let mut decoder = GzDecoder::new(content.as_slice());
+    let mut json = String::new();
+    decoder.read_to_string(&mut json)?;
+    let mut meta_data: MetaData = serde_json::from_str(&json)?;
+    // 旧的 meta 没有打包目录，直接读爬虫的结果
+    let list_json = list_json_path();
+    if meta_data.catalog.entries.is_empty() && fs::exists(&list_json)? {
+        meta_data.catalog = Catalog::from_list_json(&fs::read_to_string(&list_json)?)?;
+    }
+    meta_data.merge(load_user_meta()?);
+    Ok(meta_data)
+}
+
+// 用户导入的图标，和内置的 meta 放在同一目录
+fn user_meta_path() -> String {
+    format!("{}/user-meta.json", resource_dir())
+}
+
+fn load_user_meta() -> Result<MetaData> {
+    let path = user_meta_path();
+    if !fs::exists(&path)? {
+        return Ok(MetaData::default());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(&path)?)?)
+}
+
+// 取 `--name value` 形式的参数
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let index = args.iter().position(|a| a == name)?;
+    args.get(index + 1).map(|s| s.as_str())
+}
+
+// 不带值的开关参数，其余 `--name` 都会取下一个参数作为值
+const SWITCHES: &[&str] = &["--repeat", "--labels", "--percent", "--metrics", "--force", "--forks", "--rotate"];
+
+// 是否带了开关参数，新的开关需要加进 `SWITCHES`
+fn switch(args: &[String], name: &str) -> bool {
+    debug_assert!(SWITCHES.contains(&name), "{} is not in SWITCHES", name);
+    args.iter().any(|a| a == name)
+}
+
+// 去掉 `--name value` 和开关之后剩下的参数
+fn positional(args: &[String]) -> Vec<&str> {
+    let mut rs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg.starts_with("--") {
+            if !SWITCHES.contains(&arg.as_str()) {
+                iter.next();
+            }
+            continue;
+        }
+        rs.push(arg.as_str());
+    }
+    rs
+}
+
+/// `--overlays` 文件的条目：
+/// `[{"type": "text", "text": "Jane Doe", "x": 40, "y": 40, "size": 48},
+///   {"type": "avatar", "image": "me.jpg", "x": 1000, "y": 40, "size": 160}]`
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum OverlaySpec {
+    Text(Text),
+    Avatar {
+        /// 图片路径
+        image: String,
+        #[serde(flatten)]
+        avatar: Avatar,
+    },
+}
+
+/// banner <output.png|output.svg> <name>... [--width 1200] [--height 600] [--background #ffffff] [--arrange pile|grid|shape|treemap|hex|orbit|shelves|spiral|tile] [--seed 0]
+// 从命令行参数创建 Banner：--width --height --background --arrange --seed
+// --background-image blocks|path.jpg --fit cover|contain|tile --blur 0
+// --insets 24,32 --radius 48 --keep-out x,y,w,h;x,y,w,h
+// --font a.ttf,b.otf --overlays overlays.json
+// --shape heart|text:JD|path:M0 0 L100 0 L50 80 Z|mask.svg|mask.png --repeat
+// --optimize all|empty|balance|gaps|color --iterations 2000 在排布结果上优化，--rotate 同时旋转图标，--metrics 输出质量指标
+// --arrange tile --repeat：无缝平铺的壁纸，--repeat 循环使用图标直到填满
+// --container jar|path:M0 0 L100 0 L100 100 L0 100 Z|jar.svg --container-fill #ffffff22 --container-stroke #ffffff:4
+// --recolor --sizing --min-scale --max-scale
+// --arrange hex --hex-fill #ffffff|dominant|tint --hex-border #333333:3 --hex-radius 80 --hex-gap 6 --hex-padding 0.2
+// --arrange orbit --orbit-by weight|group --orbit-rings 3 --orbit-center 0.4，输出 .gif 时 --frames 48 --delay 60
+// --arrange shelves --shelf-pack grid|pile --heading-size 20 --heading-color #333333 --shelf-gap 12 --divider #cccccc:1
+// --arrange treemap --cell-fill #ffffff22 --cell-gap 4 --cell-radius 8 --label-color #ffffff --labels --percent
+fn banner_options(args: &[String]) -> Result<Banner> {
+    let width = flag(args, "--width").unwrap_or("1200").parse()?;
+    let height = flag(args, "--height").unwrap_or("600").parse()?;
+    let mut banner = Banner::new(width, height);
+    if let Some(bg) = flag(args, "--background") {
+        let Some(background) = Background::parse(bg) else {
+            bail!("invalid background: {}", bg);
+        };
+        banner.background = Some(background);
+    }
+    if let Some(image) = flag(args, "--background-image") {
+        let fit = flag(args, "--fit").unwrap_or("cover");
+        let Some(fit) = Fit::parse(fit) else {
+            bail!("invalid fit: {}", fit);
+        };
+        let blur = flag(args, "--blur").unwrap_or("0").parse()?;
+        banner.background = Some(Background::image(&fs::read(background_path(image))?, fit, blur)?);
+    }
+    if let Some(arrange) = flag(args, "--arrange") {
+        let Some(arrange) = Arrange::parse(arrange) else {
+            bail!("invalid arrange: {}", arrange);
+        };
+        banner.arrange = arrange;
+    }
+    if let Some(seed) = flag(args, "--seed") {
+        banner.seed = seed.parse()?;
+    }
+    if let Some(insets) = flag(args, "--insets") {
+        let Some(insets) = Insets::parse(insets) else {
+            bail!("invalid insets: {}", insets);
+        };
+        banner.frame.insets = insets;
+    }
+    if let Some(radius) = flag(args, "--radius") {
+        banner.frame.radius = radius.parse()?;
+    }
+    for region in flag(args, "--keep-out").unwrap_or("").split(';').filter(|r| !r.is_empty()) {
+        let Some(region) = Region::parse(region) else {
+            bail!("invalid keep-out region: {}", region);
+        };
+        banner.frame.keep_out.push(region);
+    }
+    for font in flag(args, "--font").unwrap_or("").split(',').filter(|f| !f.is_empty()) {
+        banner.load_font(fs::read(font)?);
+    }
+    if let Some(path) = flag(args, "--overlays") {
+        let overlays: Vec<OverlaySpec> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        for overlay in overlays {
+            match overlay {
+                OverlaySpec::Text(text) => banner.add_text(&text)?,
+                OverlaySpec::Avatar { image, avatar } => banner.add_avatar(&avatar, &fs::read(image)?)?,
+            }
+        }
+    }
+    if let Some(spec) = flag(args, "--shape") {
+        banner.shape = Some(shape(&mut banner, spec)?);
+        if flag(args, "--arrange").is_none() {
+            banner.arrange = Arrange::Shape;
+        }
+    }
+    if let Some(spec) = flag(args, "--container") {
+        let mut style = Style {
+            fill: flag(args, "--container-fill").map(|c| c.to_string()),
+            stroke: None,
+        };
+        if let Some(stroke) = flag(args, "--container-stroke") {
+            let (color, width) = stroke.split_once(':').unwrap_or((stroke, "2"));
+            style.stroke = Some(Outline {
+                color: color.to_string(),
+                width: width.parse()?,
+            });
+        }
+        banner.container = Some(container(&banner, spec, &style)?);
+    }
+    banner.repeat = switch(args, "--repeat");
+    banner.treemap.fill = flag(args, "--cell-fill").map(|c| c.to_string());
+    if let Some(gap) = flag(args, "--cell-gap") {
+        banner.treemap.gap = gap.parse()?;
+    }
+    if let Some(radius) = flag(args, "--cell-radius") {
+        banner.treemap.radius = radius.parse()?;
+    }
+    if let Some(color) = flag(args, "--label-color") {
+        banner.treemap.color = color.to_string();
+    }
+    banner.hex.fill = flag(args, "--hex-fill").map(|c| c.to_string());
+    if let Some(border) = flag(args, "--hex-border") {
+        let (color, width) = border.split_once(':').unwrap_or((border, "2"));
+        banner.hex.border = Some(Outline {
+            color: color.to_string(),
+            width: width.parse()?,
+        });
+    }
+    if let Some(radius) = flag(args, "--hex-radius") {
+        banner.hex.radius = Some(radius.parse()?);
+    }
+    if let Some(gap) = flag(args, "--hex-gap") {
+        banner.hex.gap = gap.parse()?;
+    }
+    if let Some(padding) = flag(args, "--hex-padding") {
+        banner.hex.padding = padding.parse()?;
+    }
+    if let Some(by) = flag(args, "--orbit-by") {
+        let Some(by) = RingBy::parse(by) else {
+            bail!("invalid orbit grouping: {}", by);
+        };
+        banner.orbit.by = by;
+    }
+    if let Some(rings) = flag(args, "--orbit-rings") {
+        banner.orbit.rings = rings.parse()?;
+    }
+    if let Some(center) = flag(args, "--orbit-center") {
+        banner.orbit.center = center.parse()?;
+    }
+    if let Some(pack) = flag(args, "--shelf-pack") {
+        let Some(pack) = ShelfPack::parse(pack) else {
+            bail!("invalid shelf packing: {}", pack);
+        };
+        banner.shelves.pack = pack;
+    }
+    if let Some(size) = flag(args, "--heading-size") {
+        banner.shelves.size = size.parse()?;
+    }
+    if let Some(color) = flag(args, "--heading-color") {
+        banner.shelves.color = color.to_string();
+    }
+    if let Some(gap) = flag(args, "--shelf-gap") {
+        banner.shelves.gap = gap.parse()?;
+    }
+    if let Some(divider) = flag(args, "--divider") {
+        let (color, width) = divider.split_once(':').unwrap_or((divider, "1"));
+        banner.shelves.divider = Some(Outline {
+            color: color.to_string(),
+            width: width.parse()?,
+        });
+    }
+    banner.treemap.label = switch(args, "--labels");
+    banner.treemap.percent = switch(args, "--percent");
+    if let Some(spec) = flag(args, "--recolor") {
+        let Some(recolor) = Recolor::parse(spec) else {
+            bail!("invalid recolor: {}", spec);
+        };
+        banner.recolor = recolor;
+    }
+    if let Some(curve) = flag(args, "--sizing") {
+        let Some(curve) = Curve::parse(curve) else {
+            bail!("invalid sizing: {}", curve);
+        };
+        let mut sizing = Sizing::new(curve);
+        if let Some(min) = flag(args, "--min-scale") {
+            sizing.min = min.parse()?;
+        }
+        if let Some(max) = flag(args, "--max-scale") {
+            sizing.max = max.parse()?;
+        }
+        banner.sizing = Some(sizing);
+    }
+    Ok(banner)
+}
+
+// 内置形状、文字、SVG 路径或图片文件
+fn shape(banner: &mut Banner, spec: &str) -> Result<ShapeMask> {
+    let (width, height) = (banner.width, banner.height);
+    if let Some(mask) = ShapeMask::builtin(spec, width, height) {
+        return Ok(mask);
+    }
+    if let Some(text) = spec.strip_prefix("text:") {
+        return banner.text_shape(text);
+    }
+    if let Some(d) = spec.strip_prefix("path:") {
+        return ShapeMask::from_path(d, width, height);
+    }
+    if !fs::exists(spec)? {
+        let names: Vec<&str> = ShapeMask::builtin_names().collect();
+        bail!("invalid shape: {}, expected {}, text:, path: or a file", spec, names.join(", "));
+    }
+    let data = fs::read(spec)?;
+    if spec.ends_with(".svg") {
+        return ShapeMask::from_svg(&data, width, height);
+    }
+    ShapeMask::from_image(&data, width, height)
 }
 
-impl From<(u32, u32)> for Point {
-    fn from((x, y): (u32, u32)) -> Self {
-        Point { x, y }
+// 内置容器、SVG 路径或 SVG 文件
+fn container(banner: &Banner, spec: &str, style: &Style) -> Result<Container> {
+    let (width, height) = (banner.width, banner.height);
+    if let Some(container) = Container::builtin(spec, style, width, height) {
+        return container;
     }
+    if let Some(d) = spec.strip_prefix("path:") {
+        return Container::from_path(d, style, width, height);
+    }
+    if !fs::exists(spec)? {
+        let names: Vec<&str> = Container::builtin_names().collect();
+        bail!("invalid container: {}, expected {}, path: or an SVG file", spec, names.join(", "));
+    }
+    Container::from_svg(&fs::read(spec)?, width, height)
 }
-#[derive(Serialize)]
-pub struct Meta {
-    name: String,
-    width: u32,
-    height: u32,
-    scale: f32,
-    polygon: Vec<Point>,
+
+// 变体偏好：--prefer symbol,mono,square --variant docker=docker-icon,angular=angular
+fn preference(args: &[String], meta_data: &mut MetaData, techs: &[&str]) -> Result<Preference> {
+    let rules = flag(args, "--prefer").unwrap_or("");
+    let Some(mut preference) = Preference::parse(rules) else {
+        bail!("invalid preference: {}", rules);
+    };
+    for pair in flag(args, "--variant").unwrap_or("").split(',').filter(|p| !p.is_empty()) {
+        let Some((tech, icon)) = pair.split_once('=') else {
+            bail!("invalid variant: {}", pair);
+        };
+        preference.overrides.insert(tech.to_string(), icon.to_string());
+    }
+    // 旧的 meta 没有单色标记，只给这次要比较的图标从 PNG 计算
+    if preference.tone != Tone::Any {
+        let mut resolver = Resolver::new(&meta_data.catalog, meta_data);
+        resolver.preference = preference.clone();
+        let candidates: Vec<String> = techs
+            .iter()
+            .flat_map(|tech| resolver.candidates(tech))
+            .map(str::to_string)
+            .collect();
+        let pending = |m: &Meta| m.mono.is_none() && candidates.contains(&m.name);
+        for meta in meta_data.items.iter_mut().filter(|m| pending(m)) {
+            let Some(data) = std::fs::read(format!("{}/{}.png", resource_dir(), meta.name)).ok() else {
+                continue;
+            };
+            let pixmap = resvg::tiny_skia::Pixmap::decode_png(&data)?;
+            meta.mono = Some(is_monochrome(pixmap.data()));
+        }
+    }
+    Ok(preference)
 }
 
-#[derive(Serialize)]
-pub struct MetaData {
-    items: Vec<Meta>,
+// 导入的矢量图标用清理后的 SVG，缩放和改色都不失真
+fn icon_source(meta: &Meta) -> Result<Source> {
+    match &meta.svg {
+        Some(file) => Source::from_svg(&fs::read(format!("{}/{}", resource_dir(), file))?),
+        None => Source::from_png(&fs::read(format!("{}/{}.png", resource_dir(), meta.name))?),
+    }
 }
-fn main() {
 
-    let target = get_path("repo/techwall/front/public/resource");
+fn write_banner(
+    mut banner: Banner,
+    output: &str,
+    names: &[(&str, Option<f32>)],
+    meta_data: &MetaData,
+    args: &[String],
+) -> Result<()> {
+    for &(name, weight) in names {
+        let Some(meta) = meta_data.find(name) else {
+            bail!("icon not found: {}", name);
+        };
+        banner.add(meta.clone(), icon_source(meta)?, 1.0);
+        if let Some(weight) = weight {
+            banner.set_weight(name, weight);
+        }
+        if let Some(entry) = meta_data.catalog.by_icon(name) {
+            banner.set_label(name, &entry.display);
+            banner.set_group(name, entry.category.name());
+            banner.set_popularity(name, entry.popularity);
+        }
+    }
+    // 环绕排布的轨道旋转一周，输出循环播放的 GIF
+    if output.ends_with(".gif") {
+        if banner.arrange != Arrange::Orbit {
+            bail!("animated output requires --arrange orbit");
+        }
+        let count = flag(args, "--frames").unwrap_or("48").parse()?;
+        let delay = flag(args, "--delay").unwrap_or("60").parse()?;
+        let frames: Vec<_> = banner.orbit_frames(count).iter().map(|p| banner.compose(p)).collect();
+        std::fs::write(output, encode_gif(&frames, delay)?)?;
+        println!("wrote {} frames", frames.len());
+        return Ok(());
+    }
+    let mut placements = banner.layout();
+    if switch(args, "--metrics") {
+        println!("metrics: {}", serde_json::to_string(&banner.metrics(&placements))?);
+    }
+    if let Some(objective) = flag(args, "--optimize") {
+        let Some(objective) = Objective::parse(objective) else {
+            bail!("invalid objective: {}", objective);
+        };
+        let mut config = Optimize {
+            objective,
+            ..Default::default()
+        };
+        if let Some(iterations) = flag(args, "--iterations") {
+            config.iterations = iterations.parse()?;
+        }
+        config.rotate = switch(args, "--rotate");
+        let (optimized, before, after) = banner.optimize(&placements, &config)?;
+        println!("score: {:.4} -> {:.4}", before, after);
+        placements = optimized;
+        if switch(args, "--metrics") {
+            println!("metrics: {}", serde_json::to_string(&banner.metrics(&placements))?);
+        }
+    }
+    let compositor = banner.compose(&placements);
+    if output.ends_with(".svg") {
+        std::fs::write(output, compositor.to_svg()?)?;
+    } else {
+        std::fs::write(output, compositor.to_png()?)?;
+    }
+    // 重复填充时同一图标会出现多次
+    let mut distinct: Vec<&str> = placements.iter().map(|p| p.name.as_str()).collect();
+    distinct.sort();
+    distinct.dedup();
+    if distinct.len() < placements.len() {
+        println!("placed {}/{} icons, {} items in total", distinct.len(), banner.len(), placements.len());
+    } else {
+        println!("placed {}/{} icons", placements.len(), banner.len());
+    }
+    Ok(())
+}
+
+fn banner(args: &[String]) -> Result<()> {
+    let rest = positional(args);
+    let Some((output, names)) = rest.split_first() else {
+        bail!("usage: crawler banner <output> <name[:weight]>...");
+    };
+    let banner = banner_options(args)?;
+    let mut meta_data = load_meta()?;
+    let techs: Vec<&str> = names.iter().map(|n| n.split_once(':').map_or(*n, |(n, _)| n)).collect();
+    let preference = preference(args, &mut meta_data, &techs)?;
+    let mut resolver = Resolver::new(&meta_data.catalog, &meta_data);
+    resolver.preference = preference;
+    // 名字可以是技术名或别名，后面可以带权重，例如 "rust:0.7"
+    let mut weighted = Vec::new();
+    for name in names {
+        let (name, weight) = match name.split_once(':') {
+            Some((name, weight)) => (name, Some(weight.parse()?)),
+            None => (*name, None),
+        };
+        let Some(icon) = resolver.resolve(name) else {
+            bail!("icon not found: {}", name);
+        };
+        weighted.push((icon, weight));
+    }
+    write_banner(banner, output, &weighted, &meta_data, args)
+}
+
+/// badges <output.svg|output.png|dir/{name}.svg> <name>... [--style flat|flat-square|for-the-badge] [--max-width 800] [--gap 4] [--scale 2] [--font a.ttf]
+/// 输出路径包含 `{name}` 时每个徽章单独一个文件
+fn badges(args: &[String]) -> Result<()> {
+    let rest = positional(args);
+    let Some((output, names)) = rest.split_first() else {
+        bail!("usage: crawler badges <output> <name>...");
+    };
+    let style = flag(args, "--style").unwrap_or("flat");
+    let Some(style) = BadgeStyle::parse(style) else {
+        bail!("invalid badge style: {}", style);
+    };
+    let mut badges = Badges::new(style);
+    badges.max_width = flag(args, "--max-width").unwrap_or("0").parse()?;
+    if let Some(gap) = flag(args, "--gap") {
+        badges.gap = gap.parse()?;
+    }
+    badges.scale = flag(args, "--scale").unwrap_or("1").parse()?;
+    for font in flag(args, "--font").unwrap_or("").split(',').filter(|f| !f.is_empty()) {
+        badges.load_font(fs::read(font)?);
+    }
+    let mut meta_data = load_meta()?;
+    let preference = preference(args, &mut meta_data, names)?;
+    let mut resolver = Resolver::new(&meta_data.catalog, &meta_data);
+    resolver.preference = preference;
+    for name in names {
+        let Some(icon) = resolver.resolve(name) else {
+            bail!("icon not found: {}", name);
+        };
+        let label = resolver.entry(name).map_or(*name, |e| e.display.as_str());
+        let Some(meta) = meta_data.find(icon) else {
+            bail!("icon not found: {}", icon);
+        };
+        badges.push(Badge::new(icon, label, icon_source(meta)?));
+    }
+    let write = |path: &str, compositor: techwall::compose::Compositor| -> Result<()> {
+        if path.ends_with(".svg") {
+            fs::write(path, compositor.to_svg()?)?;
+        } else {
+            fs::write(path, compositor.to_png()?)?;
+        }
+        Ok(())
+    };
+    if output.contains("{name}") {
+        let names: Vec<String> = badges.items.iter().map(|b| b.name.clone()).collect();
+        for (name, compositor) in names.iter().zip(badges.compose_each()?) {
+            write(&output.replace("{name}", name), compositor)?;
+        }
+        println!("wrote {} badges", names.len());
+    } else {
+        write(output, badges.compose()?)?;
+    }
+    Ok(())
+}
+
+/// resolve <tech>... [--prefer symbol,mono,square] [--variant docker=docker-icon]
+fn resolve(args: &[String]) -> Result<()> {
+    let mut meta_data = load_meta()?;
+    let techs = positional(args);
+    let preference = preference(args, &mut meta_data, &techs)?;
+    let mut resolver = Resolver::new(&meta_data.catalog, &meta_data);
+    resolver.preference = preference;
+    for tech in techs {
+        println!("{}\t{}", tech, resolver.resolve(tech).unwrap_or("-"));
+    }
+    Ok(())
+}
+
+/// import <file.svg|file.png> [--name acme] [--display "Acme"] [--category tool] [--aliases a,b] [--force]
+fn import(args: &[String]) -> Result<()> {
+    let rest = positional(args);
+    let Some(file) = rest.first() else {
+        bail!("usage: crawler import <file> [--name name] [--display name] [--category category]");
+    };
+    let path = std::path::Path::new(file);
+    let name = match flag(args, "--name") {
+        Some(name) => name.to_string(),
+        None => path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase().replace(' ', "-"))
+            .unwrap_or_default(),
+    };
+    let category = match flag(args, "--category") {
+        Some(c) => Category::parse(c).ok_or_else(|| anyhow::anyhow!("invalid category: {}", c))?,
+        None => Category::Other,
+    };
+    let aliases = flag(args, "--aliases")
+        .map(|a| a.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    let mut user = load_user_meta()?;
+    // 不小心覆盖内置图标时提示
+    if user.find(&name).is_none() && load_meta()?.find(&name).is_some() && !switch(args, "--force") {
+        bail!("{} is a built-in icon, use --force to replace it", name);
+    }
+    let imported = import::import(&name, &fs::read(path)?, &import::Limits::default())?;
+    fs::write(format!("{}/{}.png", resource_dir(), name), &imported.png)?;
+    if let (Some(svg), Some(file)) = (&imported.svg, &imported.meta.svg) {
+        fs::write(format!("{}/{}", resource_dir(), file), svg)?;
+    }
+    let entry = import::entry(&name, flag(args, "--display"), category, aliases);
+    import::add_to(&mut user, &imported, entry);
+    fs::write(user_meta_path(), serde_json::to_string(&user)?)?;
+    println!("imported {} ({}x{})", name, imported.meta.width, imported.meta.height);
+    Ok(())
+}
+
+/// list [query] [--limit 20] [--category language|framework|database|cloud|tool|other]
+fn list(args: &[String]) -> Result<()> {
+    let limit = flag(args, "--limit").unwrap_or("20").parse()?;
+    let category = match flag(args, "--category") {
+        Some(name) => {
+            let Some(category) = Category::parse(name) else {
+                bail!("invalid category: {}", name);
+            };
+            Some(category)
+        }
+        None => None,
+    };
+    let meta_data = load_meta()?;
+    let catalog = &meta_data.catalog;
+    let query = positional(args).join(" ");
+    if query.is_empty() {
+        let entries = catalog
+            .entries
+            .iter()
+            .filter(|e| category.is_none_or(|c| e.category == c))
+            .take(limit);
+        for e in entries {
+            println!("{}\t{}\t{}\t{}", e.primary().unwrap_or(&e.name), e.display, e.category.name(), e.score);
+        }
+    } else {
+        let hits = search(catalog, &query, usize::MAX)
+            .into_iter()
+            .filter(|h| category.is_none_or(|c| h.category == c))
+            .take(limit);
+        for h in hits {
+            println!("{}\t{}\t{}\t{}\t{:.2}", h.icon, h.display, h.category.name(), h.score, h.quality);
+        }
+    }
+    Ok(())
+}
+
+// 打印分析结果，带 --output 时用前 --count 个技术生成 banner
+fn report(analysis: &Analysis, args: &[String]) -> Result<()> {
+    let limit = flag(args, "--limit").unwrap_or("30").parse()?;
+    if flag(args, "--format") == Some("json") {
+        println!("{}", serde_json::to_string_pretty(analysis)?);
+    } else {
+        for t in analysis.technologies.iter().take(limit) {
+            println!("{}\t{:.3}\t{}\t{}\t{}", t.name, t.weight, t.bytes, t.mentions, t.evidence.join(","));
+        }
+    }
+    let Some(output) = flag(args, "--output") else {
+        return Ok(());
+    };
+    let count = flag(args, "--count").unwrap_or("20").parse()?;
+    let mut meta_data = load_meta()?;
+    let techs: Vec<&str> = analysis.technologies.iter().map(|t| t.name.as_str()).collect();
+    let preference = preference(args, &mut meta_data, &techs)?;
+    let mut resolver = Resolver::new(&meta_data.catalog, &meta_data);
+    resolver.preference = preference;
+    // 技术名换成具体的图标，没有图标的跳过
+    let mut names: Vec<(&str, Option<f32>)> = Vec::new();
+    for t in &analysis.technologies {
+        let Some(icon) = resolver.resolve(&t.name) else {
+            continue;
+        };
+        if names.len() < count && !names.iter().any(|(n, _)| *n == icon) {
+            names.push((icon, Some(t.weight)));
+        }
+    }
+    let mut banner = banner_options(args)?;
+    banner.sizing.get_or_insert_with(Sizing::default);
+    write_banner(banner, output, &names, &meta_data, args)
+}
+
+fn analyze(args: &[String]) -> Result<()> {
+    let rest = positional(args);
+    let path = rest.first().copied().unwrap_or(".");
+    let analysis = techwall::analyze::analyze(std::path::Path::new(path))?;
+    report(&analysis, args)
+}
+
+fn analyze_github<T: Transport>(client: Client<T>, user: &str, args: &[String], catalog: &Catalog) -> Result<Analysis> {
+    let mut client = client.with_token(std::env::var("GITHUB_PAT").ok());
+    if let Some(base) = flag(args, "--base") {
+        client = client.with_base(base);
+    }
+    client.include_forks = switch(args, "--forks");
+    let analysis = client.analyze_user(user, catalog)?;
+    if let Some(remaining) = client.limit.remaining {
+        eprintln!("API calls remaining: {}", remaining);
+    }
+    Ok(analysis)
+}
+
+fn github(args: &[String]) -> Result<()> {
+    let rest = positional(args);
+    let Some(user) = rest.first() else {
+        bail!("usage: crawler github <user> [--fixtures dir | --record dir] [--base url] [--output banner.png]");
+    };
+    let catalog = load_meta()?.catalog;
+    let analysis = if let Some(dir) = flag(args, "--fixtures") {
+        analyze_github(Client::new(FixtureTransport::new(dir)), user, args, &catalog)?
+    } else if let Some(dir) = flag(args, "--record") {
+        let transport = RecordingTransport::new(HttpTransport::new(), dir);
+        analyze_github(Client::new(transport), user, args, &catalog)?
+    } else {
+        analyze_github(Client::new(HttpTransport::new()), user, args, &catalog)?
+    };
+    report(&analysis, args)
+}
+fn score_source(name: &str, args: &[String]) -> Result<Box<dyn ScoreSource>> {
+    let fixtures = flag(args, "--fixtures");
+    let token = std::env::var("GITHUB_PAT").ok();
+    let source: Box<dyn ScoreSource> = match (name, fixtures) {
+        ("github", Some(dir)) => Box::new(GithubTopics::new(Client::new(FixtureTransport::new(dir)))),
+        ("github", None) => Box::new(GithubTopics::new(Client::new(HttpTransport::new()).with_token(token))),
+        ("stack", Some(dir)) => Box::new(StackTags::new(FixtureTransport::new(dir))),
+        ("stack", None) => Box::new(StackTags::new(HttpTransport::new())),
+        _ => bail!("unknown source: {}", name),
+    };
+    Ok(source)
+}
+
+// 补齐 list.json 中各技术的热度数据，可随时中断后重新运行
+fn popularity(args: &[String]) -> Result<()> {
+    let list = flag(args, "--list").map(|s| s.to_string()).unwrap_or_else(list_json_path);
+    let state = flag(args, "--state")
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| get_path("repo/techwall/crawler/ratelimit.json"));
+    let interval = flag(args, "--interval").unwrap_or("3000").parse()?;
+    let limit = flag(args, "--limit").map(|s| s.parse()).transpose()?.unwrap_or(usize::MAX);
+    let mut sources = Vec::new();
+    for name in flag(args, "--sources").unwrap_or("github").split(',') {
+        sources.push(score_source(name.trim(), args)?);
+    }
+    let overrides = match flag(args, "--override") {
+        Some(path) => Overrides::load(std::path::Path::new(path))?,
+        None => Overrides::default(),
+    };
+    let mut weights = std::collections::HashMap::new();
+    for pair in flag(args, "--weights").unwrap_or("").split(',').filter(|p| !p.is_empty()) {
+        let Some((key, weight)) = pair.split_once('=') else {
+            bail!("invalid weight: {}", pair);
+        };
+        weights.insert(key.to_string(), weight.parse()?);
+    }
+    let mut pipeline = Pipeline {
+        sources,
+        overrides,
+        limiter: Limiter::load(state, std::time::Duration::from_millis(interval))?,
+        weights,
+    };
+    pipeline.run(std::path::Path::new(&list), limit)
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(|s| s.as_str()) {
+        Some("banner") => banner(&args[1..]),
+        Some("badges") => badges(&args[1..]),
+        Some("list") => list(&args[1..]),
+        Some("resolve") => resolve(&args[1..]),
+        Some("import") => import(&args[1..]),
+        Some("analyze") => analyze(&args[1..]),
+        Some("github") => github(&args[1..]),
+        Some("popularity") => popularity(&args[1..]),
+        None | Some("build") => {
+            build();
+            Ok(())
+        }
+        Some(cmd) => bail!("unknown command: {}", cmd),
+    }
+}
+
+fn build() {
+
+    let target = resource_dir();
     let list = ["javascript", "python", "react", "nodejs-icon", "nodejs", "java", "typescript-icon", "typescript", "docker-icon", "docker", "android-icon", "android", "php", "mongodb-icon", "mongodb", "nextjs-icon", "nextjs", "tailwindcss-icon", "tailwindcss", "express", "mysql", "bootstrap", "c", "django-logo", "django", "redux", "go", "rust", "flutter", "angular-icon", "angular", "firebase-logo", "firebase", "postgresql-logo", "postgresql", "vue", "kotlin", "git-icon", "git", "flask", "ios", "laravel", "swift", "aws", "github-icon", "github", "json", "jquery-icon", "jquery", "dart", "arduino", "pytorch", "sass", "dotnet", "ruby", "tensorflow", "pandas-icon", "pandas-logo", "kubernetes", "vite", "discord-logo", "discord", "unity", "graphql", "webpack-logo", "webpack", "react-router", "spring-logo", "spring", "material-ui", "opencv", "bash-logo", "bash", "terraform-logo", "terraform", "numpy-icon", "numpy-logo", "redis", "wordpress-icon", "wordpress", "ethereum", "markdown", "ansible", "npm-logo", "npm", "macOS", "jamstack-icon", "jamstack", "maven", "jest", "raspberry-pi", "stackbit-icon", "stackbit", "azure-icon", "azure", "sqlite", "es6", "heroku-icon", "heroku", "lua", "nestjs", "eslint", "gatsby-logo", "gatsby", "scala", "electron", "selenium", "telegram", "nginx-icon", "nginx", "prisma", "vercel-logo", "vercel", "websocket", "terminal", "serverless", "opengl", "vim", "youtube-logo", "youtube", "rails", "expo-logo", "expo", "kafka-icon", "kafka", "rest", "svelte-icon", "svelte", "jekyll", "bitcoin", "gradle", "c-sharp", "twitter", "swagger", "netlify", "threejs", "haskell-icon", "haskell", "neovim", "ubuntu", "elasticsearch", "figma", "hugo-icon", "hugo", "babel", "hibernate", "rabbitmq-logo", "rabbitmq", "spark", "gulp", "qt", "svg", "assembly", "sequelize", "awesome", "symfony", "prettier", "prometheus", "component", "nuxt-icon", "nuxt", "chrome", "opensource", "stripe", "jenkins", "webgl", "deno", "visual-studio", "slack-icon", "slack", "ffmpeg-icon", "ffmpeg-logo", "emacs", "julia", "instagram-icon", "instagram", "tableau-icon", "tableau", "postman", "clojure", "objective-c", "webrtc", "grafana", "storybook-icon", "storybook", "composer", "debian", "nodemon", "uikit", "ionic-logo", "ionic", "jupyter", "webassembly", "yarn", "vagrant-logo", "vagrant", "cypress", "bluetooth", "facebook-logo", "facebook", "perl", "puppeteer", "oracle", "udacity", "d3", "apache", "cloudinary", "handlebars", "cloudflare", "xamarin", "apple", "firefox", "mariadb-icon", "mariadb", "wifi", "font-awesome", "redux-saga", "archlinux", "twitch-logo", "twitch", "visual-studio-code", "mern", "coursera", "reddit-icon", "reddit", "blender", "mocha", "whatsapp", "gsap", "travis-ci", "steam", "pug", "homebrew", "elm", "hadoop", "atom-logo", "atom", "fastify-icon", "fastify", "ant-design", "flow", "postcss", "gitlab", "oauth", "codeigniter-logo", "codeigniter", "solid", "hexo", "erlang", "wechat-logo", "wechat", "fabric", "compose", "cucumber", "passport", "hack", "delphi", "formik", "ocaml", "neo4j", "eclipse-logo", "eclipse", "cocoapods", "slides", "batch", "mobx", "salesforce", "cordova", "bulma", "airflow", "circleci", "less", "udemy-logo", "udemy", "vulkan", "mapbox-logo", "mapbox", "wikipedia", "drupal-icon", "drupal", "auth0", "magento", "openshift", "koa", "sanity", "chai", "twilio-logo", "twilio", "influxdb", "fsharp", "webcomponents", "bem", "phoenix", "rsa", "kibana", "rollup", "deploy", "contentful", "semantic-ui", "jira", "cassandra", "drone-logo", "drone", "packer", "woocommerce-logo", "woocommerce", "crystal", "platformio", "netflix-icon", "netflix", "preact", "tomcat", "loader", "letsencrypt", "strapi-logo", "strapi", "mdx", "gnome", "vault-logo", "vault", "webdev-icon", "webdev", "centos-icon", "centos", "shopify", "adobe", "netbeans", "parse", "tor", "quarkus-icon", "quarkus", "materializecss", "stylelint", "linkedin-icon", "linkedin", "akka", "gunicorn", "meteor-icon", "meteor", "gcc", "lodash", "consul", "fedora", "puppet-icon", "puppet", "ibm", "xampp", "momentjs", "stylus", "livewire", "enzyme", "flux", "alpinejs-logo", "alpinejs", "logstash", "ember", "knex", "promises", "sinatra", "webhooks", "sonarqube", "tiktok-logo", "tiktok", "mastodon-icon", "mastodon", "chromium", "heap", "sketch", "relay", "polymer", "haxe", "semantic-web", "jasmine", "chef", "openstack-logo", "openstack", "freebsd", "immutable", "sendgrid-logo", "sendgrid", "sentry-logo", "sentry", "joomla", "dialogflow", "intellij-idea", "pycharm", "line", "appium", "airbnb", "jetbrains", "paypal", "stackoverflow-icon", "stackoverflow", "mediawiki", "lumen", "trello", "realm", "grunt", "typo3-icon", "typo3", "coffeescript", "mit", "pixijs", "esbuild", "zoom-icon", "zoom-logo", "io", "signal", "karma", "doctrine", "solr", "vaadin", "pusher-logo", "pusher", "craftcms", "yandex-logo", "ghost", "reasonml-icon", "reasonml", "pm2", "docusaurus", "swoole", "medium-logo", "medium", "bitbucket", "hapi", "bower", "purescript-icon", "purescript", "splunk", "buffer", "dropbox", "nativescript", "kde", "progress", "memcached", "yeoman", "couchdb-icon", "couchdb", "highcharts", "semaphore", "lastfm", "redhat-logo", "redhat", "certbot", "blueprint", "etcd", "soundcloud", "teamwork-icon", "teamwork", "prestashop", "marvel", "mono", "insomnia", "jade", "rubygems", "foundation", "monero", "cakephp-logo", "cakephp", "gnu", "atomic-logo", "atomic", "codecademy", "hbase", "algolia", "saltstack", "qq", "editorconfig", "openlayers", "glitch-icon", "glitch", "lucene", "slim", "lighthouse", "element", "kali-logo", "datadog", "airtable", "vlang", "bing", "rancher-logo", "rancher", "now", "mailchimp", "mattermost-logo", "mattermost", "opencart", "blogger", "browsersync", "codecov", "loopback-icon", "loopback", "rubocop", "play", "yii", "flight", "hyper", "browserify-icon", "browserify", "kong-logo", "kong", "phonegap", "prismic-icon", "prismic", "zigbee", "nomad", "hasura", "mozilla", "mlab", "immer", "fastlane", "codepen-icon", "codepen", "react-spring", "box", "chalk", "safari", "webkit", "mailgun-icon", "mailgun", "bsd", "phpstorm", "flickr", "couchbase", "phantomjs", "alipay-logo", "alipay", "amp-icon", "amp", "autoprefixer", "grav", "neat", "ifttt", "gimp", "baidu-logo", "baidu", "ieee", "wildfly", "backbone-icon", "backbone", "libreoffice-icon", "libreoffice-logo", "leveldb", "compass", "todomvc", "moon", "openid-icon", "openid-logo", "aurora", "kirby-logo", "kirby", "confluence", "vuetifyjs", "wagtail", "atlassian", "sapper-icon", "ink", "gridsome-icon", "gridsome", "aurelia", "pinterest", "xstate", "jhipster-logo", "jhipster", "bitrise-icon", "bitrise", "sidekiq-logo", "sidekiq", "graphene", "inkscape", "vimeo-icon", "vimeo", "w3c", "asciidoctor", "rethinkdb", "snowpack", "phalcon", "uwsgi", "gleam", "concourse", "node-sass", "zeit-icon", "zeit", "tumblr-icon", "tumblr", "v8", "gwt", "altair", "snapchat", "ava", "graylog-logo", "graylog", "surge", "autoit", "todoist-icon", "todoist", "codesandbox", "workbox-icon", "workbox", "mesos", "flarum", "grails", "zend-framework", "redux-observable", "clion", "ramda", "middleman", "coveralls", "devto", "dojo-icon", "dojo", "arangodb", "opera", "alfresco", "dyndns", "pkg", "wikimedia", "brotli", "duckduckgo", "kraken", "segment-logo", "segment", "chakra-icon", "chakra-logo", "appveyor", "beats", "reactivex", "framework7-logo", "framework7", "square", "webstorm", "feathersjs", "bitwarden-logo", "bitwarden", "riot", "pouchdb", "dcos-logo", "dcos", "linode", "yahoo", "require", "jss", "cpanel", "linux-mint", "rocksdb", "metabase", "facebook-messenger", "jsdom", "gravatar", "stimulus", "gopher", "hyperapp", "zendesk-logo", "zendesk", "alibaba", "apache-camel", "datocms-icon", "datocms", "tencent", "hubspot", "brackets", "zhihu", "dribbble-icon", "dribbble", "visa", "haiku-logo", "haiku", "1password-logo", "1password", "thunderbird", "raml", "processwire-logo", "processwire", "mithril", "wearos", "discover", "bluemix", "refactor", "dat", "7zip", "panda", "capistrano", "oshw", "balena", "coreos-icon", "coreos", "onesignal", "skype", "wakatime", "brave-logo", "brave", "webtorrent", "disqus", "buildkite-icon", "buildkite", "hashnode-logo", "hashnode", "origami", "snyk", "appcelerator", "nsis", "dropzone", "vultr-icon", "vultr", "modx-logo", "modx", "quora", "webflow", "evernote-icon", "evernote-logo", "jquery-mobile", "mdn", "derby", "nightwatch", "codeclimate", "presto", "manuscript", "open-graph", "mega-logo", "mega", "subversion", "codebase", "sensu-logo", "sensu", "elo", "wix", "horizon", "cloudera", "akamai", "cyclejs", "adonis-icon", "adonis", "codeception", "envoyproxy", "void", "wire", "zapier-logo", "zapier", "haml", "qlik", "spinnaker", "crashlytics", "juju", "knockout", "mercurial", "zwave", "q", "zoho", "undertow", "drift", "fly", "kops", "pushbullet", "asana", "bamboo", "jruby", "sails", "suse", "aerospike-logo", "aerospike", "patreon", "apigee", "bugsnag-logo", "bugsnag", "brunch", "kickstarter-logo", "kickstarter", "statuspage", "cloudant", "looker-icon", "looker", "origin", "cockpit", "imagemin", "cljs", "createjs", "hhvm", "kaios", "namecheap", "hanami", "grommet", "intercom-logo", "intercom", "rollbar-logo", "rollbar", "browserstack", "guix", "inferno", "gitkraken", "upwork", "dynatrace-icon", "dynatrace", "keystonejs", "section-icon", "section", "svgo-icon", "svgo-logo", "mastercard", "gocd", "chromatic-icon", "chromatic", "concrete5", "cloud9", "keybase", "graphcool", "vivaldi-logo", "vivaldi", "capacitorjs-icon", "capacitorjs", "digital-ocean", "api-ai", "reveal", "osquery", "mixpanel", "percona", "shields", "codacy", "fastly", "impala", "linkerd", "typeform-icon", "typeform", "elementary", "alibaba-cloud", "grape", "t3", "jfrog", "mailjet", "viber-logo", "viber", "buck", "grove", "pd-icon", "whatwg", "emmet", "gitter", "c-plusplus", "gecko", "youtrack", "codeship", "runnable", "jsdelivr", "caffe2", "loom", "maestro", "quay", "zeplin", "iron-icon", "iron", "browserslist", "fontforge", "cssnext", "oreilly", "adyen", "bitnami", "sparkpost", "taiga", "saucelabs", "struts", "webix-icon", "webix", "ycombinator", "html-5", "zulip-logo", "zulip", "hoa", "apple-pay", "sencha", "spree", "bourbon", "lighttpd", "desk", "marionette", "webhint-icon", "webhint", "close", "waffle", "postgraphile", "treehouse", "codepush", "rum", "sherlock-logo", "sherlock", "webtask", "zenhub-logo", "zenhub", "producthunt", "modernizr", "optimizely", "serveless", "basecamp", "jspm", "apiary", "riak", "wicket-icon", "wicket", "cobalt", "kemal", "mandrill", "marko", "mention", "opsgenie", "broccoli", "lastpass-icon", "lastpass-logo", "css-3", "html5-boilerplate", "xero", "egghead", "milligram", "percy-logo", "percy", "r-lang", "behance-icon", "behance", "squarespace", "authy", "mist", "hotjar", "invision-logo", "invision", "octopus-deploy", "stetho", "lynda", "torus", "cloudacademy-logo", "cloudacademy", "trac", "xtend", "flannel", "webmin", "otto", "rubymine", "wercker", "filezilla", "glimmerjs", "deviantart", "buddy", "floydhub", "pipedrive", "rxdb", "airbrake", "seneca", "pingdom", "weave", "monday-icon", "monday", "bytedance-logo", "bytedance", "rackspace", "stitch", "cross-browser-testing", "envato", "glamorous", "rkt", "sourcetree", "kuaishou-icon", "kuaishou-logo", "mesosphere", "sourcegraph", "appdynamics", "conan-io", "sysdig-logo", "sysdig", "doubleclick", "eventbrite-icon", "eventbrite", "semaphoreci", "moltin-icon", "moltin", "cachet", "amex", "jelastic-logo", "jelastic", "jsfiddle", "amazon-connect", "ceylon", "delicious", "watchman", "loggly", "qzone", "unionpay", "fuchsia", "passbolt-logo", "passbolt", "raphael", "500px", "copyleft", "new-relic", "tectonic", "user-testing-icon", "user-testing", "evergreen-icon", "evergreen", "memsql-icon", "memsql", "vwo", "solarwinds", "blossom", "mapzen-logo", "mapzen", "nodeos", "susy", "upcase", "logentries", "turret", "autocode", "braze", "dreamhost", "mio", "nodebots", "orchid-logo", "orchid", "rax", "reindex", "vernemq", "vine", "forever", "armory", "game-analytics-logo", "game-analytics", "helpscout-logo", "helpscout", "stoplight", "webplatform", "chargebee-icon", "chargebee", "jcb", "morpheus-logo", "morpheus", "appcode", "drip", "wpengine", "dashlane-logo", "dashlane", "myth", "nuclide", "poeditor", "tutsplus", "agpl_v3", "chevereto", "esdoc", "kinvey", "mootools", "swiftype", "falcor", "opencollective", "pipefy", "scaledrone", "canjs", "codrops", "hoodie", "infer", "losant", "flat-ui", "heron", "litmus", "nanonets", "pagekit", "sidekick", "unito-logo", "unito", "ampersand", "cirrus", "codeschool", "microcosm", "astronomer", "internetexplorer", "kontena", "sugarss", "cirrus-ci", "crucible", "kore", "mageia", "tsuru", "octodns", "picasa", "plastic-scm", "protactor", "xwiki-icon", "xwiki", "yammer", "zorin-os", "appbaseio-icon", "appbaseio", "eager", "librato", "qwant-logo", "qwant", "rsmq", "scribd-icon", "scribd", "sitepoint", "stacksmith", "appsignal-icon", "appsignal", "fomo", "jsbin", "parsehub", "rocket-chat-logo", "rocket-chat", "shipit", "supersonic", "tsu", "apostrophe", "campfire", "codefactor-logo", "codefactor", "gomix", "nuodb", "shippable", "shogun", "react-styleguidist", "appbase", "gfdl", "liftweb", "stormpath", "treasuredata-logo", "treasuredata", "zest", "blocs", "customerio-logo", "customerio", "kinto-logo", "kinto", "mixmax", "nodewebkit", "podio", "puppy-linux", "steroids", "tealium", "celluloid", "keen", "keycdn-logo", "keycdn", "krakenjs", "speakerdeck", "strider", "web-fundamentals", "apache_cloudstack", "codebeat", "codersrank", "enyo", "glint", "kitematic", "skylight", "snap-svg", "victorops", "wmr", "bigpanda", "cloudlinux", "dojo-toolkit", "envoyer", "fluxxor", "lateral", "opbeat", "runscope", "targetprocess", "tunein", "dreamfactory", "frontapp", "madge", "prospect", "pyup", "scaphold", "smashingmagazine", "supergiant", "uservoice-logo", "uservoice", "apple-app-store", "botanalytics", "carbide", "cloudcraft", "codio", "eta-lang", "gordon", "magneto", "mparticle-logo", "mparticle", "nodal", "smartling", "stackshare", "stylefmt", "thimble", "umu", "bosun", "dimer", "embedly", "fleep-logo", "fleep", "forestadmin-logo", "forestadmin", "galliumos", "kissmetrics", "maxthon", "siphon", "unbounce-logo", "unbounce", "woopra-logo", "woopra", "workato-icon", "wufoo", "xcart", "adroll", "angellist", "campaignmonitor-icon", "campaignmonitor", "coderwall", "day-js", "deployhq", "ebanx", "flattr-logo", "flattr", "flocker", "gusto", "hostgator-logo", "hostgator", "houndci", "kustomer", "logmatic", "lookback", "mockflow", "styleci", "svgator", "tnw", "tutum", "weebly", "amazon-chime", "apitools", "componentkit", "containership", "convox", "dinersclub", "flyjs", "geekbot", "giantswarm", "gitup", "gratipay", "haxl", "leankit-icon", "leankit", "pagekite", "pepperoni", "productboard-logo", "productboard", "protonet", "quobyte", "speedcurve", "appfog", "apptentive", "codefund-icon", "codefund", "crateio", "dapulse", "designernews", "elemental-ui", "flowxo", "gohorse", "kallithea", "kloudless", "linux-tux", "maps-me", "maxcdn", "olark", "pingy", "precursor", "proofy", "sourcetrail", "tastejs", "ucbrowser", "workboard", "zube", "daemon-tools", "delighted-logo", "delighted", "dockbit", "engine-yard-logo", "engine-yard", "eventsentry", "formkeep", "groovehq", "harrow", "hipercard", "humongous", "importio", "launchkit", "manifoldjs", "netuitive", "positionly", "protoio-logo", "protoio", "redsmin", "sagui", "survicate", "tapcart-logo", "tapcart", "vaddy", "visaelectron", "x-ray-goggles", "100tb", "alpinejs-icon-circle", "apache-openoffice-icon", "apache-openoffice-logo", "apollostack", "appcircle-icon", "appcircle", "apphub", "appmaker", "apportable", "aurous", "backerkit", "baker-street", "basekit", "bem-2", "bitballoon", "booqable", "bowtie", "branch", "brandfolder-icon", "brandfolder", "browserling", "bugherd", "bugsee", "changetip", "chartblocks", "clickdeploy", "clusterhq", "codepicnic", "copyleft-pirate", "css-3_official", "database-labs", "dependencyci", "deppbot", "dropmark", "elasticbox", "ello", "ember-tomster", "ethnio", "fabric_io", "firefox_classic", "flexible-gs", "floodio", "freedcamp-icon", "freedcamp", "freedomdefined", "g-ads", "g-adsense", "g-adword", "g-analytics", "g-tag-manager", "gaugeio", "get-satisfaction", "getyourguide", "gitboard", "github-octocat", "gpl_v3", "hacker-one", "heroku-redis", "hosted-graphite", "instagram-icon-monochrome", "itsalive-icon", "itsalive", "kali-dragon-icon", "khan_academy-logo", "khan_academy", "koreio", "launchrock", "leafjet", "lets-cloud", "lgpl_v3", "livewire-underwater-jelly", "locent", "lucene.net", "mailchimp-freddie", "maildeveloper", "mandrill-shield", "mantl", "medium-icon-circle", "neonmetrics", "nodejitsu", "noysi", "npm-2", "olapic", "opsee", "orchid-monochrome", "peer5", "perf-rocks", "phonegap-bot", "php-alt", "pivotal_tracker", "pixate", "planless-logo", "planless", "prott", "pumpkindb", "qordoba", "reapp", "recast.ai", "redspread", "remergr", "rest-li", "run-above", "sameroom", "samsung_internet", "sass-doc", "sectionio", "sina_weibo", "skaffolder", "sninnaker", "snupps", "socket.io", "sparkcentral", "sqldep", "stickermule", "supportkit", "svg-withtext", "taskade-icon", "taskade", "teamgrid", "testlodge", "testmunk", "titon", "tor_browser", "traackr", "travis-ci-monochrome", "unitjs", "v8-ignition", "v8-turbofan", "vector-timber", "visual_website_optimizer", "whalar", "wikimedia_commons", "wiredtree", "workato-wordmark", "xplenty", "yandex-ru", "yandex_browser"];
     // let list = ["javascript"];
     
-    let mut meta_data = MetaData {
-        items: Vec::new(),
-    };
+    let mut meta_data = MetaData::default();
+    let list_json = list_json_path();
+    if fs::exists(&list_json).unwrap() {
+        let json = fs::read_to_string(&list_json).unwrap();
+        meta_data.catalog = Catalog::from_list_json(&json).unwrap();
+    }
     for ll in list {
         let root = get_path("repo/geticon/icons");
         let path = format!("{}/{}.svg", root, ll);
@@ -61,20 +790,8 @@ fn main() {
         println!("parse: {}", ll);
         // build_resouce(ll);
         let data = get_svg_bytes(ll).unwrap();
-        let (pix, scale) = techwall::png::convert_svg_to_png(data).unwrap();
-        let w = pix.width() / 10;
-        let h = pix.height() / 10;
-        let content = pix.encode_png().unwrap();
+        let (meta, content) = Meta::from_svg(ll, data).unwrap();
         // std::fs::write(format!("{}/{}/icon.png", root, ll), &content).unwrap();
-        let _content = techwall::png::load(&content, (w as usize,h as usize)).unwrap();
-        let polygon = _content.iter().map(|f|Point::from(*f)).collect();
-        let meta = Meta {
-            width: pix.width(),
-            height: pix.height(),
-            scale,
-            name: ll.to_string(),
-            polygon,
-        };
         meta_data.items.push(meta);
         std::fs::write(format!("{}/{}.png", target, ll), &content).unwrap();
     }
@@ -87,3 +804,25 @@ fn main() {
     let content = encoder.finish().unwrap();
     std::fs::write(format!("{}/meta", target), content).unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn positional_keeps_arguments_after_switches() {
+        let a = args(&["out.png", "--repeat", "rust", "--arrange", "tile", "go"]);
+        assert_eq!(positional(&a), ["out.png", "rust", "go"]);
+        assert!(switch(&a, "--repeat"));
+        assert_eq!(flag(&a, "--arrange"), Some("tile"));
+
+        let a = args(&["--force", "file.svg", "--name", "acme"]);
+        assert_eq!(positional(&a), ["file.svg"]);
+        let a = args(&["user", "--forks", "--metrics", "--labels", "--percent", "more"]);
+        assert_eq!(positional(&a), ["user", "more"]);
+    }
+}
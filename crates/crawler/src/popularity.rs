@@ -0,0 +1,475 @@
+//! 技术热度：从多个数据源抓取计数写回 `crawler/list.json`，再归一化成单一的 popularity。
+//! 取代 `crawler/index.js` 的 `processTechnologyList`，可以中断后继续
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::github::{Client, RateLimit};
+use crate::http::{encode, Transport};
+
+/// 某个数据源给出的计数，`details` 与 Node 爬虫写入的结构兼容
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SourceScore {
+    pub score: u64,
+    #[serde(default)]
+    pub details: Map<String, Value>,
+}
+
+impl SourceScore {
+    fn failed(&self) -> bool {
+        self.details.contains_key("error")
+    }
+}
+
+/// `crawler/list.json` 的条目，数据源的结果按数据源名平铺，例如 `github`、`stack`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ListItem {
+    pub name: String,
+    pub items: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub popularity: Option<f32>,
+    #[serde(flatten)]
+    pub sources: BTreeMap<String, SourceScore>,
+}
+
+/// 可插拔的数据源
+pub trait ScoreSource {
+    /// 写入 list.json 时使用的字段名
+    fn key(&self) -> &'static str;
+    fn fetch(&mut self, name: &str) -> Result<SourceScore>;
+    /// 最近一次请求后的配额
+    fn limit(&self) -> RateLimit {
+        RateLimit::default()
+    }
+}
+
+/// GitHub 上带该 topic 的仓库数
+pub struct GithubTopics<T: Transport> {
+    client: Client<T>,
+}
+
+impl<T: Transport> GithubTopics<T> {
+    pub fn new(client: Client<T>) -> Self {
+        GithubTopics { client }
+    }
+}
+
+impl<T: Transport> ScoreSource for GithubTopics<T> {
+    fn key(&self) -> &'static str {
+        "github"
+    }
+
+    fn fetch(&mut self, name: &str) -> Result<SourceScore> {
+        let count = self.client.topic_count(name)?;
+        Ok(SourceScore {
+            score: count,
+            details: details(json!({ "repositoryCount": count, "source": "GitHub API" })),
+        })
+    }
+
+    fn limit(&self) -> RateLimit {
+        self.client.limit
+    }
+}
+
+#[derive(Deserialize)]
+struct TagInfo {
+    #[serde(default)]
+    items: Vec<TagCount>,
+    quota_remaining: Option<u64>,
+    /// 要求客户端等待的秒数
+    backoff: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TagCount {
+    count: u64,
+}
+
+/// StackOverflow 上该标签的问题数
+pub struct StackTags<T: Transport> {
+    transport: T,
+    limit: RateLimit,
+}
+
+impl<T: Transport> StackTags<T> {
+    pub fn new(transport: T) -> Self {
+        StackTags {
+            transport,
+            limit: RateLimit::default(),
+        }
+    }
+}
+
+impl<T: Transport> ScoreSource for StackTags<T> {
+    fn key(&self) -> &'static str {
+        "stack"
+    }
+
+    fn fetch(&mut self, name: &str) -> Result<SourceScore> {
+        let url = format!(
+            "https://api.stackexchange.com/2.3/tags/{}/info?site=stackoverflow",
+            encode(&name.to_lowercase())
+        );
+        let response = self.transport.get(&url, &[])?;
+        if response.status != 200 {
+            bail!("GET {} returned {}: {}", url, response.status, response.body.trim());
+        }
+        let info: TagInfo = serde_json::from_str(&response.body)?;
+        self.limit = RateLimit {
+            remaining: info.backoff.map(|_| 0).or(info.quota_remaining),
+            reset: info.backoff.map(|s| now_ms() / 1000 + s),
+        };
+        let count = info.items.first().map(|t| t.count).unwrap_or(0);
+        Ok(SourceScore {
+            score: count,
+            details: details(json!({ "questionCount": count, "source": "StackOverflow API" })),
+        })
+    }
+
+    fn limit(&self) -> RateLimit {
+        self.limit
+    }
+}
+
+fn details(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug)]
+struct LimitState {
+    /// 下一次允许请求的时间，unix 毫秒
+    next: u64,
+    #[serde(flatten)]
+    limit: RateLimit,
+}
+
+/// 每个数据源一个令牌的限速器，状态写入文件，重启后不会立即超出配额
+pub struct Limiter {
+    path: PathBuf,
+    interval: Duration,
+    state: HashMap<String, LimitState>,
+    /// 配额用完时最多等待多久，超过则直接报错
+    pub max_wait: Duration,
+}
+
+impl Limiter {
+    pub fn load(path: impl Into<PathBuf>, interval: Duration) -> Result<Limiter> {
+        let path = path.into();
+        let state = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(_) => HashMap::new(),
+        };
+        Ok(Limiter {
+            path,
+            interval,
+            state,
+            max_wait: Duration::from_secs(15 * 60),
+        })
+    }
+
+    /// 等到数据源可以再次请求，需要等待超过 `max_wait` 时报错
+    pub fn wait(&self, key: &str) -> Result<()> {
+        let Some(state) = self.state.get(key) else {
+            return Ok(());
+        };
+        let mut until = state.next;
+        if let (Some(0), Some(reset)) = (state.limit.remaining, state.limit.reset) {
+            until = until.max(reset * 1000);
+        }
+        let now = now_ms();
+        if until > now {
+            let wait = Duration::from_millis(until - now);
+            if wait > self.max_wait {
+                bail!("rate limit for {} exhausted, resets in {}s", key, wait.as_secs());
+            }
+            if wait.as_secs() >= 10 {
+                eprintln!("rate limit reached, waiting {}s", wait.as_secs());
+            }
+            std::thread::sleep(wait);
+        }
+        Ok(())
+    }
+
+    pub fn record(&mut self, key: &str, limit: RateLimit) -> Result<()> {
+        self.state.insert(
+            key.to_string(),
+            LimitState {
+                next: now_ms() + self.interval.as_millis() as u64,
+                limit,
+            },
+        );
+        std::fs::write(&self.path, serde_json::to_string_pretty(&self.state)?)?;
+        Ok(())
+    }
+}
+
+/// 本地覆盖文件 `{ "技术名": { "github": 123, "stack": 456 } }`，
+/// 优先于远程数据源，用于修正名字有歧义或接口查不到的技术
+#[derive(Default)]
+pub struct Overrides {
+    scores: HashMap<String, HashMap<String, u64>>,
+}
+
+impl Overrides {
+    pub fn load(path: &Path) -> Result<Overrides> {
+        let scores = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Ok(Overrides { scores })
+    }
+
+    fn get(&self, name: &str, key: &str) -> Option<u64> {
+        self.scores.get(name)?.get(key).copied()
+    }
+}
+
+pub struct Pipeline<'a> {
+    pub sources: Vec<Box<dyn ScoreSource + 'a>>,
+    pub overrides: Overrides,
+    pub limiter: Limiter,
+    /// 各数据源在 popularity 中的权重，未列出的为 1
+    pub weights: HashMap<String, f32>,
+}
+
+impl Pipeline<'_> {
+    /// 补齐缺失或失败的数据，每处理完一项就写回文件。`limit` 限制本次请求的次数
+    pub fn run(&mut self, list_path: &Path, limit: usize) -> Result<()> {
+        let mut list: Vec<ListItem> = serde_json::from_str(&std::fs::read_to_string(list_path)?)?;
+        let mut requests = 0;
+        for index in 0..list.len() {
+            let mut changed = false;
+            for source in self.sources.iter_mut() {
+                let key = source.key();
+                let item = &mut list[index];
+                if let Some(score) = self.overrides.get(&item.name, key) {
+                    let value = SourceScore {
+                        score,
+                        details: details(json!({ "source": "local override" })),
+                    };
+                    item.sources.insert(key.to_string(), value);
+                    changed = true;
+                    continue;
+                }
+                if item.sources.get(key).is_some_and(|s| !s.failed()) || requests >= limit {
+                    continue;
+                }
+                self.limiter.wait(key)?;
+                println!("resolve {} {} index {}", key, item.name, index + 1);
+                let value = source.fetch(&item.name).unwrap_or_else(|e| {
+                    eprintln!("error fetching {} for {}: {}", key, item.name, e);
+                    SourceScore {
+                        score: 0,
+                        details: details(json!({ "error": e.to_string() })),
+                    }
+                });
+                self.limiter.record(key, source.limit())?;
+                item.sources.insert(key.to_string(), value);
+                requests += 1;
+                changed = true;
+            }
+            if changed {
+                save(list_path, &list)?;
+            }
+        }
+        normalize(&mut list, &self.weights);
+        save(list_path, &list)
+    }
+}
+
+fn save(path: &Path, list: &[ListItem]) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(list)?)?;
+    Ok(())
+}
+
+/// 每个数据源按对数缩放到 0 到 1（除以该数据源的最大值），再按权重取平均。
+/// 失败的结果不参与计算
+pub fn normalize(list: &mut [ListItem], weights: &HashMap<String, f32>) {
+    let mut max: HashMap<&str, u64> = HashMap::new();
+    for item in list.iter() {
+        for (key, s) in item.sources.iter().filter(|(_, s)| !s.failed()) {
+            let m = max.entry(key.as_str()).or_default();
+            *m = (*m).max(s.score);
+        }
+    }
+    let max: HashMap<String, f32> = max
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), (v as f32).ln_1p()))
+        .collect();
+    for item in list.iter_mut() {
+        let mut total = 0.0;
+        let mut weight = 0.0;
+        for (key, s) in item.sources.iter().filter(|(_, s)| !s.failed()) {
+            let w = weights.get(key).copied().unwrap_or(1.0);
+            let m = max.get(key).copied().unwrap_or(0.0);
+            if m > 0.0 {
+                total += w * (s.score as f32).ln_1p() / m;
+            }
+            weight += w;
+        }
+        item.popularity = (weight > 0.0).then(|| total / weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    // 记录请求的假数据源，计数为名字长度乘 100
+    struct Fake {
+        key: &'static str,
+        calls: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl ScoreSource for Fake {
+        fn key(&self) -> &'static str {
+            self.key
+        }
+
+        fn fetch(&mut self, name: &str) -> Result<SourceScore> {
+            self.calls.borrow_mut().push(format!("{} {}", self.key, name));
+            Ok(SourceScore {
+                score: name.len() as u64 * 100,
+                details: Map::new(),
+            })
+        }
+    }
+
+    // 把 fixture 复制到临时目录，运行会改写 list.json
+    fn workspace(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("techwall-popularity-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/popularity");
+        for file in ["list.json", "overrides.json"] {
+            std::fs::copy(fixtures.join(file), dir.join(file)).unwrap();
+        }
+        dir
+    }
+
+    fn pipeline(dir: &Path, calls: &Rc<RefCell<Vec<String>>>) -> Pipeline<'static> {
+        let sources: Vec<Box<dyn ScoreSource>> = ["github", "stack"]
+            .into_iter()
+            .map(|key| Box::new(Fake { key, calls: calls.clone() }) as Box<dyn ScoreSource>)
+            .collect();
+        Pipeline {
+            sources,
+            overrides: Overrides::load(&dir.join("overrides.json")).unwrap(),
+            limiter: Limiter::load(dir.join("limits.json"), Duration::ZERO).unwrap(),
+            weights: HashMap::new(),
+        }
+    }
+
+    fn load(path: &Path) -> Vec<ListItem> {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn resumes_from_partial_list() {
+        let dir = workspace("resume");
+        let list = dir.join("list.json");
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        // 只补失败和缺失的结果，本次限一次请求
+        pipeline(&dir, &calls).run(&list, 1).unwrap();
+        assert_eq!(*calls.borrow(), ["github vue"]);
+        let items = load(&list);
+        assert_eq!(items[1].sources["github"].score, 300);
+        assert!(!items[2].sources.contains_key("github"));
+
+        // 中断后继续只请求剩下的
+        calls.borrow_mut().clear();
+        pipeline(&dir, &calls).run(&list, 10).unwrap();
+        assert_eq!(*calls.borrow(), ["github svelte"]);
+        let items = load(&list);
+        assert_eq!(items[0].sources["github"].score, 1000);
+        assert!(items.iter().all(|i| i.popularity.is_some()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn overrides_win_without_fetching() {
+        let dir = workspace("overrides");
+        let list = dir.join("list.json");
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        pipeline(&dir, &calls).run(&list, 10).unwrap();
+        assert!(!calls.borrow().iter().any(|c| c == "stack svelte"));
+        let stack = &load(&list)[2].sources["stack"];
+        assert_eq!(stack.score, 42);
+        assert_eq!(stack.details["source"], "local override");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn item(name: &str, sources: &[(&str, u64, bool)]) -> ListItem {
+        let sources = sources
+            .iter()
+            .map(|&(key, score, failed)| {
+                let details = if failed { details(json!({ "error": "timeout" })) } else { Map::new() };
+                (key.to_string(), SourceScore { score, details })
+            })
+            .collect();
+        ListItem {
+            name: name.to_string(),
+            items: vec![name.to_string()],
+            popularity: None,
+            sources,
+        }
+    }
+
+    #[test]
+    fn normalize_scales_and_weights_sources() {
+        let mut list = vec![
+            item("react", &[("github", 1000, false), ("stack", 100, false)]),
+            item("vue", &[("github", 5000, true), ("stack", 10, false)]),
+            item("svelte", &[]),
+        ];
+        let weights = HashMap::from([("stack".to_string(), 3.0)]);
+        normalize(&mut list, &weights);
+        // 各数据源的最大值为 1，失败的 5000 不算最大值
+        assert!((list[0].popularity.unwrap() - 1.0).abs() < 1e-6);
+        // vue 只有 stack 参与计算
+        let stack = (11f32).ln() / (101f32).ln();
+        assert!((list[1].popularity.unwrap() - stack).abs() < 1e-6);
+        assert_eq!(list[2].popularity, None);
+
+        // 权重按数据源加权平均
+        list[0].sources.get_mut("stack").unwrap().score = 10;
+        list[1].sources.get_mut("stack").unwrap().score = 100;
+        normalize(&mut list, &weights);
+        let expected = (1.0 + 3.0 * stack) / 4.0;
+        assert!((list[0].popularity.unwrap() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn limiter_errors_instead_of_blocking() {
+        let dir = workspace("limiter");
+        let path = dir.join("limits.json");
+        let mut limiter = Limiter::load(&path, Duration::ZERO).unwrap();
+        let exhausted = RateLimit {
+            remaining: Some(0),
+            reset: Some(now_ms() / 1000 + 3600),
+        };
+        limiter.record("github", exhausted).unwrap();
+        limiter.record("stack", RateLimit::default()).unwrap();
+
+        // 状态写入文件，重新加载后仍然生效
+        let mut limiter = Limiter::load(&path, Duration::ZERO).unwrap();
+        limiter.max_wait = Duration::from_secs(60);
+        let error = limiter.wait("github").unwrap_err().to_string();
+        assert!(error.contains("github"), "{}", error);
+        assert!(limiter.wait("stack").is_ok());
+        assert!(limiter.wait("unknown").is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,22 +1,117 @@
+use crate::frame::Frame;
+
 pub type Point = (u32, u32);
 
-type Polygon = Vec<Point>;
+pub type Polygon = Vec<Point>;
+
+#[derive(Clone, Debug)]
+pub struct Icon {
+    pub width: u32,      // 图片宽度
+    pub height: u32,     // 图片高度
+    pub polygon: Polygon, // 以图片左上角为原点的多边形顶点
+    pub scale: f32,      // 图片缩放比例
+    pub angle: f32,      // 绕图片中心顺时针旋转的角度
+}
+
+impl Icon {
+    pub fn new(width: u32, height: u32, polygon: Polygon, scale: f32) -> Icon {
+        Icon { width, height, polygon, scale, angle: 0.0 }
+    }
+
+    pub fn rotated(mut self, angle: f32) -> Icon {
+        self.angle = angle;
+        self
+    }
+
+    // 缩放后未旋转的尺寸
+    fn unrotated_size(&self) -> (f32, f32) {
+        (
+            (self.width as f32 * self.scale).trunc(),
+            (self.height as f32 * self.scale).trunc(),
+        )
+    }
+
+    /// 缩放并旋转后的外接矩形尺寸，位置 (x, y) 为这个矩形的左上角
+    pub fn scaled_size(&self) -> (u32, u32) {
+        let (w, h) = self.unrotated_size();
+        if self.angle == 0.0 {
+            return (w as u32, h as u32);
+        }
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        // 减去一点避免 90 度时 cos 的舍入误差多出一个像素
+        (
+            (w * cos.abs() + h * sin.abs() - 1e-3).ceil() as u32,
+            (w * sin.abs() + h * cos.abs() - 1e-3).ceil() as u32,
+        )
+    }
+
+    /// 缩放后的多边形，没有轮廓数据时退化为外接矩形。
+    /// 旋转时绕图片中心转动，再平移到旋转后外接矩形的坐标系，顶点不会出现负数
+    pub fn scaled_polygon(&self) -> Polygon {
+        let (w, h) = self.unrotated_size();
+        let points: Vec<(f32, f32)> = if self.polygon.len() < 3 {
+            vec![(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)]
+        } else {
+            self.polygon
+                .iter()
+                .map(|&(x, y)| ((x as f32 * self.scale).trunc(), (y as f32 * self.scale).trunc()))
+                .collect()
+        };
+        if self.angle == 0.0 {
+            return points.into_iter().map(|(x, y)| (x as u32, y as u32)).collect();
+        }
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let (sw, sh) = self.scaled_size();
+        let (cx, cy) = (w / 2.0, h / 2.0);
+        points
+            .into_iter()
+            .map(|(x, y)| {
+                let (dx, dy) = (x - cx, y - cy);
+                let rx = dx * cos - dy * sin + sw as f32 / 2.0;
+                let ry = dx * sin + dy * cos + sh as f32 / 2.0;
+                (rx.round().clamp(0.0, sw as f32) as u32, ry.round().clamp(0.0, sh as f32) as u32)
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Position {
+    pub x: u32,
+    pub y: u32,
+    pub icon: Icon,
+}
 
-struct Icon {
-    width: u32,      // 图片宽度
-    height: u32,     // 图片高度
-    polygon: Polygon, // 以图片左上角为原点的多边形顶点
-    scale: f32,      // 图片缩放比例
+impl Position {
+    pub fn new(icon: Icon) -> Position {
+        Position { x: 0, y: 0, icon }
+    }
 }
 
-struct Position {
-    x: u32,
-    y: u32,
-    icon: Icon,
+// 多边形外接矩形 (min_x, min_y, max_x, max_y)
+pub(crate) fn bounds(p: &Polygon, offset: (u32, u32)) -> (u32, u32, u32, u32) {
+    let mut b = (u32::MAX, u32::MAX, 0, 0);
+    for &(x, y) in p {
+        b.0 = b.0.min(x + offset.0);
+        b.1 = b.1.min(y + offset.1);
+        b.2 = b.2.max(x + offset.0);
+        b.3 = b.3.max(y + offset.1);
+    }
+    b
 }
 
 // 判断两多边形是否重叠
-fn polygons_overlap(p1: &Polygon, p2: &Polygon, offset1: (u32, u32), offset2: (u32, u32)) -> bool {
+pub fn polygons_overlap(p1: &Polygon, p2: &Polygon, offset1: (u32, u32), offset2: (u32, u32)) -> bool {
+    if p1.is_empty() || p2.is_empty() {
+        return false;
+    }
+    // 先用外接矩形快速排除
+    let b1 = bounds(p1, offset1);
+    let b2 = bounds(p2, offset2);
+    if b1.2 <= b2.0 || b2.2 <= b1.0 || b1.3 <= b2.1 || b2.3 <= b1.1 {
+        return false;
+    }
+
     let translate = |p: &Polygon, offset: (u32, u32)| -> Polygon {
         p.iter().map(|&(x, y)| (x + offset.0, y + offset.1)).collect()
     };
@@ -43,15 +138,19 @@ fn get_axes(polygon: &Polygon) -> Vec<(i32, i32)> {
         let p1 = polygon[i];
         let p2 = polygon[(i + 1) % polygon.len()];
         let edge = (p2.0 as i32 - p1.0 as i32, p2.1 as i32 - p1.1 as i32);
+        if edge == (0, 0) {
+            // 重复顶点没有法线，跳过以免误判为分离
+            continue;
+        }
         axes.push((-edge.1, edge.0)); // 垂直边
     }
     axes
 }
 
 // 将多边形投影到轴上
-fn project_polygon(polygon: &Polygon, axis: &(i32, i32)) -> (i32, i32) {
-    let dot = |p: &(u32, u32), axis: &(i32, i32)| -> i32 {
-        (p.0 as i32) * axis.0 + (p.1 as i32) * axis.1
+fn project_polygon(polygon: &Polygon, axis: &(i32, i32)) -> (i64, i64) {
+    let dot = |p: &(u32, u32), axis: &(i32, i32)| -> i64 {
+        (p.0 as i64) * axis.0 as i64 + (p.1 as i64) * axis.1 as i64
     };
     let mut min = dot(&polygon[0], axis);
     let mut max = min;
@@ -63,84 +162,84 @@ fn project_polygon(polygon: &Polygon, axis: &(i32, i32)) -> (i32, i32) {
     (min, max)
 }
 
-fn re_align(items: &mut Vec<Position>) {
-    let mut placed_items: Vec<Position> = Vec::new();
-
-    for item in items.iter_mut() {
-        let scaled_polygon: Polygon = item
-            .icon
-            .polygon
-            .iter()
-            .map(|&(x, y)| {
-                (
-                    (x as f32 * item.icon.scale) as u32,
-                    (y as f32 * item.icon.scale) as u32,
-                )
-            })
-            .collect();
-
-        let mut placed = false;
-        for y in 0..10000 { // 假设画布无限大
-            for x in 0..10000 {
-                let is_overlap = placed_items.iter().any(|placed_item| {
-                    polygons_overlap(
-                        &scaled_polygon,
-                        &placed_item.icon.polygon,
-                        (x, y),
-                        (placed_item.x, placed_item.y),
-                    )
+/// 在 `width` x `height` 的画布内按行扫描放置图标，步长为 `step`，避开 `frame` 的留白和保留区域。
+/// 返回放不下的图标下标。
+pub fn re_align(items: &mut [Position], width: u32, height: u32, step: u32, frame: &Frame) -> Vec<usize> {
+    let step = step.max(1) as usize;
+    let (x0, y0, x1, y1) = frame.area(width, height);
+    let mut placed_items: Vec<(Polygon, (u32, u32))> = frame.obstacles(width, height);
+    let mut overflow = Vec::new();
+
+    for (index, item) in items.iter_mut().enumerate() {
+        let scaled_polygon = item.icon.scaled_polygon();
+        let (w, h) = item.icon.scaled_size();
+        if w > x1 - x0 || h > y1 - y0 {
+            overflow.push(index);
+            continue;
+        }
+
+        let mut placed = None;
+        'scan: for y in (y0..=y1 - h).step_by(step) {
+            for x in (x0..=x1 - w).step_by(step) {
+                let is_overlap = placed_items.iter().any(|(polygon, offset)| {
+                    polygons_overlap(&scaled_polygon, polygon, (x, y), *offset)
                 });
 
                 if !is_overlap {
-                    item.x = x;
-                    item.y = y;
-                    placed_items.push(Position {
-                        x,
-                        y,
-                        icon: Icon {
-                            width: item.icon.width,
-                            height: item.icon.height,
-                            polygon: scaled_polygon.clone(),
-                            scale: item.icon.scale,
-                        },
-                    });
-                    placed = true;
-                    break;
+                    placed = Some((x, y));
+                    break 'scan;
                 }
             }
-            if placed {
-                break;
+        }
+        match placed {
+            Some((x, y)) => {
+                item.x = x;
+                item.y = y;
+                placed_items.push((scaled_polygon, (x, y)));
             }
+            None => overflow.push(index),
         }
     }
+    overflow
 }
 
-fn main() {
-    let icon1 = Icon {
-        width: 100,
-        height: 200,
-        polygon: vec![(0, 0), (100, 0), (100, 200), (0, 200)],
-        scale: 1.0,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let icon2 = Icon {
-        width: 150,
-        height: 150,
-        polygon: vec![(0, 0), (150, 0), (150, 150), (0, 150)],
-        scale: 1.0,
-    };
+    fn square(size: u32) -> Position {
+        Position::new(Icon::new(size, size, vec![(0, 0), (size, 0), (size, size), (0, size)], 1.0))
+    }
 
-    let mut items = vec![
-        Position { x: 0, y: 0, icon: icon1 },
-        Position { x: 0, y: 0, icon: icon2 },
-    ];
+    #[test]
+    fn rotated_polygon_fits_its_bounds() {
+        let icon = Icon::new(40, 10, vec![(0, 0), (40, 0), (40, 10), (0, 10)], 1.0);
+        assert_eq!(icon.clone().rotated(90.0).scaled_size(), (10, 40));
+        let icon = icon.rotated(30.0);
+        let (w, h) = icon.scaled_size();
+        assert_eq!((w, h), (40, 29));
+        let polygon = icon.scaled_polygon();
+        assert_eq!(bounds(&polygon, (0, 0)), (0, 0, w, h));
+    }
 
-    re_align(&mut items);
+    #[test]
+    fn overflow_keeps_original_indices() {
+        // 第二个比画布大，最后一个放不下
+        let mut items = vec![square(50), square(150), square(50), square(50)];
+        let overflow = re_align(&mut items, 100, 50, 5, &Frame::default());
+        assert_eq!(overflow, vec![1, 3]);
+        assert_eq!((items[0].x, items[0].y), (0, 0));
+        assert_eq!((items[2].x, items[2].y), (50, 0));
+    }
 
-    for (i, item) in items.iter().enumerate() {
-        println!(
-            "Item {}: Position = ({}, {}), Polygon = {:?}",
-            i, item.x, item.y, item.icon.polygon
-        );
+    #[test]
+    fn avoids_keep_out() {
+        let mut items = vec![square(40)];
+        let frame = Frame {
+            keep_out: vec![crate::frame::Region { x: 0, y: 0, width: 60, height: 100 }],
+            ..Frame::default()
+        };
+        assert!(re_align(&mut items, 200, 100, 5, &frame).is_empty());
+        assert!(items[0].x >= 60);
     }
 }
@@ -0,0 +1,1008 @@
+//! 分析本地仓库使用的技术，结果是目录中的技术名，例如 "rust"、"react"、"docker"
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+// 不进入的目录
+const SKIP_DIRS: &[&str] = &[
+    "node_modules", "target", "vendor", "dist", "build", "out", "venv", "__pycache__", "bower_components",
+];
+const MAX_DEPTH: usize = 16;
+const MAX_FILES: usize = 100_000;
+// 依赖/配置类技术合计占总权重的比例
+const MANIFEST_SHARE: f32 = 0.5;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Technology {
+    pub name: String,
+    /// 该语言源文件的总字节数
+    pub bytes: u64,
+    /// 在清单、配置中出现的次数
+    pub mentions: usize,
+    /// 0 到 1 之间，语言按字节占比，其他技术按出现次数
+    pub weight: f32,
+    /// 命中的文件，相对于仓库根目录
+    pub evidence: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct Analysis {
+    /// 按权重从高到低排列
+    pub technologies: Vec<Technology>,
+}
+
+impl Analysis {
+    pub fn get(&self, name: &str) -> Option<&Technology> {
+        self.technologies.iter().find(|t| t.name == name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.technologies.iter().map(|t| t.name.as_str()).collect()
+    }
+}
+
+/// 累计各技术的字节数和出现次数，本地仓库和 GitHub 账号分析共用
+#[derive(Default)]
+pub struct Collector {
+    found: HashMap<String, Technology>,
+    files: usize,
+}
+
+impl Collector {
+    fn entry(&mut self, name: &str) -> &mut Technology {
+        self.found.entry(name.to_string()).or_insert_with(|| Technology {
+            name: name.to_string(),
+            bytes: 0,
+            mentions: 0,
+            weight: 0.0,
+            evidence: Vec::new(),
+        })
+    }
+
+    /// 记录语言代码量
+    pub fn bytes(&mut self, name: &str, bytes: u64) {
+        self.entry(name).bytes += bytes;
+    }
+
+    /// 记录一次出现，`path` 是出处
+    pub fn mention(&mut self, name: &str, path: &str) {
+        let tech = self.entry(name);
+        tech.mentions += 1;
+        if !tech.evidence.iter().any(|e| e == path) {
+            tech.evidence.push(path.to_string());
+        }
+    }
+
+    pub fn finish(self) -> Analysis {
+        let total_bytes: u64 = self.found.values().map(|t| t.bytes).sum();
+        let total_mentions: usize = self
+            .found
+            .values()
+            .filter(|t| t.bytes == 0)
+            .map(|t| t.mentions)
+            .sum();
+        // 有依赖/配置类技术时语言只占剩下的部分，所有权重加起来为 1
+        let language_share = if total_mentions > 0 { 1.0 - MANIFEST_SHARE } else { 1.0 };
+        let mut technologies: Vec<Technology> = self
+            .found
+            .into_values()
+            .map(|mut t| {
+                t.weight = if t.bytes > 0 {
+                    language_share * t.bytes as f32 / total_bytes as f32
+                } else if total_mentions > 0 {
+                    MANIFEST_SHARE * t.mentions as f32 / total_mentions as f32
+                } else {
+                    0.0
+                };
+                t
+            })
+            .collect();
+        technologies.sort_by(|a, b| b.weight.total_cmp(&a.weight).then_with(|| a.name.cmp(&b.name)));
+        Analysis { technologies }
+    }
+}
+
+/// 遍历本地仓库，按扩展名/shebang 统计语言，按清单文件识别框架和工具
+pub fn analyze(root: &Path) -> Result<Analysis> {
+    // 根目录读不了时报错，子目录读不了时跳过
+    fs::read_dir(root)?;
+    let mut collector = Collector::default();
+    walk(root, root, 0, &mut collector);
+    Ok(collector.finish())
+}
+
+fn walk(root: &Path, dir: &Path, depth: usize, collector: &mut Collector) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        if collector.files >= MAX_FILES {
+            break;
+        }
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(kind) = entry.file_type() else {
+            continue;
+        };
+        let rel = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if kind.is_dir() {
+            match name.as_str() {
+                ".github" if path.join("workflows").is_dir() => collector.mention("github", &rel),
+                ".circleci" => collector.mention("circleci", &rel),
+                _ => {}
+            }
+            if name.starts_with('.') || SKIP_DIRS.contains(&name.as_str()) {
+                continue;
+            }
+            walk(root, &path, depth + 1, collector);
+        } else if kind.is_file() {
+            collector.files += 1;
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            if let Some(language) = language_of(&path, &name) {
+                collector.bytes(language, size);
+            }
+            inspect(&path, &name, &rel, collector);
+        }
+    }
+}
+
+fn language_of(path: &Path, name: &str) -> Option<&'static str> {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => by_extension(&ext.to_lowercase()),
+        // 没有扩展名的脚本看 shebang
+        _ => shebang(path),
+    }
+}
+
+fn by_extension(ext: &str) -> Option<&'static str> {
+    let language = match ext {
+        "rs" => "rust",
+        "js" | "mjs" | "cjs" | "jsx" => "javascript",
+        "ts" | "mts" | "cts" | "tsx" => "typescript",
+        "py" | "pyw" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => "c-plusplus",
+        "cs" => "c-sharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "scala" | "sc" => "scala",
+        "hs" => "haskell",
+        "lua" => "lua",
+        "pl" | "pm" => "perl",
+        "r" => "r-lang",
+        "jl" => "julia",
+        "erl" | "hrl" => "erlang",
+        "clj" | "cljs" | "cljc" => "clojure",
+        "fs" | "fsx" => "fsharp",
+        "ml" | "mli" => "ocaml",
+        "elm" => "elm",
+        "cr" => "crystal",
+        "m" | "mm" => "objective-c",
+        "dart" => "dart",
+        "sh" | "bash" | "zsh" => "bash",
+        "html" | "htm" => "html-5",
+        "css" => "css-3",
+        "scss" | "sass" => "sass",
+        "less" => "less",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "coffee" => "coffeescript",
+        _ => return None,
+    };
+    Some(language)
+}
+
+fn shebang(path: &Path) -> Option<&'static str> {
+    // 只读第一行，最多 256 字节
+    let file = fs::File::open(path).ok()?;
+    let mut line = String::new();
+    BufReader::new(file.take(256)).read_line(&mut line).ok()?;
+    let line = line.strip_prefix("#!")?;
+    let mut parts = line.split_whitespace();
+    let mut program = parts.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = parts.find(|p| !p.starts_with('-'))?;
+    }
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let language = match program {
+        "python" => "python",
+        "node" | "nodejs" => "javascript",
+        "deno" => "typescript",
+        "bash" | "sh" | "zsh" => "bash",
+        "ruby" => "ruby",
+        "perl" => "perl",
+        "php" => "php",
+        "lua" => "lua",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// GitHub (linguist) 语言名对应的技术名，例如 "C++" -> "c-plusplus"
+pub fn linguist_language(name: &str) -> Option<&'static str> {
+    let language = match name {
+        "Rust" => "rust",
+        "JavaScript" => "javascript",
+        "TypeScript" => "typescript",
+        "Python" | "Jupyter Notebook" => "python",
+        "Go" => "go",
+        "Java" => "java",
+        "Kotlin" => "kotlin",
+        "Swift" => "swift",
+        "C" => "c",
+        "C++" => "c-plusplus",
+        "C#" => "c-sharp",
+        "Ruby" => "ruby",
+        "PHP" => "php",
+        "Scala" => "scala",
+        "Haskell" => "haskell",
+        "Lua" => "lua",
+        "Perl" => "perl",
+        "R" => "r-lang",
+        "Julia" => "julia",
+        "Erlang" => "erlang",
+        "Clojure" => "clojure",
+        "F#" => "fsharp",
+        "OCaml" => "ocaml",
+        "Elm" => "elm",
+        "Crystal" => "crystal",
+        "Objective-C" | "Objective-C++" => "objective-c",
+        "Dart" => "dart",
+        "Shell" => "bash",
+        "HTML" => "html-5",
+        "CSS" => "css-3",
+        "SCSS" | "Sass" => "sass",
+        "Less" => "less",
+        "Vue" => "vue",
+        "Svelte" => "svelte",
+        "CoffeeScript" => "coffeescript",
+        "Dockerfile" => "docker",
+        "HCL" => "terraform",
+        _ => return None,
+    };
+    Some(language)
+}
+
+// 依赖名 -> 技术名
+type DepTable = &'static [(&'static str, &'static str)];
+
+const NPM_DEPS: DepTable = &[
+    ("react", "react"),
+    ("vue", "vue"),
+    ("@angular/core", "angular"),
+    ("svelte", "svelte"),
+    ("next", "nextjs"),
+    ("nuxt", "nuxt"),
+    ("gatsby", "gatsby"),
+    ("express", "express"),
+    ("@nestjs/core", "nestjs"),
+    ("fastify", "fastify"),
+    ("koa", "koa"),
+    ("electron", "electron"),
+    ("jquery", "jquery"),
+    ("redux", "redux"),
+    ("@reduxjs/toolkit", "redux"),
+    ("tailwindcss", "tailwindcss"),
+    ("bootstrap", "bootstrap"),
+    ("@mui/material", "material-ui"),
+    ("@material-ui/core", "material-ui"),
+    ("webpack", "webpack"),
+    ("vite", "vite"),
+    ("@babel/core", "babel"),
+    ("eslint", "eslint"),
+    ("prettier", "prettier"),
+    ("jest", "jest"),
+    ("mocha", "mocha"),
+    ("cypress", "cypress"),
+    ("puppeteer", "puppeteer"),
+    ("typescript", "typescript"),
+    ("graphql", "graphql"),
+    ("three", "threejs"),
+    ("d3", "d3"),
+    ("socket.io", "socket.io"),
+    ("mongoose", "mongodb"),
+    ("mongodb", "mongodb"),
+    ("pg", "postgresql"),
+    ("mysql", "mysql"),
+    ("mysql2", "mysql"),
+    ("redis", "redis"),
+    ("ioredis", "redis"),
+    ("sqlite3", "sqlite"),
+    ("prisma", "prisma"),
+    ("@prisma/client", "prisma"),
+    ("sequelize", "sequelize"),
+    ("@storybook/react", "storybook"),
+    ("@ionic/core", "ionic"),
+    ("preact", "preact"),
+    ("firebase", "firebase"),
+    ("lodash", "lodash"),
+    ("rollup", "rollup"),
+    ("esbuild", "esbuild"),
+    ("sass", "sass"),
+    ("less", "less"),
+];
+
+const PYTHON_DEPS: DepTable = &[
+    ("django", "django"),
+    ("flask", "flask"),
+    ("tensorflow", "tensorflow"),
+    ("torch", "pytorch"),
+    ("opencv-python", "opencv"),
+    ("psycopg2", "postgresql"),
+    ("psycopg2-binary", "postgresql"),
+    ("psycopg", "postgresql"),
+    ("pymongo", "mongodb"),
+    ("redis", "redis"),
+    ("mysqlclient", "mysql"),
+    ("pymysql", "mysql"),
+    ("jupyter", "jupyter"),
+    ("notebook", "jupyter"),
+    ("selenium", "selenium"),
+    ("gunicorn", "gunicorn"),
+    ("elasticsearch", "elasticsearch"),
+    ("kafka-python", "kafka"),
+    ("pika", "rabbitmq"),
+];
+
+const CARGO_DEPS: DepTable = &[
+    ("wasm-bindgen", "webassembly"),
+    ("redis", "redis"),
+    ("postgres", "postgresql"),
+    ("tokio-postgres", "postgresql"),
+    ("mongodb", "mongodb"),
+    ("rusqlite", "sqlite"),
+    ("mysql", "mysql"),
+    ("rdkafka", "kafka"),
+    ("kube", "kubernetes"),
+    ("graphql", "graphql"),
+    ("async-graphql", "graphql"),
+];
+
+const GO_DEPS: DepTable = &[
+    ("k8s.io/client-go", "kubernetes"),
+    ("github.com/go-redis/redis", "redis"),
+    ("github.com/redis/go-redis", "redis"),
+    ("github.com/lib/pq", "postgresql"),
+    ("github.com/jackc/pgx", "postgresql"),
+    ("go.mongodb.org/mongo-driver", "mongodb"),
+    ("github.com/go-sql-driver/mysql", "mysql"),
+    ("github.com/mattn/go-sqlite3", "sqlite"),
+    ("github.com/segmentio/kafka-go", "kafka"),
+    ("github.com/graphql-go/graphql", "graphql"),
+];
+
+// Maven 坐标 "group:artifact"，只写 group 时匹配该 group 及其子 group 下的所有 artifact
+const JVM_DEPS: DepTable = &[
+    ("org.springframework", "spring"),
+    ("org.hibernate", "hibernate"),
+    ("mysql:mysql-connector-java", "mysql"),
+    ("com.mysql:mysql-connector-j", "mysql"),
+    ("org.postgresql:postgresql", "postgresql"),
+    ("org.jetbrains.kotlin", "kotlin"),
+    ("com.android", "android"),
+    ("org.apache.kafka:kafka-clients", "kafka"),
+    ("org.seleniumhq.selenium", "selenium"),
+    ("io.quarkus", "quarkus"),
+];
+
+const RUBY_DEPS: DepTable = &[
+    ("rails", "rails"),
+    ("sinatra", "sinatra"),
+    ("pg", "postgresql"),
+    ("mysql2", "mysql"),
+    ("redis", "redis"),
+    ("sidekiq", "sidekiq"),
+    ("jekyll", "jekyll"),
+    ("rubocop", "rubocop"),
+];
+
+const PHP_DEPS: DepTable = &[
+    ("laravel/framework", "laravel"),
+    ("symfony/framework-bundle", "symfony"),
+    ("symfony/symfony", "symfony"),
+    ("cakephp/cakephp", "cakephp"),
+    ("yiisoft/yii2", "yii"),
+    ("codeigniter4/framework", "codeigniter"),
+    ("drupal/core", "drupal"),
+    ("doctrine/orm", "doctrine"),
+];
+
+// Docker 镜像名 -> 技术名
+const IMAGES: DepTable = &[
+    ("node", "nodejs"),
+    ("python", "python"),
+    ("golang", "go"),
+    ("rust", "rust"),
+    ("openjdk", "java"),
+    ("eclipse-temurin", "java"),
+    ("ruby", "ruby"),
+    ("php", "php"),
+    ("nginx", "nginx"),
+    ("httpd", "apache"),
+    ("postgres", "postgresql"),
+    ("mysql", "mysql"),
+    ("mariadb", "mariadb"),
+    ("mongo", "mongodb"),
+    ("redis", "redis"),
+    ("rabbitmq", "rabbitmq"),
+    ("elasticsearch", "elasticsearch"),
+    ("grafana/grafana", "grafana"),
+    ("prom/prometheus", "prometheus"),
+    ("ubuntu", "ubuntu"),
+    ("debian", "debian"),
+];
+
+// Terraform provider -> 技术名
+const PROVIDERS: DepTable = &[
+    ("aws", "aws"),
+    ("azurerm", "azure"),
+    ("kubernetes", "kubernetes"),
+    ("cloudflare", "cloudflare"),
+    ("digitalocean", "digital-ocean"),
+    ("heroku", "heroku"),
+    ("docker", "docker"),
+];
+
+fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+// 依赖名与表中的名字相同，或者是它下面的路径（Go 的 "github.com/go-redis/redis/v8"）
+fn match_names(names: &[String], table: DepTable, rel: &str, collector: &mut Collector) {
+    let mut hit: Vec<&str> = Vec::new();
+    for name in names {
+        let name = name.to_lowercase();
+        for &(dep, tech) in table {
+            let matched = name == dep || name.starts_with(&format!("{}/", dep));
+            if matched && !hit.contains(&tech) {
+                hit.push(tech);
+            }
+        }
+    }
+    for tech in hit {
+        collector.mention(tech, rel);
+    }
+}
+
+// 引号以外第一个 `target` 的位置
+fn unquoted(text: &str, target: char) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == target => return Some(i),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None => {}
+        }
+    }
+    None
+}
+
+// 去掉注释后的行
+fn strip_comment(line: &str) -> &str {
+    line[..unquoted(line, '#').unwrap_or(line.len())].trim()
+}
+
+// 需求字符串中的包名："Django[argon2]>=4.0; python_version>'3'" -> "Django"
+fn requirement_name(spec: &str) -> &str {
+    let spec = spec.trim();
+    let end = spec
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-_.".contains(c)))
+        .unwrap_or(spec.len());
+    &spec[..end]
+}
+
+// 带引号的字符串
+fn quoted(text: &str) -> Vec<&str> {
+    let mut rs = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(['"', '\'']) {
+        let quote = rest[start..].chars().next().unwrap_or('"');
+        let Some(len) = rest[start + 1..].find(quote) else {
+            break;
+        };
+        rs.push(&rest[start + 1..start + 1 + len]);
+        rest = &rest[start + len + 2..];
+    }
+    rs
+}
+
+// TOML 中表名满足 `wanted` 的表里的键；`[dependencies.serde]` 这种表名本身就是一个依赖
+fn toml_keys(text: &str, wanted: impl Fn(&str) -> bool) -> Vec<String> {
+    let mut rs = Vec::new();
+    let mut inside = false;
+    for line in text.lines() {
+        let line = strip_comment(line);
+        if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let table = table.trim_matches(['[', ']']).trim();
+            inside = wanted(table);
+            if let Some((parent, name)) = table.rsplit_once('.') {
+                if !inside && wanted(parent) {
+                    rs.push(name.trim_matches(['"', '\'']).to_string());
+                }
+            }
+            continue;
+        }
+        if inside {
+            if let Some((key, _)) = line.split_once('=') {
+                rs.push(key.trim().trim_matches(['"', '\'']).to_string());
+            }
+        }
+    }
+    rs
+}
+
+// `key = [...]` 或 `key=[...]` 列表中的需求，列表可以跨行
+fn requirement_list(text: &str, key: &str) -> Vec<String> {
+    let mut rs = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(key) {
+        let after = &rest[start + key.len()..];
+        rest = after;
+        // 键必须是完整的标识符
+        let before = text.len() - after.len() - key.len();
+        if text[..before].ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            continue;
+        }
+        let Some(list) = after.trim_start().strip_prefix('=').map(str::trim_start) else {
+            continue;
+        };
+        let Some(list) = list.strip_prefix('[') else {
+            continue;
+        };
+        // 列表在引号以外的 `]` 处结束，extras 里的方括号在引号内
+        let list = &list[..unquoted(list, ']').unwrap_or(list.len())];
+        let lines: Vec<&str> = list.lines().map(strip_comment).collect();
+        for spec in quoted(&lines.join("\n")) {
+            rs.push(requirement_name(spec).to_string());
+        }
+    }
+    rs
+}
+
+// Cargo.toml 的 dependencies、dev-dependencies、build-dependencies、workspace.dependencies
+// 以及 target 专属的依赖表
+fn cargo_dependencies(text: &str) -> Vec<String> {
+    toml_keys(text, |table| {
+        table == "workspace.dependencies"
+            || ["dependencies", "dev-dependencies", "build-dependencies"]
+                .iter()
+                .any(|t| table == *t || table.starts_with("target.") && table.ends_with(&format!(".{}", t)))
+    })
+}
+
+// requirements.txt、pyproject.toml、Pipfile、setup.py 中声明的依赖
+fn python_dependencies(name: &str, text: &str) -> Vec<String> {
+    match name {
+        "requirements.txt" => text
+            .lines()
+            .map(strip_comment)
+            .filter(|l| !l.is_empty() && !l.starts_with('-'))
+            .map(|l| requirement_name(l).to_string())
+            .collect(),
+        "pipfile" => toml_keys(text, |table| table == "packages" || table == "dev-packages"),
+        "setup.py" => requirement_list(text, "install_requires"),
+        _ => {
+            // PEP 621 的 dependencies 和 optional-dependencies，以及 Poetry 的依赖表
+            let mut rs = requirement_list(text, "dependencies");
+            let mut inside = false;
+            for line in text.lines() {
+                let line = strip_comment(line);
+                if line.starts_with('[') {
+                    inside = line == "[project.optional-dependencies]";
+                } else if inside {
+                    rs.extend(quoted(line).into_iter().map(|s| requirement_name(s).to_string()));
+                }
+            }
+            rs.extend(toml_keys(text, |table| {
+                table.starts_with("tool.poetry.")
+                    && (table.ends_with(".dependencies") || table.ends_with(".dev-dependencies"))
+            }));
+            rs.retain(|n| n != "python");
+            rs
+        }
+    }
+}
+
+// go.mod 的 require 行和 require (...) 块
+fn go_dependencies(text: &str) -> Vec<String> {
+    let mut rs = Vec::new();
+    let mut block = false;
+    for line in text.lines() {
+        let line = line.split("//").next().unwrap_or(line).trim();
+        if block {
+            if line.starts_with(')') {
+                block = false;
+            } else if let Some(module) = line.split_whitespace().next() {
+                rs.push(module.to_string());
+            }
+        } else if let Some(rest) = line.strip_prefix("require") {
+            let rest = rest.trim();
+            if rest.starts_with('(') {
+                block = true;
+            } else if let Some(module) = rest.split_whitespace().next() {
+                rs.push(module.to_string());
+            }
+        }
+    }
+    rs
+}
+
+// Gemfile 的 gem '...' 行
+fn ruby_dependencies(text: &str) -> Vec<String> {
+    text.lines()
+        .map(strip_comment)
+        .filter_map(|line| line.strip_prefix("gem "))
+        .filter_map(|rest| quoted(rest).first().map(|s| s.to_string()))
+        .collect()
+}
+
+// pom.xml 中每个 dependency/plugin/parent 的 groupId 和 artifactId 拼成 "group:artifact"
+fn pom_coordinates(text: &str) -> Vec<String> {
+    let value = |block: &str, tag: &str| -> Option<String> {
+        let open = format!("<{}>", tag);
+        let start = block.find(&open)? + open.len();
+        let end = start + block[start..].find('<')?;
+        Some(block[start..end].trim().to_string())
+    };
+    let mut coordinates = Vec::new();
+    for tag in ["dependency", "plugin", "parent", "extension"] {
+        let close = format!("</{}>", tag);
+        for block in text.split(&format!("<{}>", tag)).skip(1) {
+            let block = block.split(&close).next().unwrap_or(block);
+            if let (Some(group), Some(artifact)) = (value(block, "groupId"), value(block, "artifactId")) {
+                coordinates.push(format!("{}:{}", group, artifact));
+            }
+        }
+    }
+    coordinates
+}
+
+// Gradle 中的 "group:artifact:version" 字符串去掉版本，带点的插件 id 当作 group
+fn gradle_coordinates(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_ascii_alphanumeric() || "-_.:".contains(c)))
+        .filter(|t| t.contains('.') || t.contains(':'))
+        .map(|t| t.splitn(3, ':').take(2).collect::<Vec<_>>().join(":"))
+        .collect()
+}
+
+fn match_coordinates(coordinates: &[String], rel: &str, collector: &mut Collector) {
+    let mut hit: Vec<&str> = Vec::new();
+    for coordinate in coordinates {
+        let coordinate = coordinate.to_lowercase();
+        let (group, artifact) = coordinate.split_once(':').unwrap_or((coordinate.as_str(), ""));
+        for &(dep, tech) in JVM_DEPS {
+            let matched = match dep.split_once(':') {
+                Some((g, a)) => group == g && artifact == a,
+                None => group == dep || group.starts_with(&format!("{}.", dep)),
+            };
+            if matched && !hit.contains(&tech) {
+                hit.push(tech);
+            }
+        }
+    }
+    for tech in hit {
+        collector.mention(tech, rel);
+    }
+}
+
+fn match_json_deps(text: &str, keys: &[&str], table: DepTable, rel: &str, collector: &mut Collector) {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(text) else {
+        return;
+    };
+    let mut hit: Vec<&str> = Vec::new();
+    for key in keys {
+        let Some(deps) = value.get(key).and_then(|d| d.as_object()) else {
+            continue;
+        };
+        for dep in deps.keys() {
+            if let Some(&(_, tech)) = table.iter().find(|(d, _)| d == dep) {
+                if !hit.contains(&tech) {
+                    hit.push(tech);
+                }
+            }
+        }
+    }
+    for tech in hit {
+        collector.mention(tech, rel);
+    }
+}
+
+fn match_images(text: &str, rel: &str, collector: &mut Collector) {
+    for line in text.lines() {
+        let line = line.trim();
+        let image = if let Some(rest) = line.strip_prefix("FROM ").or(line.strip_prefix("from ")) {
+            rest.split_whitespace().find(|p| !p.starts_with("--"))
+        } else {
+            line.strip_prefix("image:").map(|rest| rest.trim().trim_matches(['"', '\'']))
+        };
+        let Some(image) = image else {
+            continue;
+        };
+        // library/node:20-alpine -> node
+        let image = image.split([':', '@']).next().unwrap_or(image);
+        let image = image.strip_prefix("library/").unwrap_or(image);
+        let image = image.strip_prefix("docker.io/").unwrap_or(image);
+        if let Some(&(_, tech)) = IMAGES.iter().find(|(name, _)| *name == image) {
+            collector.mention(tech, rel);
+        }
+    }
+}
+
+// 判断 YAML 是否为 k8s 资源
+fn is_k8s(text: &str) -> bool {
+    let mut api = false;
+    let mut kind = false;
+    for line in text.lines() {
+        api |= line.starts_with("apiVersion:");
+        kind |= line.starts_with("kind:");
+    }
+    api && kind
+}
+
+fn inspect(path: &Path, name: &str, rel: &str, collector: &mut Collector) {
+    let lower = name.to_lowercase();
+    match lower.as_str() {
+        "cargo.toml" => {
+            collector.mention("rust", rel);
+            if let Some(text) = read(path) {
+                match_names(&cargo_dependencies(&text), CARGO_DEPS, rel, collector);
+            }
+        }
+        "package.json" => {
+            collector.mention("nodejs", rel);
+            collector.mention("npm", rel);
+            if let Some(text) = read(path) {
+                match_json_deps(&text, &["dependencies", "devDependencies", "peerDependencies"], NPM_DEPS, rel, collector);
+            }
+        }
+        "yarn.lock" => collector.mention("yarn", rel),
+        "requirements.txt" | "pyproject.toml" | "pipfile" | "setup.py" => {
+            collector.mention("python", rel);
+            if let Some(text) = read(path) {
+                match_names(&python_dependencies(&lower, &text), PYTHON_DEPS, rel, collector);
+            }
+        }
+        "go.mod" => {
+            collector.mention("go", rel);
+            if let Some(text) = read(path) {
+                match_names(&go_dependencies(&text), GO_DEPS, rel, collector);
+            }
+        }
+        "pom.xml" => {
+            collector.mention("java", rel);
+            collector.mention("maven", rel);
+            if let Some(text) = read(path) {
+                match_coordinates(&pom_coordinates(&text), rel, collector);
+            }
+        }
+        "build.gradle" | "build.gradle.kts" | "settings.gradle" | "settings.gradle.kts" => {
+            collector.mention("gradle", rel);
+            if lower.ends_with(".kts") {
+                collector.mention("kotlin", rel);
+            }
+            if let Some(text) = read(path) {
+                match_coordinates(&gradle_coordinates(&text), rel, collector);
+            }
+        }
+        "gemfile" => {
+            collector.mention("ruby", rel);
+            if let Some(text) = read(path) {
+                match_names(&ruby_dependencies(&text), RUBY_DEPS, rel, collector);
+            }
+        }
+        "composer.json" => {
+            collector.mention("php", rel);
+            collector.mention("composer", rel);
+            if let Some(text) = read(path) {
+                match_json_deps(&text, &["require", "require-dev"], PHP_DEPS, rel, collector);
+            }
+        }
+        "pubspec.yaml" => {
+            collector.mention("dart", rel);
+            if read(path).is_some_and(|t| t.contains("flutter")) {
+                collector.mention("flutter", rel);
+            }
+        }
+        "dockerfile" | "containerfile" => {
+            collector.mention("docker", rel);
+            if let Some(text) = read(path) {
+                match_images(&text, rel, collector);
+            }
+        }
+        "docker-compose.yml" | "docker-compose.yaml" | "compose.yml" | "compose.yaml" => {
+            collector.mention("docker", rel);
+            if let Some(text) = read(path) {
+                match_images(&text, rel, collector);
+            }
+        }
+        "chart.yaml" => collector.mention("kubernetes", rel),
+        "jenkinsfile" => collector.mention("jenkins", rel),
+        ".gitlab-ci.yml" => collector.mention("gitlab", rel),
+        ".travis.yml" => collector.mention("travis-ci", rel),
+        "vagrantfile" => collector.mention("vagrant", rel),
+        "nginx.conf" => collector.mention("nginx", rel),
+        "deno.json" | "deno.jsonc" => collector.mention("deno", rel),
+        _ => {
+            if lower.ends_with(".dockerfile") {
+                collector.mention("docker", rel);
+            } else if lower.ends_with(".tf") {
+                collector.mention("terraform", rel);
+                if let Some(text) = read(path) {
+                    match_providers(&text, rel, collector);
+                }
+            } else if lower.ends_with(".yaml") || lower.ends_with(".yml") {
+                if read(path).is_some_and(|t| is_k8s(&t)) {
+                    collector.mention("kubernetes", rel);
+                }
+            } else if lower.ends_with(".csproj") || lower.ends_with(".sln") {
+                collector.mention("dotnet", rel);
+            }
+        }
+    }
+}
+
+// provider "aws" { ... } 和 required_providers 中的 source = "hashicorp/aws"
+fn match_providers(text: &str, rel: &str, collector: &mut Collector) {
+    for line in text.lines() {
+        let line = line.trim();
+        let name = if let Some(rest) = line.strip_prefix("provider ") {
+            rest.split('"').nth(1)
+        } else if line.starts_with("source") && line.contains('=') {
+            line.split('"').nth(1).and_then(|s| s.rsplit('/').next())
+        } else {
+            None
+        };
+        if let Some(&(_, tech)) = name.and_then(|n| PROVIDERS.iter().find(|(p, _)| *p == n)) {
+            collector.mention(tech, rel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 在临时目录里写入一个清单文件后分析
+    fn detect(name: &str, text: &str) -> Vec<String> {
+        let dir = std::env::temp_dir().join(format!("techwall-analyze-{}-{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("app")).unwrap();
+        fs::write(dir.join("app").join(name), text).unwrap();
+        let analysis = analyze(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let mut names: Vec<String> = analysis.names().into_iter().map(String::from).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn maven_coordinates() {
+        let pom = r#"<project>
+  <groupId>com.example</groupId>
+  <artifactId>kotlin-demo</artifactId>
+  <dependencies>
+    <dependency>
+      <groupId>org.hibernate.orm</groupId>
+      <artifactId>hibernate-core</artifactId>
+    </dependency>
+    <dependency>
+      <groupId>io.quarkus</groupId>
+      <artifactId>quarkus-resteasy</artifactId>
+    </dependency>
+    <dependency>
+      <artifactId>selenium-java</artifactId>
+      <groupId>org.seleniumhq.selenium</groupId>
+    </dependency>
+    <dependency>
+      <groupId>com.example</groupId>
+      <artifactId>postgresql</artifactId>
+    </dependency>
+  </dependencies>
+</project>"#;
+        assert_eq!(detect("pom.xml", pom), ["hibernate", "java", "maven", "quarkus", "selenium"]);
+    }
+
+    #[test]
+    fn gradle_coordinates_and_plugins() {
+        let gradle = r#"plugins {
+    id("org.jetbrains.kotlin.jvm") version "1.9.0"
+}
+dependencies {
+    implementation("org.hibernate:hibernate-core:6.4.0.Final")
+    implementation 'org.postgresql:postgresql:42.7.1'
+    testImplementation "org.seleniumhq.selenium:selenium-java:4.16.1"
+    implementation("com.example:kafka-clients:1.0")
+}"#;
+        assert_eq!(
+            detect("build.gradle", gradle),
+            ["gradle", "hibernate", "kotlin", "postgresql", "selenium"]
+        );
+    }
+
+    #[test]
+    fn names_match_whole_dependencies() {
+        let mut collector = Collector::default();
+        let names = ["redis-extra".to_string(), "tokio-postgres".to_string()];
+        match_names(&names, CARGO_DEPS, "Cargo.toml", &mut collector);
+        assert_eq!(collector.finish().names(), ["postgresql"]);
+    }
+
+    #[test]
+    fn cargo_dependency_tables_only() {
+        let cargo = r#"[package]
+name = "redis-dashboard"
+description = "a mongodb and kafka viewer"
+authors = ["postgres fan"]
+
+[dependencies]
+tokio-postgres = "0.7"  # not mysql
+serde = { version = "1", features = ["derive"] }
+
+[dev-dependencies.rusqlite]
+version = "0.30"
+
+[target.'cfg(unix)'.build-dependencies]
+rdkafka = "0.36"
+
+[features]
+redis = []
+"#;
+        assert_eq!(detect("Cargo.toml", cargo), ["kafka", "postgresql", "rust", "sqlite"]);
+    }
+
+    #[test]
+    fn python_dependency_entries_only() {
+        let pyproject = r#"[project]
+name = "redis-tools"
+description = "Works with flask and django"
+dependencies = [
+    "psycopg2-binary>=2.9",  # postgres
+    "flask-extras @ https://example.com/flask.zip#egg=flask-extras",
+    "Pika[async]; python_version > '3.8'",
+]
+
+[project.optional-dependencies]
+notebook = ["jupyter"]
+
+[project.urls]
+Homepage = "https://github.com/example/selenium"
+"#;
+        assert_eq!(detect("pyproject.toml", pyproject), ["jupyter", "postgresql", "python", "rabbitmq"]);
+        assert_eq!(requirement_list(pyproject, "dependencies")[1], "flask-extras");
+        let requirements = "# uses redis at runtime\n-r base.txt\nDjango>=4.0\ngunicorn==21.2\n";
+        assert_eq!(detect("requirements.txt", requirements), ["django", "gunicorn", "python"]);
+        let setup = "setup(\n    name='flask-extras',\n    install_requires=['selenium>=4', \"redis\"],\n)\n";
+        assert_eq!(detect("setup.py", setup), ["python", "redis", "selenium"]);
+    }
+
+    #[test]
+    fn go_and_ruby_requirements_only() {
+        let gomod = "module github.com/example/redis-proxy\n\ngo 1.22\n\nrequire github.com/lib/pq v1.10.9\n\nrequire (\n\tk8s.io/client-go v0.29.0 // indirect\n)\n";
+        assert_eq!(detect("go.mod", gomod), ["go", "kubernetes", "postgresql"]);
+        let gemfile = "source 'https://rubygems.org'\n# redis is optional\ngem 'rails', '~> 7.1'\ngem \"pg\"\n";
+        assert_eq!(detect("Gemfile", gemfile), ["postgresql", "rails", "ruby"]);
+    }
+
+    #[test]
+    fn missing_root_is_an_error() {
+        assert!(analyze(Path::new("/nonexistent/techwall")).is_err());
+    }
+}
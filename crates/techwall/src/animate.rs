@@ -0,0 +1,35 @@
+//! 动画输出：把每一帧合成器渲染后编码为循环播放的 GIF
+use anyhow::Result;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use crate::compose::Compositor;
+
+// GIF 量化速度，1 最慢质量最好，30 最快
+const SPEED: i32 = 10;
+
+/// `delay` 为每帧的毫秒数
+pub fn encode_gif(frames: &[Compositor], delay: u32) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = GifEncoder::new_with_speed(&mut out, SPEED);
+        encoder.set_repeat(Repeat::Infinite)?;
+        for compositor in frames {
+            let pixmap = compositor.render()?;
+            // tiny-skia 为预乘格式
+            let data: Vec<u8> = pixmap
+                .pixels()
+                .iter()
+                .flat_map(|p| {
+                    let c = p.demultiply();
+                    [c.red(), c.green(), c.blue(), c.alpha()]
+                })
+                .collect();
+            let Some(image) = RgbaImage::from_raw(pixmap.width(), pixmap.height(), data) else {
+                continue;
+            };
+            encoder.encode_frame(Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(delay, 1)))?;
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,474 @@
+//! 画布背景：纯色、渐变、图片（平铺、模糊）和程序生成的图案。
+//! 对应前端 `front/public/background` 里的背景图和 `Frame.isColor` 的纯色
+use std::fmt::Write;
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::RgbaImage;
+use resvg::tiny_skia::{
+    Color, ColorU8, FillRule, FilterQuality, GradientStop, LinearGradient, Paint, PathBuilder, Pattern, Pixmap,
+    PixmapPaint, Point, RadialGradient, Rect, Shader, SpreadMode, Transform,
+};
+
+use crate::compose::{color_to_hex, parse_color};
+use crate::png::to_pixmap;
+use crate::rng::Rng;
+
+/// 背景图片的缩放方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Fit {
+    /// 铺满画布，超出部分裁掉
+    #[default]
+    Cover,
+    /// 完整显示，居中
+    Contain,
+    /// 原尺寸平铺
+    Tile,
+}
+
+impl Fit {
+    pub fn parse(name: &str) -> Option<Fit> {
+        match name {
+            "cover" => Some(Fit::Cover),
+            "contain" => Some(Fit::Contain),
+            "tile" => Some(Fit::Tile),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Background {
+    Solid(Color),
+    /// 角度与 CSS 的 `linear-gradient` 一致：0 从下到上，90 从左到右
+    Linear { angle: f32, stops: Vec<Color> },
+    /// 从中心到角落
+    Radial { stops: Vec<Color> },
+    /// `blur` 为高斯模糊的 sigma，0 不模糊
+    Image { pixmap: Pixmap, fit: Fit, blur: f32 },
+    Dots { color: Color, base: Color, spacing: f32, radius: f32 },
+    Grid { color: Color, base: Color, spacing: f32, width: f32 },
+    /// 在底色上叠加随机亮度，`amount` 为 0 到 1
+    Noise { base: Color, amount: f32, seed: u64 },
+}
+
+impl Background {
+    /// 除图片外的背景：
+    /// "#101018"、"linear:135:#1e1e2e:#89b4fa"、"radial:#313244:#11111b"、
+    /// "dots:#ffffff33:#101018:24:2"、"grid:#ffffff22:#101018:32:1"、"noise:#101018:0.08:1"
+    pub fn parse(spec: &str) -> Option<Background> {
+        if spec.starts_with('#') {
+            return parse_color(spec).map(Background::Solid);
+        }
+        let mut parts = spec.split(':');
+        let kind = parts.next()?;
+        let rest: Vec<&str> = parts.collect();
+        let number = |i: usize, default: f32| match rest.get(i) {
+            Some(s) => s.parse::<f32>().ok().filter(|n| n.is_finite() && *n >= 0.0),
+            None => Some(default),
+        };
+        let colors = |from: usize| -> Option<Vec<Color>> {
+            let stops = rest[from.min(rest.len())..].iter().map(|c| parse_color(c)).collect::<Option<Vec<_>>>()?;
+            (stops.len() >= 2).then_some(stops)
+        };
+        let rs = match kind {
+            "linear" => Background::Linear {
+                angle: rest.first()?.parse().ok()?,
+                stops: colors(1)?,
+            },
+            "radial" => Background::Radial { stops: colors(0)? },
+            "dots" if rest.len() <= 4 => Background::Dots {
+                color: parse_color(rest.first()?)?,
+                base: parse_color(rest.get(1)?)?,
+                spacing: number(2, 24.0)?.max(1.0),
+                radius: number(3, 2.0)?,
+            },
+            "grid" if rest.len() <= 4 => Background::Grid {
+                color: parse_color(rest.first()?)?,
+                base: parse_color(rest.get(1)?)?,
+                spacing: number(2, 32.0)?.max(1.0),
+                width: number(3, 1.0)?,
+            },
+            "noise" if rest.len() <= 3 => Background::Noise {
+                base: parse_color(rest.first()?)?,
+                amount: number(1, 0.08)?.min(1.0),
+                seed: rest.get(2).map_or(Some(0), |s| s.parse().ok())?,
+            },
+            _ => return None,
+        };
+        Some(rs)
+    }
+
+    /// PNG、JPG 等背景图片
+    pub fn image(data: &[u8], fit: Fit, blur: f32) -> Result<Background> {
+        let img = image::load_from_memory(data)?.to_rgba8();
+        Ok(Background::Image {
+            pixmap: to_pixmap(&img)?,
+            fit,
+            blur: blur.max(0.0),
+        })
+    }
+
+    /// 背景的平均颜色，用于判断图标与背景的对比度
+    pub fn average(&self) -> Color {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Linear { stops, .. } | Background::Radial { stops } => mix(stops),
+            Background::Dots { base, .. } | Background::Grid { base, .. } | Background::Noise { base, .. } => *base,
+            Background::Image { pixmap, .. } => {
+                let pixels: Vec<Color> = pixmap
+                    .pixels()
+                    .iter()
+                    .step_by(7)
+                    .map(|p| {
+                        let c = p.demultiply();
+                        Color::from_rgba8(c.red(), c.green(), c.blue(), c.alpha())
+                    })
+                    .collect();
+                mix(&pixels)
+            }
+        }
+    }
+
+    pub fn paint(&self, pixmap: &mut Pixmap) {
+        let (width, height) = (pixmap.width() as f32, pixmap.height() as f32);
+        let Some(rect) = Rect::from_xywh(0.0, 0.0, width, height) else {
+            return;
+        };
+        match self {
+            Background::Solid(color) => pixmap.fill(*color),
+            Background::Linear { angle, stops } => {
+                let (start, end) = linear_points(*angle, width, height);
+                if let Some(shader) = LinearGradient::new(start, end, gradient_stops(stops), SpreadMode::Pad, Transform::identity()) {
+                    fill(pixmap, rect, shader);
+                }
+            }
+            Background::Radial { stops } => {
+                let center = Point::from_xy(width / 2.0, height / 2.0);
+                let radius = (width * width + height * height).sqrt() / 2.0;
+                if let Some(shader) =
+                    RadialGradient::new(center, center, radius, gradient_stops(stops), SpreadMode::Pad, Transform::identity())
+                {
+                    fill(pixmap, rect, shader);
+                }
+            }
+            Background::Image { pixmap: image, fit, blur } => {
+                let (w, h) = (image.width() as f32, image.height() as f32);
+                match fit {
+                    Fit::Tile => {
+                        let shader = Pattern::new(
+                            image.as_ref(),
+                            SpreadMode::Repeat,
+                            FilterQuality::Nearest,
+                            1.0,
+                            Transform::identity(),
+                        );
+                        fill(pixmap, rect, shader);
+                    }
+                    Fit::Cover | Fit::Contain => {
+                        let scale = match fit {
+                            Fit::Cover => (width / w).max(height / h),
+                            _ => (width / w).min(height / h),
+                        };
+                        let transform = Transform::from_row(
+                            scale,
+                            0.0,
+                            0.0,
+                            scale,
+                            (width - w * scale) / 2.0,
+                            (height - h * scale) / 2.0,
+                        );
+                        let paint = PixmapPaint {
+                            quality: FilterQuality::Bicubic,
+                            ..Default::default()
+                        };
+                        pixmap.draw_pixmap(0, 0, image.as_ref(), &paint, transform, None);
+                    }
+                }
+                if *blur > 0.0 {
+                    blur_pixmap(pixmap, *blur);
+                }
+            }
+            Background::Dots {
+                color,
+                base,
+                spacing,
+                radius,
+            } => {
+                pixmap.fill(*base);
+                let mut pb = PathBuilder::new();
+                for y in grid_lines(height, *spacing) {
+                    for x in grid_lines(width, *spacing) {
+                        pb.push_circle(x, y, *radius);
+                    }
+                }
+                if let Some(path) = pb.finish() {
+                    pixmap.fill_path(&path, &solid(*color), FillRule::Winding, Transform::identity(), None);
+                }
+            }
+            Background::Grid {
+                color,
+                base,
+                spacing,
+                width: line,
+            } => {
+                pixmap.fill(*base);
+                let mut pb = PathBuilder::new();
+                for x in grid_lines(width, *spacing) {
+                    if let Some(r) = Rect::from_xywh(x - line / 2.0, 0.0, *line, height) {
+                        pb.push_rect(r);
+                    }
+                }
+                for y in grid_lines(height, *spacing) {
+                    if let Some(r) = Rect::from_xywh(0.0, y - line / 2.0, width, *line) {
+                        pb.push_rect(r);
+                    }
+                }
+                if let Some(path) = pb.finish() {
+                    pixmap.fill_path(&path, &solid(*color), FillRule::Winding, Transform::identity(), None);
+                }
+            }
+            Background::Noise { base, amount, seed } => {
+                pixmap.fill(*base);
+                let mut rng = Rng::new(*seed);
+                let range = (amount * 255.0).round() as i32;
+                for p in pixmap.pixels_mut() {
+                    let c = p.demultiply();
+                    // 三个通道同一偏移，只改变亮度
+                    let delta = rng.below(range as u32 * 2 + 1) as i32 - range;
+                    let channel = |v: u8| (v as i32 + delta).clamp(0, 255) as u8;
+                    *p = ColorU8::from_rgba(channel(c.red()), channel(c.green()), channel(c.blue()), c.alpha()).premultiply();
+                }
+            }
+        }
+    }
+
+    /// SVG 片段，纯色和渐变输出矢量，其余背景栅格化后以 data url 嵌入
+    pub fn to_svg(&self, width: u32, height: u32) -> Result<String> {
+        let mut out = String::new();
+        match self {
+            Background::Solid(color) => write!(
+                out,
+                r#"<rect width="100%" height="100%" fill="{}" fill-opacity="{}"/>"#,
+                color_to_hex(*color),
+                color.alpha()
+            )?,
+            Background::Linear { angle, stops } => {
+                let (start, end) = linear_points(*angle, width as f32, height as f32);
+                write!(
+                    out,
+                    r#"<defs><linearGradient id="bg" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">{}</linearGradient></defs>"#,
+                    start.x,
+                    start.y,
+                    end.x,
+                    end.y,
+                    svg_stops(stops)
+                )?;
+                out.push_str(r#"<rect width="100%" height="100%" fill="url(#bg)"/>"#);
+            }
+            Background::Radial { stops } => {
+                let radius = ((width * width + height * height) as f32).sqrt() / 2.0;
+                write!(
+                    out,
+                    r#"<defs><radialGradient id="bg" gradientUnits="userSpaceOnUse" cx="{0}" cy="{1}" fx="{0}" fy="{1}" r="{2}">{3}</radialGradient></defs>"#,
+                    width as f32 / 2.0,
+                    height as f32 / 2.0,
+                    radius,
+                    svg_stops(stops)
+                )?;
+                out.push_str(r#"<rect width="100%" height="100%" fill="url(#bg)"/>"#);
+            }
+            _ => {
+                let mut pixmap = Pixmap::new(width, height).ok_or(anyhow!("incorrect size"))?;
+                self.paint(&mut pixmap);
+                write!(
+                    out,
+                    r#"<image width="{}" height="{}" href="data:image/png;base64,{}"/>"#,
+                    width,
+                    height,
+                    STANDARD.encode(pixmap.encode_png()?)
+                )?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn solid(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(color);
+    paint.anti_alias = true;
+    paint
+}
+
+fn fill(pixmap: &mut Pixmap, rect: Rect, shader: Shader) {
+    let paint = Paint {
+        shader,
+        ..Default::default()
+    };
+    pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+}
+
+// 从半个间距开始，画布边缘不会出现半个点
+fn grid_lines(length: f32, spacing: f32) -> impl Iterator<Item = f32> {
+    let count = (length / spacing).ceil() as u32;
+    (0..count).map(move |i| (i as f32 + 0.5) * spacing)
+}
+
+// CSS 的渐变线：穿过中心，长度保证角落正好是首尾颜色
+fn linear_points(angle: f32, width: f32, height: f32) -> (Point, Point) {
+    let rad = angle.to_radians();
+    let (dx, dy) = (rad.sin(), -rad.cos());
+    let half = (width * dx.abs() + height * dy.abs()) / 2.0;
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    (
+        Point::from_xy(cx - dx * half, cy - dy * half),
+        Point::from_xy(cx + dx * half, cy + dy * half),
+    )
+}
+
+fn offsets(stops: &[Color]) -> impl Iterator<Item = (f32, Color)> + '_ {
+    let last = (stops.len() - 1).max(1) as f32;
+    stops.iter().enumerate().map(move |(i, c)| (i as f32 / last, *c))
+}
+
+fn gradient_stops(stops: &[Color]) -> Vec<GradientStop> {
+    offsets(stops).map(|(t, c)| GradientStop::new(t, c)).collect()
+}
+
+fn svg_stops(stops: &[Color]) -> String {
+    offsets(stops)
+        .map(|(t, c)| {
+            format!(
+                r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+                t,
+                color_to_hex(c),
+                c.alpha()
+            )
+        })
+        .collect()
+}
+
+fn mix(colors: &[Color]) -> Color {
+    let total: f32 = colors.iter().map(|c| c.alpha()).sum();
+    if total <= 0.0 {
+        return Color::TRANSPARENT;
+    }
+    let channel = |f: fn(&Color) -> f32| colors.iter().map(|c| f(c) * c.alpha()).sum::<f32>() / total;
+    Color::from_rgba(
+        channel(Color::red),
+        channel(Color::green),
+        channel(Color::blue),
+        total / colors.len() as f32,
+    )
+    .unwrap_or(Color::TRANSPARENT)
+}
+
+// 预乘后的数据直接模糊，透明边缘不会发黑
+fn blur_pixmap(pixmap: &mut Pixmap, sigma: f32) {
+    let Some(img) = RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixmap.data().to_vec()) else {
+        return;
+    };
+    let blurred = image::imageops::fast_blur(&img, sigma);
+    for (dst, src) in pixmap.data_mut().chunks_exact_mut(4).zip(blurred.as_raw().chunks_exact(4)) {
+        // 取整误差可能让颜色略大于透明度
+        let alpha = src[3];
+        dst.copy_from_slice(&[src[0].min(alpha), src[1].min(alpha), src[2].min(alpha), alpha]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 左半红、右半蓝的 20 x 10 图片
+    fn halves() -> Pixmap {
+        let mut pix = Pixmap::new(20, 10).unwrap();
+        for (i, p) in pix.pixels_mut().iter_mut().enumerate() {
+            *p = if i % 20 < 10 { ColorU8::from_rgba(255, 0, 0, 255) } else { ColorU8::from_rgba(0, 0, 255, 255) }
+                .premultiply();
+        }
+        pix
+    }
+
+    fn painted(background: &Background, width: u32, height: u32) -> Pixmap {
+        let mut pix = Pixmap::new(width, height).unwrap();
+        background.paint(&mut pix);
+        pix
+    }
+
+    fn at(pix: &Pixmap, x: u32, y: u32) -> (u8, u8, u8, u8) {
+        let c = pix.pixel(x, y).unwrap().demultiply();
+        (c.red(), c.green(), c.blue(), c.alpha())
+    }
+
+    const RED: (u8, u8, u8, u8) = (255, 0, 0, 255);
+    const BLUE: (u8, u8, u8, u8) = (0, 0, 255, 255);
+
+    #[test]
+    fn image_fit_modes() {
+        let image = |fit: Fit| Background::Image {
+            pixmap: halves(),
+            fit,
+            blur: 0.0,
+        };
+        // 放大四倍铺满，左右各裁掉 20
+        let cover = painted(&image(Fit::Cover), 40, 40);
+        assert_eq!((at(&cover, 5, 0), at(&cover, 35, 39)), (RED, BLUE));
+        // 放大两倍居中，上下留空
+        let contain = painted(&image(Fit::Contain), 40, 40);
+        assert_eq!((at(&contain, 5, 20), at(&contain, 35, 20)), (RED, BLUE));
+        assert_eq!((at(&contain, 20, 2).3, at(&contain, 20, 37).3), (0, 0));
+        // 原尺寸重复
+        let tile = painted(&image(Fit::Tile), 40, 40);
+        assert_eq!((at(&tile, 25, 15), at(&tile, 15, 35), at(&tile, 35, 25)), (RED, BLUE, BLUE));
+
+        assert_eq!(Fit::parse("contain"), Some(Fit::Contain));
+        assert_eq!(Fit::parse("stretch"), None);
+    }
+
+    #[test]
+    fn gradients() {
+        let linear = Background::parse("linear:90:#000000:#ffffff").unwrap();
+        let pix = painted(&linear, 100, 10);
+        // 从左到右变亮
+        assert!(at(&pix, 0, 5).0 < 8 && at(&pix, 99, 5).0 > 247);
+        assert!((at(&pix, 50, 5).0 as i32 - 128).abs() < 8);
+        let svg = linear.to_svg(100, 10).unwrap();
+        assert!(svg.contains(r#"x1="0""#) && svg.contains(r#"x2="100""#), "{}", svg);
+        // 0 度从下到上
+        let (start, end) = linear_points(0.0, 100.0, 10.0);
+        assert!((start.y - 10.0).abs() < 1e-3 && end.y.abs() < 1e-3);
+
+        let radial = Background::parse("radial:#ffffff:#000000").unwrap();
+        let pix = painted(&radial, 40, 40);
+        assert!(at(&pix, 20, 20).0 > 240 && at(&pix, 0, 0).0 < 16);
+        assert!(radial.to_svg(40, 40).unwrap().contains("radialGradient"));
+        assert_eq!(color_to_hex(radial.average()), "#808080");
+
+        assert!(Background::parse("linear:90:#000000").is_none());
+        assert!(Background::parse("linear:up:#000000:#ffffff").is_none());
+    }
+
+    #[test]
+    fn patterns() {
+        let Some(Background::Dots { spacing, radius, .. }) = Background::parse("dots:#ffffff:#000000") else {
+            panic!("dots not parsed");
+        };
+        assert_eq!((spacing, radius), (24.0, 2.0));
+        let dots = Background::parse("dots:#ffffff:#000000:20:4").unwrap();
+        let pix = painted(&dots, 40, 40);
+        // 点的中心在半个间距处
+        assert_eq!((at(&pix, 10, 10), at(&pix, 30, 30)), ((255, 255, 255, 255), (255, 255, 255, 255)));
+        assert_eq!(at(&pix, 0, 0), (0, 0, 0, 255));
+        assert!(dots.to_svg(40, 40).unwrap().starts_with("<image"));
+
+        let noise = Background::parse("noise:#808080:0.1:7").unwrap();
+        let a = painted(&noise, 16, 16);
+        assert_eq!(a.data(), painted(&noise, 16, 16).data());
+        assert!(a.pixels().iter().all(|p| p.red().abs_diff(128) <= 26 && p.red() == p.blue()));
+
+        assert!(Background::parse("dots:#ffffff:#000000:20:4:1").is_none());
+        assert!(Background::parse("noise:#808080:-1").is_none());
+    }
+}
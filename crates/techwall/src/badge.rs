@@ -0,0 +1,298 @@
+//! shields 风格的徽章：左边深灰底放图标，右边是名字，底色取图标的主色。
+//! 多个徽章按最大宽度换行拼成一张图，也可以每个徽章单独输出
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::Result;
+use resvg::tiny_skia::Color;
+use usvg::fontdb;
+
+use crate::compose::{color_to_hex, Compositor, Layer, Source};
+use crate::overlay::{default_families, escape, system_fonts, Align, Text};
+use crate::recolor::{dominant, luminance, Recolor};
+
+// 图标所在的左半边
+const LEFT: &str = "#555555";
+const FAMILY: &str = "Verdana, DejaVu Sans, sans-serif";
+
+/// 徽章样式，与 shields.io 同名
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Style {
+    #[default]
+    Flat,
+    FlatSquare,
+    ForTheBadge,
+}
+
+// 高度、字号、字重、字间距、图标边长、图标两侧留白、文字两侧留白、圆角
+struct Metrics {
+    height: f32,
+    size: f32,
+    weight: u16,
+    spacing: f32,
+    icon: f32,
+    icon_pad: f32,
+    text_pad: f32,
+    radius: f32,
+}
+
+impl Style {
+    pub fn parse(name: &str) -> Option<Style> {
+        match name {
+            "flat" => Some(Style::Flat),
+            "flat-square" => Some(Style::FlatSquare),
+            "for-the-badge" => Some(Style::ForTheBadge),
+            _ => None,
+        }
+    }
+
+    fn metrics(self) -> Metrics {
+        match self {
+            Style::Flat | Style::FlatSquare => Metrics {
+                height: 20.0,
+                size: 11.0,
+                weight: 400,
+                spacing: 0.0,
+                icon: 14.0,
+                icon_pad: 5.0,
+                text_pad: 6.0,
+                radius: if self == Style::Flat { 3.0 } else { 0.0 },
+            },
+            Style::ForTheBadge => Metrics {
+                height: 28.0,
+                size: 10.0,
+                weight: 700,
+                spacing: 1.0,
+                icon: 16.0,
+                icon_pad: 9.0,
+                text_pad: 12.0,
+                radius: 0.0,
+            },
+        }
+    }
+}
+
+/// 一个徽章：图标、名字和右半边的底色
+pub struct Badge {
+    pub name: String,
+    pub label: String,
+    pub color: Color,
+    pub source: Arc<Source>,
+}
+
+impl Badge {
+    /// 底色默认为图标的主色
+    pub fn new(name: &str, label: &str, source: Source) -> Badge {
+        let color = dominant(&source).unwrap_or(Color::from_rgba8(0x00, 0x7e, 0xc6, 255));
+        Badge {
+            name: name.to_string(),
+            label: label.to_string(),
+            color,
+            source: Arc::new(source),
+        }
+    }
+}
+
+/// 一组徽章
+pub struct Badges {
+    pub style: Style,
+    /// 合成一张图时每行的最大宽度，0 为不换行
+    pub max_width: u32,
+    /// 徽章之间的间距
+    pub gap: u32,
+    /// 输出的缩放，PNG 需要更高的分辨率时使用
+    pub scale: f32,
+    pub items: Vec<Badge>,
+    fonts: Option<Arc<fontdb::Database>>,
+}
+
+impl Badges {
+    pub fn new(style: Style) -> Badges {
+        Badges {
+            style,
+            max_width: 0,
+            gap: 4,
+            scale: 1.0,
+            items: Vec::new(),
+            fonts: None,
+        }
+    }
+
+    // 第一次用到时加载系统字体
+    fn fonts(&mut self) -> &Arc<fontdb::Database> {
+        self.fonts.get_or_insert_with(system_fonts)
+    }
+
+    /// 添加 TTF/OTF 字体
+    pub fn load_font(&mut self, data: Vec<u8>) {
+        let fonts = Arc::make_mut(self.fonts.get_or_insert_with(system_fonts));
+        fonts.load_font_data(data);
+        default_families(fonts);
+    }
+
+    pub fn push(&mut self, badge: Badge) {
+        self.items.push(badge);
+    }
+
+    fn text(&self, label: &str) -> Text {
+        let m = self.style.metrics();
+        Text {
+            text: match self.style {
+                Style::ForTheBadge => label.to_uppercase(),
+                _ => label.to_string(),
+            },
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            size: m.size,
+            family: Some(FAMILY.to_string()),
+            weight: m.weight,
+            color: "#ffffff".to_string(),
+            align: Align::Center,
+            line_height: 1.0,
+            shadow: None,
+            stroke: None,
+        }
+    }
+
+    // 徽章的矢量图层（底色和文字）和图标图层，坐标为 (x, y) 处的徽章，返回宽度
+    fn layers(&self, badge: &Badge, x: f32, y: f32, fonts: &Arc<fontdb::Database>) -> Result<(Vec<Layer>, f32)> {
+        let m = self.style.metrics();
+        let text = self.text(&badge.label);
+        let opt = Text::options(fonts);
+        let chars = text.text.chars().count() as f32;
+        let label = text.measure(&text.text, &opt)? + m.spacing * chars;
+        let left = m.icon + m.icon_pad * 2.0;
+        let width = (left + label + m.text_pad * 2.0).round();
+        let h = m.height;
+
+        let c = badge.color.to_color_u8();
+        let ink = if luminance((c.red(), c.green(), c.blue())) > 0.65 { "#333333" } else { "#ffffff" };
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, h
+        )?;
+        if self.style == Style::Flat {
+            svg.push_str(r##"<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbbbbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>"##);
+        }
+        write!(
+            svg,
+            r#"<clipPath id="r"><rect width="{}" height="{}" rx="{}"/></clipPath><g clip-path="url(#r)">"#,
+            width, h, m.radius
+        )?;
+        write!(svg, r#"<rect width="{}" height="{}" fill="{}"/>"#, left, h, LEFT)?;
+        write!(
+            svg,
+            r#"<rect x="{}" width="{}" height="{}" fill="{}"/>"#,
+            left,
+            width - left,
+            h,
+            color_to_hex(badge.color)
+        )?;
+        if self.style == Style::Flat {
+            write!(svg, r#"<rect width="{}" height="{}" fill="url(#s)"/>"#, width, h)?;
+        }
+        svg.push_str("</g>");
+        write!(
+            svg,
+            r#"<g font-family="{}" font-size="{}" font-weight="{}" letter-spacing="{}" text-anchor="middle">"#,
+            escape(FAMILY),
+            m.size,
+            m.weight,
+            m.spacing
+        )?;
+        // 文字垂直居中，基线大约在字号的 0.35 倍处
+        let (tx, ty) = (left + (width - left) / 2.0 + m.spacing / 2.0, h / 2.0 + m.size * 0.35);
+        if self.style == Style::Flat && ink == "#ffffff" {
+            write!(
+                svg,
+                r##"<text x="{}" y="{}" fill="#010101" fill-opacity=".3">{}</text>"##,
+                tx,
+                ty + 1.0,
+                escape(&text.text)
+            )?;
+        }
+        write!(svg, r#"<text x="{}" y="{}" fill="{}">{}</text></g></svg>"#, tx, ty, ink, escape(&text.text))?;
+        let tree = usvg::Tree::from_str(&svg, &opt)?;
+
+        let s = self.scale;
+        let mut layers = vec![Layer {
+            source: Arc::new(Source::Vector(Box::new(tree))),
+            x: x * s,
+            y: y * s,
+            width: width * s,
+            height: h * s,
+            clip: None,
+            rotation: 0.0,
+        }];
+        // 图标在左半边居中，与深灰底对比度太低时反转亮度
+        let (iw, ih) = badge.source.size();
+        let fit = (m.icon / iw).min(m.icon / ih);
+        let background = Color::from_rgba8(0x55, 0x55, 0x55, 255);
+        let source = Recolor::AutoInvert
+            .apply(&badge.source, Some(background))
+            .map(Arc::new)
+            .unwrap_or_else(|_| badge.source.clone());
+        layers.push(Layer {
+            source,
+            x: (x + (left - iw * fit) / 2.0) * s,
+            y: (y + (h - ih * fit) / 2.0) * s,
+            width: iw * fit * s,
+            height: ih * fit * s,
+            clip: None,
+            rotation: 0.0,
+        });
+        Ok((layers, width))
+    }
+
+    /// 所有徽章合成一张图，超过 `max_width` 时换行
+    pub fn compose(&mut self) -> Result<Compositor> {
+        let fonts = self.fonts().clone();
+        let h = self.style.metrics().height;
+        let gap = self.gap as f32;
+        let (mut x, mut y, mut width) = (0.0f32, 0.0f32, 0.0f32);
+        let mut layers = Vec::new();
+        for badge in &self.items {
+            let (mut placed, w) = self.layers(badge, x, y, &fonts)?;
+            if x > 0.0 && self.max_width > 0 && x + w > self.max_width as f32 {
+                // 移到下一行开头
+                for layer in &mut placed {
+                    layer.x -= x * self.scale;
+                    layer.y += (h + gap) * self.scale;
+                }
+                x = 0.0;
+                y += h + gap;
+            }
+            layers.extend(placed);
+            width = width.max(x + w);
+            x += w + gap;
+        }
+        let height = if self.items.is_empty() { 0.0 } else { y + h };
+        let mut compositor = Compositor::new(
+            (width * self.scale).ceil().max(1.0) as u32,
+            (height * self.scale).ceil().max(1.0) as u32,
+        );
+        compositor.layers = layers;
+        Ok(compositor)
+    }
+
+    /// 每个徽章单独一张图，顺序与 `items` 相同
+    pub fn compose_each(&mut self) -> Result<Vec<Compositor>> {
+        let fonts = self.fonts().clone();
+        let h = self.style.metrics().height;
+        self.items
+            .iter()
+            .map(|badge| {
+                let (layers, w) = self.layers(badge, 0.0, 0.0, &fonts)?;
+                let mut compositor = Compositor::new(
+                    (w * self.scale).ceil().max(1.0) as u32,
+                    (h * self.scale).ceil().max(1.0) as u32,
+                );
+                compositor.layers = layers;
+                Ok(compositor)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,753 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+
+use resvg::tiny_skia::Color;
+use serde::{Deserialize, Serialize};
+use usvg::fontdb;
+
+use crate::align::{polygons_overlap, re_align, Icon, Position};
+use crate::background::Background;
+use crate::compose::{Compositor, Layer, Source};
+use crate::container::Container;
+use crate::frame::Frame;
+use crate::hex::Hex;
+use crate::mask::{fill_shape, ShapeMask};
+use crate::meta::Meta;
+use crate::metrics::{measure, Metrics};
+use crate::optimize::Optimize;
+use crate::orbit::{orbit, Orbit, Orbits};
+use crate::overlay::{default_families, region, system_fonts, Avatar, Text};
+use crate::physics::settle;
+use crate::recolor::{dominant, Recolor};
+use crate::shelves::{Shelf, Shelves};
+use crate::spiral::spiral;
+use crate::tile::tile;
+use crate::sizing::Sizing;
+use crate::treemap::{squarify, Cell, Treemap};
+
+/// 排布方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arrange {
+    /// 按行扫描紧密排列
+    Grid,
+    /// 从顶部落下堆积，和前端的物理效果一致
+    Pile,
+    /// 填满 `Banner::shape` 指定的形状
+    Shape,
+    /// 矩形树图，格子面积与权重成正比
+    Treemap,
+    /// 蜂窝状的六边形贴纸
+    Hex,
+    /// 主要技术在中间，其余图标在同心轨道上
+    Orbit,
+    /// 按分组（目录分类）分成带标题的横向分区
+    Shelves,
+    /// 沿螺线从中心向外放置，权重大的在中间
+    Spiral,
+    /// 无缝平铺，图标可以跨过边缘，输出的图片首尾相接
+    Tile,
+}
+
+impl Arrange {
+    pub fn parse(name: &str) -> Option<Arrange> {
+        match name {
+            "grid" => Some(Arrange::Grid),
+            "pile" => Some(Arrange::Pile),
+            "shape" => Some(Arrange::Shape),
+            "treemap" => Some(Arrange::Treemap),
+            "hex" => Some(Arrange::Hex),
+            "orbit" => Some(Arrange::Orbit),
+            "shelves" => Some(Arrange::Shelves),
+            "spiral" => Some(Arrange::Spiral),
+            "tile" => Some(Arrange::Tile),
+            _ => None,
+        }
+    }
+}
+
+/// 排布结果，坐标为图标（旋转后外接矩形）的左上角
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Placement {
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub scale: f32,
+    /// 绕图标中心顺时针旋转的角度，只有优化时会旋转
+    #[serde(default)]
+    pub angle: f32,
+}
+
+// 排布方式附带的图层：画在图标下面和上面的图层，以及每个图标的裁剪区域
+#[derive(Default)]
+struct Decoration {
+    under: Vec<Layer>,
+    over: Vec<Layer>,
+    clips: Vec<Option<Vec<(f32, f32)>>>,
+}
+
+struct Entry {
+    meta: Meta,
+    source: Arc<Source>,
+    scale: f32,
+    weight: Option<f32>,
+    /// 目录中归一化的热度，没有设置任何权重时代替权重
+    popularity: Option<f32>,
+    /// 显示的名字，默认为图标名
+    label: Option<String>,
+    /// 分组，例如目录中的分类，环绕排布时同组的图标在同一条轨道上，书架排布时在同一个分区
+    group: Option<String>,
+}
+
+/// 图标排布 + 合成的完整流程，浏览器 (wasm) 和命令行共用
+pub struct Banner {
+    pub width: u32,
+    pub height: u32,
+    pub background: Option<Background>,
+    pub arrange: Arrange,
+    pub seed: u64,
+    pub step: u32,
+    /// 设置后带权重的图标按权重缩放，并优先排布
+    pub sizing: Option<Sizing>,
+    pub recolor: Recolor,
+    /// 留白、圆角和保留区域
+    pub frame: Frame,
+    /// `Arrange::Shape` 的目标形状，为空时填满容器或画框
+    pub shape: Option<ShapeMask>,
+    /// 形状排布和平铺时循环使用图标，直到填满
+    pub repeat: bool,
+    /// 堆积时图标倒进容器里，容器画在图标下面
+    pub container: Option<Container>,
+    /// 树图的格子和文字
+    pub treemap: Treemap,
+    /// 六边形格子的大小、填充和描边
+    pub hex: Hex,
+    /// 环绕排布的轨道
+    pub orbit: Orbit,
+    /// 书架的分区和标题
+    pub shelves: Shelves,
+    entries: Vec<Entry>,
+    /// 文字和头像，画在图标上面
+    overlays: Vec<Layer>,
+    fonts: Option<Arc<fontdb::Database>>,
+}
+
+impl Banner {
+    pub fn new(width: u32, height: u32) -> Banner {
+        Banner {
+            width,
+            height,
+            background: None,
+            arrange: Arrange::Pile,
+            seed: 0,
+            step: 4,
+            sizing: None,
+            recolor: Recolor::Original,
+            frame: Frame::default(),
+            shape: None,
+            repeat: false,
+            container: None,
+            treemap: Treemap::default(),
+            hex: Hex::default(),
+            orbit: Orbit::default(),
+            shelves: Shelves::default(),
+            entries: Vec::new(),
+            overlays: Vec::new(),
+            fonts: None,
+        }
+    }
+
+    // 第一次用到时加载系统字体，浏览器中只有 `load_font` 添加的字体
+    fn fonts(&mut self) -> &mut Arc<fontdb::Database> {
+        self.fonts.get_or_insert_with(system_fonts)
+    }
+
+    /// 添加 TTF/OTF 字体
+    pub fn load_font(&mut self, data: Vec<u8>) {
+        let fonts = Arc::make_mut(self.fonts());
+        fonts.load_font_data(data);
+        default_families(fonts);
+    }
+
+    /// 文字轮廓作为形状，使用与文字图层相同的字体
+    pub fn text_shape(&mut self, text: &str) -> Result<ShapeMask> {
+        let (width, height) = (self.width, self.height);
+        ShapeMask::from_text(text, self.fonts(), width, height)
+    }
+
+    /// 添加文字图层，排布时图标会避开文字
+    pub fn add_text(&mut self, text: &Text) -> Result<()> {
+        let layer = text.layer(self.fonts())?;
+        self.overlays.push(layer);
+        Ok(())
+    }
+
+    /// 添加圆形头像，`data` 为图片文件内容
+    pub fn add_avatar(&mut self, avatar: &Avatar, data: &[u8]) -> Result<()> {
+        self.overlays.push(avatar.layer(data)?);
+        Ok(())
+    }
+
+    /// 添加图标，`scale` 是相对于 meta 栅格尺寸的缩放
+    pub fn add(&mut self, meta: Meta, source: Source, scale: f32) {
+        self.entries.push(Entry {
+            meta,
+            source: Arc::new(source),
+            scale,
+            weight: None,
+            popularity: None,
+            label: None,
+            group: None,
+        });
+    }
+
+    /// 设置图标的权重，例如语言占比或仓库数，找不到图标时返回 false
+    pub fn set_weight(&mut self, name: &str, weight: f32) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.meta.name == name) else {
+            return false;
+        };
+        entry.weight = Some(weight);
+        true
+    }
+
+    /// 设置图标在目录中的热度 (0 到 1)，所有图标都没有权重时按热度缩放和排序，找不到图标时返回 false
+    pub fn set_popularity(&mut self, name: &str, popularity: f32) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.meta.name == name) else {
+            return false;
+        };
+        entry.popularity = Some(popularity).filter(|&p| p > 0.0);
+        true
+    }
+
+    // 每个图标的权重：设置过权重时只用权重，一个都没有设置时用目录中的热度
+    fn weights(&self) -> Vec<Option<f32>> {
+        if self.entries.iter().any(|e| e.weight.is_some()) {
+            self.entries.iter().map(|e| e.weight).collect()
+        } else {
+            self.entries.iter().map(|e| e.popularity).collect()
+        }
+    }
+
+    /// 设置图标显示的名字，例如目录中的 "TypeScript"，找不到图标时返回 false
+    pub fn set_label(&mut self, name: &str, label: &str) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.meta.name == name) else {
+            return false;
+        };
+        entry.label = Some(label.to_string());
+        true
+    }
+
+    /// 设置图标的分组，找不到图标时返回 false
+    pub fn set_group(&mut self, name: &str, group: &str) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.meta.name == name) else {
+            return false;
+        };
+        entry.group = Some(group.to_string());
+        true
+    }
+
+    // 每个图标实际使用的缩放
+    fn scales(&self) -> Vec<f32> {
+        let mut scales: Vec<f32> = self.entries.iter().map(|e| e.scale).collect();
+        let Some(sizing) = &self.sizing else {
+            return scales;
+        };
+        let all = self.weights();
+        let weighted: Vec<usize> = (0..self.entries.len()).filter(|&i| all[i].is_some()).collect();
+        let weights: Vec<f32> = weighted.iter().filter_map(|&i| all[i]).collect();
+        for (i, scale) in weighted.into_iter().zip(sizing.scales(&weights)) {
+            scales[i] = scale;
+        }
+        scales
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 排布顺序和对应的图标
+    fn items(&self) -> (Vec<f32>, Vec<usize>, Vec<Position>) {
+        let scales = self.scales();
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        if self.sizing.is_some() {
+            // 大图标先排，空间不够时丢弃的是次要技术
+            order.sort_by(|&a, &b| scales[b].total_cmp(&scales[a]));
+        } else if self.arrange == Arrange::Spiral {
+            // 没有按权重缩放时也按权重排序，权重大的放在中间
+            let weights = self.weights();
+            let weight = |i: usize| weights[i].unwrap_or(0.0);
+            order.sort_by(|&a, &b| weight(b).total_cmp(&weight(a)));
+        }
+        let items: Vec<Position> = order
+            .iter()
+            .map(|&i| Position::new(self.entries[i].meta.icon(scales[i])))
+            .collect();
+        (scales, order, items)
+    }
+
+    /// 计算排布，放不下的图标会被丢弃
+    pub fn layout(&self) -> Vec<Placement> {
+        let (scales, order, mut items) = self.items();
+        let frame = self.layout_frame();
+        let overflow = match self.arrange {
+            Arrange::Grid => re_align(&mut items, self.width, self.height, self.step, &frame),
+            Arrange::Pile => settle(
+                &mut items,
+                self.width,
+                self.height,
+                self.seed,
+                &frame,
+                self.container.as_ref().map(|c| &c.mask),
+            ),
+            Arrange::Spiral => spiral(&mut items, self.width, self.height, self.step, &frame),
+            Arrange::Tile => return self.layout_tile(&items, &order, &frame),
+            Arrange::Shape => return self.layout_shape(&items, &order, &frame),
+            Arrange::Treemap => return self.layout_treemap(),
+            Arrange::Hex => return self.layout_hex(&items, &order, &frame),
+            Arrange::Orbit => return self.orbit_frames(1).pop().unwrap_or_default(),
+            Arrange::Shelves => return self.layout_shelves(&items, &order, &frame),
+        };
+        items
+            .iter()
+            .zip(&order)
+            .enumerate()
+            .filter(|(index, _)| !overflow.contains(index))
+            .map(|(_, (pos, &i))| Placement {
+                name: self.entries[i].meta.name.clone(),
+                x: pos.x,
+                y: pos.y,
+                scale: scales[i],
+                angle: 0.0,
+            })
+            .collect()
+    }
+
+    // 环绕排布的轨道，图标的缩放由轨道决定
+    fn orbits(&self) -> (Orbits, Vec<usize>, Vec<usize>, Vec<Position>) {
+        let (_, order, mut items) = self.items();
+        let groups: Vec<Option<&str>> = order.iter().map(|&i| self.entries[i].group.as_deref()).collect();
+        let frame = self.layout_frame();
+        let (orbits, overflow) = orbit(&mut items, &groups, self.width, self.height, &frame, &self.orbit);
+        (orbits, overflow, order, items)
+    }
+
+    /// 环绕排布的动画，每一帧轨道转过 1 / `count` 周，第一帧与 `layout` 相同。
+    /// 只有轨道上的图标会动，首尾相接可以循环播放
+    pub fn orbit_frames(&self, count: u32) -> Vec<Vec<Placement>> {
+        let (orbits, overflow, order, items) = self.orbits();
+        (0..count.max(1))
+            .map(|f| {
+                let phase = f as f32 / count.max(1) as f32;
+                items
+                    .iter()
+                    .zip(&order)
+                    .enumerate()
+                    .filter(|(index, _)| !overflow.contains(index))
+                    .map(|(index, (pos, &i))| {
+                        let (x, y) = orbits.position(index, pos.icon.scaled_size(), phase);
+                        Placement {
+                            name: self.entries[i].meta.name.clone(),
+                            x,
+                            y,
+                            scale: pos.icon.scale,
+                            angle: pos.icon.angle,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // 书架的分区，图标下标为排布顺序中的位置
+    fn shelf_list(&self, items: &[Position], order: &[usize], frame: &Frame) -> Vec<Shelf> {
+        let groups: Vec<Option<&str>> = order.iter().map(|&i| self.entries[i].group.as_deref()).collect();
+        self.shelves.shelves(items, &groups, frame.area(self.width, self.height))
+    }
+
+    // 每个分区单独排布，分区放不下时会缩小分区内的图标
+    fn layout_shelves(&self, items: &[Position], order: &[usize], frame: &Frame) -> Vec<Placement> {
+        let mut placements = Vec::new();
+        for (k, shelf) in self.shelf_list(items, order, frame).into_iter().enumerate() {
+            let mut members: Vec<Position> = shelf.members.iter().map(|&i| items[i].clone()).collect();
+            let sub = shelf.frame(frame, self.width, self.height);
+            // 每个分区使用不同的种子，避免堆积的形状相同
+            let seed = self.seed.wrapping_add(k as u64);
+            let overflow = self.shelves.pack(&mut members, self.width, self.height, self.step, seed, &sub);
+            placements.extend(
+                members
+                    .iter()
+                    .zip(&shelf.members)
+                    .enumerate()
+                    .filter(|(index, _)| !overflow.contains(index))
+                    .map(|(_, (pos, &m))| Placement {
+                        name: self.entries[order[m]].meta.name.clone(),
+                        x: pos.x,
+                        y: pos.y,
+                        scale: pos.icon.scale,
+                        angle: pos.icon.angle,
+                    }),
+            );
+        }
+        placements
+    }
+
+    // 分区标题画在图标上面，分隔线画在下面；标题颜色无效时不画
+    fn shelf_layers(&self) -> Decoration {
+        let (_, order, items) = self.items();
+        let shelves = self.shelf_list(&items, &order, &self.layout_frame());
+        let fonts = self.fonts.clone().unwrap_or_else(system_fonts);
+        Decoration {
+            under: self
+                .shelves
+                .divider_layer(&shelves, self.width, self.height)
+                .ok()
+                .flatten()
+                .into_iter()
+                .collect(),
+            over: shelves
+                .iter()
+                .filter(|s| !s.members.is_empty())
+                .filter_map(|s| self.shelves.heading_layer(s, &fonts).ok().flatten())
+                .collect(),
+            clips: Vec::new(),
+        }
+    }
+
+    // 文字和头像所在的区域也不放图标
+    fn layout_frame(&self) -> Frame {
+        let mut frame = self.frame.clone();
+        frame.keep_out.extend(self.overlays.iter().map(region));
+        frame
+    }
+
+    // 形状排布会改变缩放，重复填充时同一图标可能出现多次
+    fn layout_shape(&self, items: &[Position], order: &[usize], frame: &Frame) -> Vec<Placement> {
+        let mut mask = match (&self.shape, &self.container) {
+            (Some(shape), _) => shape.clone(),
+            (None, Some(container)) => container.mask.clone(),
+            (None, None) => ShapeMask::full(self.width, self.height),
+        };
+        let (x0, y0, x1, y1) = frame.area(self.width, self.height);
+        mask.clip(x0, y0, x1, y1);
+        let icons: Vec<Icon> = items.iter().map(|p| p.icon.clone()).collect();
+        fill_shape(&icons, &mask, &frame.obstacles(self.width, self.height), self.step, self.repeat)
+            .into_iter()
+            .map(|(index, pos)| Placement {
+                name: self.entries[order[index]].meta.name.clone(),
+                x: pos.x,
+                y: pos.y,
+                scale: pos.icon.scale,
+                angle: pos.icon.angle,
+            })
+            .collect()
+    }
+
+    // 平铺不使用留白，只避开保留区域，重复填充时同一图标可能出现多次
+    fn layout_tile(&self, items: &[Position], order: &[usize], frame: &Frame) -> Vec<Placement> {
+        let obstacles = Frame {
+            keep_out: frame.keep_out.clone(),
+            ..Default::default()
+        }
+        .obstacles(self.width, self.height);
+        tile(items, self.width, self.height, self.step, self.seed, &obstacles, self.repeat)
+            .into_iter()
+            .map(|(index, pos)| Placement {
+                name: self.entries[order[index]].meta.name.clone(),
+                x: pos.x,
+                y: pos.y,
+                scale: pos.icon.scale,
+                angle: pos.icon.angle,
+            })
+            .collect()
+    }
+
+    // 每个图标的格子和权重占比，没有权重的图标按最小的权重计算，都没有时平分
+    fn cells(&self, frame: &Frame) -> Vec<(Cell, f32)> {
+        let weights = self.weights();
+        let min = weights
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&w| w > 0.0)
+            .fold(f32::INFINITY, f32::min);
+        let fallback = if min.is_finite() { min } else { 1.0 };
+        let weights: Vec<f32> = weights.iter().map(|w| w.unwrap_or(fallback).max(0.0)).collect();
+        let total: f32 = weights.iter().sum();
+        let (x0, y0, x1, y1) = frame.area(self.width, self.height);
+        let area = Cell {
+            x: x0 as f32,
+            y: y0 as f32,
+            width: (x1 - x0) as f32,
+            height: (y1 - y0) as f32,
+        };
+        squarify(&weights, area)
+            .into_iter()
+            .zip(&weights)
+            .map(|(cell, w)| (cell, if total > 0.0 { w / total } else { 0.0 }))
+            .collect()
+    }
+
+    // 树图占满画框以内的区域；图标保持比例缩放到格子扣除保留区域后最大的矩形中间，
+    // 放不下或碰到圆角时不显示
+    fn layout_treemap(&self) -> Vec<Placement> {
+        let frame = self.layout_frame();
+        let obstacles = frame.obstacles(self.width, self.height);
+        self.entries
+            .iter()
+            .zip(self.cells(&frame))
+            .filter_map(|(entry, (cell, _))| {
+                let area = self.treemap.icon_area(cell).free(&frame.keep_out);
+                let (w, h) = (entry.meta.width as f32, entry.meta.height as f32);
+                let scale = (area.width / w).min(area.height / h);
+                if scale <= 0.0 || w * scale < 1.0 || h * scale < 1.0 {
+                    return None;
+                }
+                let x = (area.x + (area.width - w * scale) / 2.0).round() as u32;
+                let y = (area.y + (area.height - h * scale) / 2.0).round() as u32;
+                let polygon = entry.meta.icon(scale).scaled_polygon();
+                if obstacles.iter().any(|(p, o)| polygons_overlap(&polygon, p, (x, y), *o)) {
+                    return None;
+                }
+                Some(Placement {
+                    name: entry.meta.name.clone(),
+                    x,
+                    y,
+                    scale,
+                    angle: 0.0,
+                })
+            })
+            .collect()
+    }
+
+    // 六边形格子的外接圆半径和中心，由内向外
+    fn hex_slots(&self, count: usize, frame: &Frame) -> (f32, Vec<(f32, f32)>) {
+        let area = frame.area(self.width, self.height);
+        self.hex.slots(count, area, &frame.obstacles(self.width, self.height))
+    }
+
+    // 每个图标缩放到格子里，排在前面的图标靠近中心
+    fn layout_hex(&self, items: &[Position], order: &[usize], frame: &Frame) -> Vec<Placement> {
+        let (pitch, slots) = self.hex_slots(items.len(), frame);
+        items
+            .iter()
+            .zip(order)
+            .zip(slots)
+            .map(|((pos, &i), (cx, cy))| {
+                let scale = self.hex.scale(pitch, pos.icon.width, pos.icon.height);
+                let (w, h) = (pos.icon.width as f32 * scale, pos.icon.height as f32 * scale);
+                Placement {
+                    name: self.entries[i].meta.name.clone(),
+                    x: (cx - w / 2.0).max(0.0).round() as u32,
+                    y: (cy - h / 2.0).max(0.0).round() as u32,
+                    scale,
+                    angle: 0.0,
+                }
+            })
+            .collect()
+    }
+
+    // 图标所在的格子为离图标中心最近的格子，填充画在图标下面，描边画在上面；颜色无效时不画
+    fn hex_layers(&self, placements: &[Placement]) -> Decoration {
+        let (pitch, slots) = self.hex_slots(placements.len(), &self.layout_frame());
+        let mut tiles = Vec::new();
+        let mut centers = Vec::new();
+        let mut clips = Vec::new();
+        for p in placements {
+            let entry = self.entries.iter().find(|e| e.meta.name == p.name);
+            let center = entry.and_then(|e| {
+                let cx = p.x as f32 + e.meta.width as f32 * p.scale / 2.0;
+                let cy = p.y as f32 + e.meta.height as f32 * p.scale / 2.0;
+                let distance = |&(x, y): &(f32, f32)| (x - cx).powi(2) + (y - cy).powi(2);
+                slots.iter().copied().min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            });
+            let (Some(entry), Some(center)) = (entry, center) else {
+                clips.push(None);
+                continue;
+            };
+            if let Ok(Some(color)) = self.hex.fill_color(&entry.source) {
+                tiles.push((center, color));
+            }
+            centers.push(center);
+            clips.push(Some(self.hex.clip(pitch, center)));
+        }
+        let mut decoration = Decoration {
+            clips,
+            ..Default::default()
+        };
+        if !tiles.is_empty() {
+            decoration.under.extend(self.hex.fill_layer(pitch, &tiles, self.width, self.height).ok());
+        }
+        decoration
+            .over
+            .extend(self.hex.border_layer(pitch, &centers, self.width, self.height).ok().flatten());
+        decoration
+    }
+
+    // 树图的格子画在图标下面，文字画在图标上面；文字颜色无效时不画文字
+    fn treemap_layers(&self, placements: &[Placement]) -> Decoration {
+        let frame = self.layout_frame();
+        let cells = self.cells(&frame);
+        let shown: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| placements.iter().any(|p| p.name == self.entries[i].meta.name))
+            .collect();
+        let mut decoration = Decoration::default();
+        decoration.under.extend(
+            self.treemap
+                .cells_layer(&cells.iter().map(|(c, _)| *c).collect::<Vec<_>>(), self.width, self.height)
+                .ok()
+                .flatten(),
+        );
+        if !self.treemap.label && !self.treemap.percent {
+            return decoration;
+        }
+        let fonts = self.fonts.clone().unwrap_or_else(system_fonts);
+        decoration.over = shown
+            .into_iter()
+            .filter_map(|i| {
+                let entry = &self.entries[i];
+                let name = entry.label.as_deref().unwrap_or(&entry.meta.name);
+                let (cell, share) = cells[i];
+                self.treemap.label_layer(cell, name, share, &fonts).ok().flatten()
+            })
+            // 与保留区域相交的文字不显示
+            .filter(|layer| {
+                let rect = Cell {
+                    x: layer.x,
+                    y: layer.y,
+                    width: layer.width,
+                    height: layer.height,
+                };
+                rect.free(&frame.keep_out) == rect
+            })
+            .collect();
+        decoration
+    }
+
+    // 排布结果中的图标和它们的主色，找不到的图标会被跳过
+    fn positions<'a>(&self, placements: &'a [Placement]) -> (Vec<&'a Placement>, Vec<Position>, Vec<Option<Color>>) {
+        let mut kept = Vec::new();
+        let mut items = Vec::new();
+        let mut colors = Vec::new();
+        for p in placements {
+            let Some(entry) = self.entries.iter().find(|e| e.meta.name == p.name) else {
+                continue;
+            };
+            kept.push(p);
+            items.push(Position {
+                x: p.x,
+                y: p.y,
+                icon: entry.meta.icon(p.scale).rotated(p.angle),
+            });
+            colors.push(dominant(&entry.source));
+        }
+        (kept, items, colors)
+    }
+
+    /// 排布的质量指标，统计画框以内的区域，文字和头像所在的保留区域不算空白
+    pub fn metrics(&self, placements: &[Placement]) -> Metrics {
+        let (_, items, colors) = self.positions(placements);
+        let frame = self.layout_frame();
+        measure(&items, &colors, frame.area(self.width, self.height), &frame.obstacles(self.width, self.height))
+    }
+
+    /// 在已有排布上优化选定的指标，图标的缩放不变；返回新的排布以及优化前后的分数。
+    /// 优化只在画框内移动图标，不支持依赖格子、形状、分区、轨道、容器或首尾相接的排布
+    pub fn optimize(&self, placements: &[Placement], config: &Optimize) -> Result<(Vec<Placement>, f32, f32)> {
+        match self.arrange {
+            Arrange::Tile | Arrange::Hex | Arrange::Treemap | Arrange::Shelves | Arrange::Shape | Arrange::Orbit => {
+                bail!("the {:?} arrangement cannot be optimized", self.arrange)
+            }
+            _ if self.container.is_some() => bail!("arrangements in a container cannot be optimized"),
+            _ => {}
+        }
+        let (kept, mut items, colors) = self.positions(placements);
+        let frame = self.layout_frame();
+        let (before, after) = config.run(
+            &mut items,
+            &colors,
+            frame.area(self.width, self.height),
+            &frame.obstacles(self.width, self.height),
+            self.seed,
+        );
+        let placements = kept
+            .into_iter()
+            .zip(items)
+            .map(|(p, pos)| Placement {
+                name: p.name.clone(),
+                x: pos.x,
+                y: pos.y,
+                scale: p.scale,
+                angle: pos.icon.angle,
+            })
+            .collect();
+        Ok((placements, before, after))
+    }
+
+    /// 按排布结果生成合成器
+    pub fn compose(&self, placements: &[Placement]) -> Compositor {
+        let mut compositor = Compositor::new(self.width, self.height);
+        compositor.background = self.background.clone();
+        compositor.wrap = self.arrange == Arrange::Tile;
+        if let Some(container) = &self.container {
+            compositor.push(container.layer());
+        }
+        let mut decoration = match self.arrange {
+            Arrange::Treemap => self.treemap_layers(placements),
+            Arrange::Hex => self.hex_layers(placements),
+            Arrange::Shelves => self.shelf_layers(),
+            _ => Decoration::default(),
+        };
+        decoration.clips.resize(placements.len(), None);
+        for layer in decoration.under {
+            compositor.push(layer);
+        }
+        let average = self.background.as_ref().map(Background::average);
+        // 循环使用的图标会放置多次，每个图标只着色一次
+        let mut recolored: HashMap<&str, Arc<Source>> = HashMap::new();
+        for (p, clip) in placements.iter().zip(decoration.clips) {
+            let Some(entry) = self.entries.iter().find(|e| e.meta.name == p.name) else {
+                continue;
+            };
+            let source = recolored
+                .entry(entry.meta.name.as_str())
+                .or_insert_with(|| match self.recolor {
+                    Recolor::Original => entry.source.clone(),
+                    // 着色失败时保留原色
+                    recolor => recolor
+                        .apply(&entry.source, average)
+                        .map(Arc::new)
+                        .unwrap_or_else(|_| entry.source.clone()),
+                })
+                .clone();
+            // 旋转后 (x, y) 为外接矩形的左上角，图片中心在外接矩形中心
+            let (width, height) = (entry.meta.width as f32 * p.scale, entry.meta.height as f32 * p.scale);
+            let (w, h) = if p.angle == 0.0 {
+                (width, height)
+            } else {
+                let (bw, bh) = entry.meta.icon(p.scale).rotated(p.angle).scaled_size();
+                (bw as f32, bh as f32)
+            };
+            compositor.push(Layer {
+                source,
+                x: p.x as f32 + (w - width) / 2.0,
+                y: p.y as f32 + (h - height) / 2.0,
+                width,
+                height,
+                clip,
+                rotation: p.angle,
+            });
+        }
+        for layer in decoration.over {
+            compositor.push(layer);
+        }
+        for layer in &self.overlays {
+            compositor.push(layer.clone());
+        }
+        compositor
+    }
+}
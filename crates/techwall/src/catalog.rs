@@ -0,0 +1,486 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// 技术分类
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Language,
+    Framework,
+    Database,
+    Cloud,
+    Tool,
+    #[default]
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 6] = [
+        Category::Language,
+        Category::Framework,
+        Category::Database,
+        Category::Cloud,
+        Category::Tool,
+        Category::Other,
+    ];
+
+    pub fn parse(name: &str) -> Option<Category> {
+        Category::ALL.into_iter().find(|c| c.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Language => "language",
+            Category::Framework => "framework",
+            Category::Database => "database",
+            Category::Cloud => "cloud",
+            Category::Tool => "tool",
+            Category::Other => "other",
+        }
+    }
+}
+
+/// 同一技术的不同图标文件
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum VariantKind {
+    /// 与技术同名的文件，通常带文字
+    Default,
+    /// `-icon` 只有图形
+    Icon,
+    /// `-logo`
+    Logo,
+    /// `-wordmark` 只有文字
+    Wordmark,
+}
+
+impl VariantKind {
+    pub fn of(file: &str) -> VariantKind {
+        if file.ends_with("-wordmark") {
+            VariantKind::Wordmark
+        } else if file.ends_with("-logo") {
+            VariantKind::Logo
+        } else if file.contains("-icon") {
+            VariantKind::Icon
+        } else {
+            VariantKind::Default
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Variant {
+    pub name: String,
+    pub kind: VariantKind,
+}
+
+/// 目录中的一项技术
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Entry {
+    pub name: String,
+    pub display: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub category: Category,
+    /// GitHub 上带该 topic 的仓库数
+    #[serde(default)]
+    pub score: u64,
+    /// 多个数据源归一化后的热度，0 到 1，由 `crawler popularity` 生成
+    #[serde(default)]
+    pub popularity: f32,
+    pub variants: Vec<Variant>,
+}
+
+impl Entry {
+    pub fn variant(&self, kind: VariantKind) -> Option<&Variant> {
+        self.variants.iter().find(|v| v.kind == kind)
+    }
+
+    /// 默认使用的图标文件：优先与技术同名的文件
+    pub fn primary(&self) -> Option<&str> {
+        self.variant(VariantKind::Default)
+            .or(self.variants.first())
+            .map(|v| v.name.as_str())
+    }
+}
+
+/// 图标目录，打包进 `resource/meta`
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Catalog {
+    pub entries: Vec<Entry>,
+}
+
+// crawler/list.json 的条目
+#[derive(Deserialize)]
+struct ListItem {
+    name: String,
+    items: Vec<String>,
+    github: Option<GithubScore>,
+    popularity: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct GithubScore {
+    score: u64,
+}
+
+impl Catalog {
+    /// 读取 Node 爬虫生成的 `crawler/list.json`
+    pub fn from_list_json(json: &str) -> Result<Catalog> {
+        let list: Vec<ListItem> = serde_json::from_str(json)?;
+        let mut entries: Vec<Entry> = list
+            .into_iter()
+            .map(|item| {
+                let known = known(&item.name);
+                let variants = item
+                    .items
+                    .iter()
+                    .map(|f| Variant {
+                        name: f.clone(),
+                        kind: VariantKind::of(f),
+                    })
+                    .collect();
+                Entry {
+                    display: known
+                        .map(|k| k.display.to_string())
+                        .unwrap_or_else(|| display_name(&item.name)),
+                    aliases: known
+                        .map(|k| k.aliases.iter().map(|a| a.to_string()).collect())
+                        .unwrap_or_default(),
+                    category: known.map(|k| k.category).unwrap_or_default(),
+                    score: item.github.map(|g| g.score).unwrap_or(0),
+                    popularity: item.popularity.unwrap_or(0.0),
+                    name: item.name,
+                    variants,
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.popularity
+                .total_cmp(&a.popularity)
+                .then_with(|| b.score.cmp(&a.score))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(Catalog { entries })
+    }
+
+    /// 添加或替换同名技术
+    pub fn insert(&mut self, entry: Entry) {
+        match self.entries.iter_mut().find(|e| e.name == entry.name) {
+            Some(e) => *e = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// 通过图标文件名找到所属技术
+    pub fn by_icon(&self, file: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|e| e.variants.iter().any(|v| v.name == file))
+    }
+
+    pub fn by_category(&self, category: Category) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(move |e| e.category == category)
+    }
+
+    /// 按热度排序的前 `count` 个技术的默认图标，用作初始选择
+    pub fn default_selection(&self, count: usize) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.primary())
+            .take(count)
+            .collect()
+    }
+}
+
+// "react-router" -> "React Router"
+pub(crate) fn display_name(name: &str) -> String {
+    name.split(['-', '_'])
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let mut chars = s.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct Known {
+    name: &'static str,
+    display: &'static str,
+    category: Category,
+    aliases: &'static [&'static str],
+}
+
+const fn k(
+    name: &'static str,
+    display: &'static str,
+    category: Category,
+    aliases: &'static [&'static str],
+) -> Known {
+    Known {
+        name,
+        display,
+        category,
+        aliases,
+    }
+}
+
+fn known(name: &str) -> Option<&'static Known> {
+    KNOWN.iter().find(|k| k.name == name)
+}
+
+use Category::{Cloud, Database, Framework, Language, Tool};
+
+// 常见技术的显示名、分类和别名
+const KNOWN: &[Known] = &[
+    k("javascript", "JavaScript", Language, &["js", "ecmascript"]),
+    k("typescript", "TypeScript", Language, &["ts"]),
+    k("python", "Python", Language, &["py", "python3"]),
+    k("java", "Java", Language, &["jdk", "jvm"]),
+    k("c", "C", Language, &["clang"]),
+    k("c-plusplus", "C++", Language, &["c++", "cpp", "cplusplus", "cxx"]),
+    k("c-sharp", "C#", Language, &["c#", "csharp", "cs"]),
+    k("go", "Go", Language, &["golang"]),
+    k("rust", "Rust", Language, &["rs", "rustlang"]),
+    k("php", "PHP", Language, &[]),
+    k("ruby", "Ruby", Language, &["rb"]),
+    k("kotlin", "Kotlin", Language, &["kt"]),
+    k("swift", "Swift", Language, &[]),
+    k("dart", "Dart", Language, &[]),
+    k("scala", "Scala", Language, &[]),
+    k("haskell", "Haskell", Language, &["hs"]),
+    k("lua", "Lua", Language, &[]),
+    k("perl", "Perl", Language, &["pl"]),
+    k("r-lang", "R", Language, &["r", "rlang"]),
+    k("julia", "Julia", Language, &["jl"]),
+    k("erlang", "Erlang", Language, &["erl"]),
+    k("clojure", "Clojure", Language, &["clj"]),
+    k("fsharp", "F#", Language, &["f#"]),
+    k("ocaml", "OCaml", Language, &["ml"]),
+    k("elm", "Elm", Language, &[]),
+    k("crystal", "Crystal", Language, &[]),
+    k("objective-c", "Objective-C", Language, &["objc", "objectivec"]),
+    k("assembly", "Assembly", Language, &["asm"]),
+    k("bash", "Bash", Language, &["shell", "sh", "zsh"]),
+    k("coffeescript", "CoffeeScript", Language, &["coffee"]),
+    k("purescript", "PureScript", Language, &[]),
+    k("reasonml", "ReasonML", Language, &["reason"]),
+    k("gleam", "Gleam", Language, &[]),
+    k("vlang", "V", Language, &["v"]),
+    k("haxe", "Haxe", Language, &[]),
+    k("delphi", "Delphi", Language, &["pascal"]),
+    k("html-5", "HTML5", Language, &["html", "html5"]),
+    k("css-3", "CSS3", Language, &["css", "css3"]),
+    k("sass", "Sass", Language, &["scss"]),
+    k("less", "Less", Language, &[]),
+    k("webassembly", "WebAssembly", Language, &["wasm"]),
+    k("markdown", "Markdown", Language, &["md"]),
+    k("json", "JSON", Language, &[]),
+    k("graphql", "GraphQL", Language, &["gql"]),
+    k("react", "React", Framework, &["reactjs", "react.js"]),
+    k("vue", "Vue.js", Framework, &["vuejs", "vue.js"]),
+    k("angular", "Angular", Framework, &["angularjs"]),
+    k("svelte", "Svelte", Framework, &["sveltekit"]),
+    k("nextjs", "Next.js", Framework, &["next", "next.js"]),
+    k("nuxt", "Nuxt", Framework, &["nuxtjs", "nuxt.js"]),
+    k("gatsby", "Gatsby", Framework, &["gatsbyjs"]),
+    k("nodejs", "Node.js", Framework, &["node", "node.js"]),
+    k("deno", "Deno", Framework, &[]),
+    k("express", "Express", Framework, &["expressjs"]),
+    k("nestjs", "NestJS", Framework, &["nest"]),
+    k("fastify", "Fastify", Framework, &[]),
+    k("koa", "Koa", Framework, &[]),
+    k("django", "Django", Framework, &[]),
+    k("flask", "Flask", Framework, &[]),
+    k("rails", "Ruby on Rails", Framework, &["ror", "ruby-on-rails"]),
+    k("laravel", "Laravel", Framework, &[]),
+    k("symfony", "Symfony", Framework, &[]),
+    k("spring", "Spring", Framework, &["spring-boot", "springboot"]),
+    k("dotnet", ".NET", Framework, &[".net", "net", "aspnet", "dotnet-core"]),
+    k("flutter", "Flutter", Framework, &[]),
+    k("android", "Android", Framework, &[]),
+    k("ios", "iOS", Framework, &[]),
+    k("electron", "Electron", Framework, &[]),
+    k("jquery", "jQuery", Framework, &[]),
+    k("redux", "Redux", Framework, &[]),
+    k("bootstrap", "Bootstrap", Framework, &[]),
+    k("tailwindcss", "Tailwind CSS", Framework, &["tailwind"]),
+    k("material-ui", "Material UI", Framework, &["mui"]),
+    k("threejs", "three.js", Framework, &["three"]),
+    k("tensorflow", "TensorFlow", Framework, &[]),
+    k("pytorch", "PyTorch", Framework, &["torch"]),
+    k("pandas", "pandas", Framework, &["pd"]),
+    k("numpy", "NumPy", Framework, &["np"]),
+    k("opencv", "OpenCV", Framework, &["cv2"]),
+    k("unity", "Unity", Framework, &["unity3d"]),
+    k("qt", "Qt", Framework, &[]),
+    k("phoenix", "Phoenix", Framework, &[]),
+    k("quarkus", "Quarkus", Framework, &[]),
+    k("hibernate", "Hibernate", Framework, &[]),
+    k("ionic", "Ionic", Framework, &[]),
+    k("preact", "Preact", Framework, &[]),
+    k("ember", "Ember.js", Framework, &["emberjs"]),
+    k("solid", "Solid", Framework, &["solidjs"]),
+    k("mysql", "MySQL", Database, &[]),
+    k("postgresql", "PostgreSQL", Database, &["postgres", "pg", "psql"]),
+    k("mongodb", "MongoDB", Database, &["mongo"]),
+    k("redis", "Redis", Database, &[]),
+    k("sqlite", "SQLite", Database, &["sqlite3"]),
+    k("mariadb", "MariaDB", Database, &[]),
+    k("elasticsearch", "Elasticsearch", Database, &["elastic"]),
+    k("cassandra", "Cassandra", Database, &[]),
+    k("couchdb", "CouchDB", Database, &[]),
+    k("neo4j", "Neo4j", Database, &[]),
+    k("oracle", "Oracle", Database, &[]),
+    k("influxdb", "InfluxDB", Database, &["influx"]),
+    k("memcached", "Memcached", Database, &[]),
+    k("firebase", "Firebase", Database, &[]),
+    k("prisma", "Prisma", Database, &[]),
+    k("sequelize", "Sequelize", Database, &[]),
+    k("rethinkdb", "RethinkDB", Database, &[]),
+    k("rocksdb", "RocksDB", Database, &[]),
+    k("leveldb", "LevelDB", Database, &[]),
+    k("arangodb", "ArangoDB", Database, &[]),
+    k("couchbase", "Couchbase", Database, &[]),
+    k("hbase", "HBase", Database, &[]),
+    k("aws", "AWS", Cloud, &["amazon-web-services", "amazon"]),
+    k("azure", "Azure", Cloud, &["microsoft-azure"]),
+    k("docker", "Docker", Cloud, &["container", "dockerfile"]),
+    k("kubernetes", "Kubernetes", Cloud, &["k8s", "kube"]),
+    k("terraform", "Terraform", Cloud, &["tf", "hcl"]),
+    k("ansible", "Ansible", Cloud, &[]),
+    k("heroku", "Heroku", Cloud, &[]),
+    k("vercel", "Vercel", Cloud, &[]),
+    k("netlify", "Netlify", Cloud, &[]),
+    k("nginx", "NGINX", Cloud, &[]),
+    k("apache", "Apache", Cloud, &["httpd"]),
+    k("jenkins", "Jenkins", Cloud, &[]),
+    k("circleci", "CircleCI", Cloud, &[]),
+    k("travis-ci", "Travis CI", Cloud, &["travis"]),
+    k("prometheus", "Prometheus", Cloud, &[]),
+    k("grafana", "Grafana", Cloud, &[]),
+    k("kafka", "Kafka", Cloud, &["apache-kafka"]),
+    k("rabbitmq", "RabbitMQ", Cloud, &["rabbit"]),
+    k("cloudflare", "Cloudflare", Cloud, &[]),
+    k("digital-ocean", "DigitalOcean", Cloud, &["digitalocean", "do"]),
+    k("openshift", "OpenShift", Cloud, &[]),
+    k("serverless", "Serverless", Cloud, &[]),
+    k("vagrant", "Vagrant", Cloud, &[]),
+    k("packer", "Packer", Cloud, &[]),
+    k("vault", "Vault", Cloud, &[]),
+    k("consul", "Consul", Cloud, &[]),
+    k("nomad", "Nomad", Cloud, &[]),
+    k("puppet", "Puppet", Cloud, &[]),
+    k("chef", "Chef", Cloud, &[]),
+    k("linux-tux", "Linux", Cloud, &["linux", "tux"]),
+    k("ubuntu", "Ubuntu", Cloud, &[]),
+    k("debian", "Debian", Cloud, &[]),
+    k("git", "Git", Tool, &[]),
+    k("github", "GitHub", Tool, &["gh"]),
+    k("gitlab", "GitLab", Tool, &[]),
+    k("bitbucket", "Bitbucket", Tool, &[]),
+    k("npm", "npm", Tool, &[]),
+    k("yarn", "Yarn", Tool, &[]),
+    k("webpack", "webpack", Tool, &[]),
+    k("vite", "Vite", Tool, &["vitejs"]),
+    k("babel", "Babel", Tool, &[]),
+    k("eslint", "ESLint", Tool, &[]),
+    k("prettier", "Prettier", Tool, &[]),
+    k("jest", "Jest", Tool, &[]),
+    k("mocha", "Mocha", Tool, &[]),
+    k("cypress", "Cypress", Tool, &[]),
+    k("selenium", "Selenium", Tool, &[]),
+    k("puppeteer", "Puppeteer", Tool, &[]),
+    k("storybook", "Storybook", Tool, &[]),
+    k("gradle", "Gradle", Tool, &[]),
+    k("maven", "Maven", Tool, &["mvn"]),
+    k("composer", "Composer", Tool, &[]),
+    k("rollup", "Rollup", Tool, &["rollupjs"]),
+    k("esbuild", "esbuild", Tool, &[]),
+    k("gulp", "gulp", Tool, &[]),
+    k("grunt", "Grunt", Tool, &[]),
+    k("vim", "Vim", Tool, &[]),
+    k("neovim", "Neovim", Tool, &["nvim"]),
+    k("emacs", "Emacs", Tool, &[]),
+    k("visual-studio-code", "VS Code", Tool, &["vscode", "code"]),
+    k("visual-studio", "Visual Studio", Tool, &["vs"]),
+    k("intellij-idea", "IntelliJ IDEA", Tool, &["intellij", "idea"]),
+    k("figma", "Figma", Tool, &[]),
+    k("postman", "Postman", Tool, &[]),
+    k("swagger", "Swagger", Tool, &["openapi"]),
+    k("jupyter", "Jupyter", Tool, &["ipynb", "notebook"]),
+    k("jira", "Jira", Tool, &[]),
+    k("homebrew", "Homebrew", Tool, &["brew"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST: &str = r#"[
+        {"name": "react-router", "items": ["react-router"], "github": {"score": 50}},
+        {"name": "docker", "items": ["docker-icon", "docker", "docker-wordmark"], "github": {"score": 900}},
+        {"name": "rust", "items": ["rust"], "popularity": 0.8},
+        {"name": "bun", "items": ["bun-logo"], "github": {"score": 900}},
+        {"name": "empty", "items": []}
+    ]"#;
+
+    fn names(catalog: &Catalog) -> Vec<&str> {
+        catalog.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn from_list_json() {
+        let catalog = Catalog::from_list_json(LIST).unwrap();
+        // 先按热度，再按 GitHub 分数，最后按名字
+        assert_eq!(names(&catalog), ["rust", "bun", "docker", "react-router", "empty"]);
+
+        let docker = catalog.get("docker").unwrap();
+        assert_eq!((docker.display.as_str(), docker.category), ("Docker", Category::Cloud));
+        assert!(docker.aliases.iter().any(|a| a == "dockerfile"));
+        let kinds: Vec<VariantKind> = docker.variants.iter().map(|v| v.kind).collect();
+        assert_eq!(kinds, [VariantKind::Icon, VariantKind::Default, VariantKind::Wordmark]);
+        assert_eq!(catalog.by_icon("docker-wordmark").map(|e| e.name.as_str()), Some("docker"));
+
+        // 不在内置表里的技术用名字生成显示名
+        let router = catalog.get("react-router").unwrap();
+        assert_eq!((router.display.as_str(), router.category), ("React Router", Category::Other));
+        assert!(router.aliases.is_empty());
+        assert_eq!(catalog.get("rust").map(|e| (e.score, e.popularity)), Some((0, 0.8)));
+        assert!(Catalog::from_list_json(r#"[{"name": "rust"}]"#).is_err());
+    }
+
+    #[test]
+    fn known_table_is_unambiguous() {
+        let mut seen: Vec<&str> = Vec::new();
+        for known in KNOWN {
+            assert!(!known.display.is_empty(), "{}", known.name);
+            // 名字和别名都只能指向一项技术
+            for name in std::iter::once(&known.name).chain(known.aliases) {
+                assert!(!seen.contains(name), "{} is used twice", name);
+                seen.push(name);
+            }
+        }
+        assert_eq!(known("c-sharp").map(|k| k.display), Some("C#"));
+        assert!(known("react-router").is_none());
+        assert_eq!(display_name("react_router--dom"), "React Router Dom");
+    }
+
+    #[test]
+    fn default_selection() {
+        let catalog = Catalog::from_list_json(LIST).unwrap();
+        // 优先同名文件，没有时用第一个变体，没有变体的技术跳过
+        assert_eq!(catalog.default_selection(3), ["rust", "bun-logo", "docker"]);
+        assert_eq!(catalog.default_selection(10), ["rust", "bun-logo", "docker", "react-router"]);
+        assert!(catalog.default_selection(0).is_empty());
+    }
+}
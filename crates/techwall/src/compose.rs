@@ -0,0 +1,249 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use resvg::tiny_skia::{Color, FillRule, FilterQuality, Mask, PathBuilder, Pixmap, PixmapPaint, Transform};
+
+use crate::background::Background;
+use crate::png::svg_options;
+
+/// 图层的图像来源
+pub enum Source {
+    Raster(Pixmap),
+    Vector(Box<usvg::Tree>),
+}
+
+impl Source {
+    pub fn from_png(data: &[u8]) -> Result<Source> {
+        Ok(Source::Raster(Pixmap::decode_png(data)?))
+    }
+
+    pub fn from_svg(data: &[u8]) -> Result<Source> {
+        let tree = usvg::Tree::from_data(data, &svg_options())?;
+        Ok(Source::Vector(Box::new(tree)))
+    }
+
+    /// 原始尺寸
+    pub fn size(&self) -> (f32, f32) {
+        match self {
+            Source::Raster(pix) => (pix.width() as f32, pix.height() as f32),
+            Source::Vector(tree) => (tree.size().width(), tree.size().height()),
+        }
+    }
+}
+
+/// 放置在画布上的一个图像，来源会被拉伸到 `width` x `height`
+#[derive(Clone)]
+pub struct Layer {
+    pub source: Arc<Source>,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// 裁剪多边形，画布坐标
+    pub clip: Option<Vec<(f32, f32)>>,
+    /// 绕图层中心顺时针旋转的角度
+    pub rotation: f32,
+}
+
+impl Layer {
+    fn transform(&self) -> Transform {
+        let (w, h) = self.source.size();
+        let t = Transform::from_row(self.width / w, 0.0, 0.0, self.height / h, self.x, self.y);
+        if self.rotation == 0.0 {
+            return t;
+        }
+        t.post_rotate_at(self.rotation, self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+/// 把背景和图层合成为最终的 banner
+pub struct Compositor {
+    pub width: u32,
+    pub height: u32,
+    pub background: Option<Background>,
+    pub layers: Vec<Layer>,
+    /// 无缝平铺：超出边缘的图层在对边再画一次，输出可以首尾相接地重复
+    pub wrap: bool,
+}
+
+impl Compositor {
+    pub fn new(width: u32, height: u32) -> Compositor {
+        Compositor {
+            width,
+            height,
+            background: None,
+            layers: Vec::new(),
+            wrap: false,
+        }
+    }
+
+    pub fn push(&mut self, layer: Layer) {
+        self.layers.push(layer);
+    }
+
+    // 实际绘制的图层，平铺时加上超出边缘部分在对边的副本
+    fn placed(&self) -> Vec<Layer> {
+        if !self.wrap {
+            return self.layers.clone();
+        }
+        let (w, h) = (self.width as f32, self.height as f32);
+        let mut rs = Vec::with_capacity(self.layers.len());
+        for layer in &self.layers {
+            for dy in [0.0, -h, h] {
+                for dx in [0.0, -w, w] {
+                    let (x, y) = (layer.x + dx, layer.y + dy);
+                    if x >= w || y >= h || x + layer.width <= 0.0 || y + layer.height <= 0.0 {
+                        continue;
+                    }
+                    rs.push(Layer {
+                        x,
+                        y,
+                        clip: layer
+                            .clip
+                            .as_ref()
+                            .map(|c| c.iter().map(|&(cx, cy)| (cx + dx, cy + dy)).collect()),
+                        ..layer.clone()
+                    });
+                }
+            }
+        }
+        rs
+    }
+
+    pub fn render(&self) -> Result<Pixmap> {
+        let mut pixmap = Pixmap::new(self.width, self.height).ok_or(anyhow!("incorrect size"))?;
+        if let Some(background) = &self.background {
+            background.paint(&mut pixmap);
+        }
+        let paint = PixmapPaint {
+            quality: FilterQuality::Bicubic,
+            ..Default::default()
+        };
+        for layer in &self.placed() {
+            let Some(clip) = &layer.clip else {
+                draw(&mut pixmap, layer, Transform::identity(), &paint);
+                continue;
+            };
+            // 先画到裁剪区域大小的画布上，裁剪后再叠加
+            let (x0, y0, x1, y1) = clip.iter().fold((f32::MAX, f32::MAX, f32::MIN, f32::MIN), |b, &(x, y)| {
+                (b.0.min(x), b.1.min(y), b.2.max(x), b.3.max(y))
+            });
+            let (x0, y0) = (x0.floor().max(0.0) as i32, y0.floor().max(0.0) as i32);
+            let x1 = x1.ceil().min(self.width as f32) as i32;
+            let y1 = y1.ceil().min(self.height as f32) as i32;
+            let (Some(mut canvas), Some(mut mask), Some(path)) = (
+                Pixmap::new((x1 - x0).max(0) as u32, (y1 - y0).max(0) as u32),
+                Mask::new((x1 - x0).max(0) as u32, (y1 - y0).max(0) as u32),
+                polygon_path(clip),
+            ) else {
+                continue;
+            };
+            let shift = Transform::from_translate(-x0 as f32, -y0 as f32);
+            draw(&mut canvas, layer, shift, &paint);
+            mask.fill_path(&path, FillRule::Winding, true, shift);
+            canvas.apply_mask(&mask);
+            pixmap.draw_pixmap(x0, y0, canvas.as_ref(), &PixmapPaint::default(), Transform::identity(), None);
+        }
+        Ok(pixmap)
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        Ok(self.render()?.encode_png()?)
+    }
+
+    /// 输出 SVG，矢量图层内联，位图图层以 data url 嵌入
+    pub fn to_svg(&self) -> Result<String> {
+        let mut out = String::new();
+        write!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            self.width, self.height
+        )?;
+        if let Some(background) = &self.background {
+            out.push_str(&background.to_svg(self.width, self.height)?);
+        }
+        for (index, layer) in self.placed().iter().enumerate() {
+            if let Some(clip) = &layer.clip {
+                write!(out, r#"<clipPath id="clip{}"><polygon points=""#, index)?;
+                for (x, y) in clip {
+                    write!(out, "{},{} ", x, y)?;
+                }
+                write!(out, r#""/></clipPath><g clip-path="url(#clip{})">"#, index)?;
+            }
+            let t = layer.transform();
+            write!(
+                out,
+                r#"<g transform="matrix({} {} {} {} {} {})">"#,
+                t.sx, t.ky, t.kx, t.sy, t.tx, t.ty
+            )?;
+            match layer.source.as_ref() {
+                Source::Raster(pix) => {
+                    let data = STANDARD.encode(pix.encode_png()?);
+                    write!(
+                        out,
+                        r#"<image width="{}" height="{}" href="data:image/png;base64,{}"/>"#,
+                        pix.width(),
+                        pix.height(),
+                        data
+                    )?;
+                }
+                Source::Vector(tree) => {
+                    let opt = usvg::WriteOptions {
+                        id_prefix: Some(format!("l{}-", index)),
+                        indent: usvg::Indent::None,
+                        ..Default::default()
+                    };
+                    out.push_str(&tree.to_string(&opt));
+                }
+            }
+            out.push_str("</g>");
+            if layer.clip.is_some() {
+                out.push_str("</g>");
+            }
+        }
+        out.push_str("</svg>");
+        Ok(out)
+    }
+}
+
+fn draw(pixmap: &mut Pixmap, layer: &Layer, shift: Transform, paint: &PixmapPaint) {
+    let transform = layer.transform().post_concat(shift);
+    match layer.source.as_ref() {
+        Source::Raster(pix) => {
+            pixmap.draw_pixmap(0, 0, pix.as_ref(), paint, transform, None);
+        }
+        Source::Vector(tree) => {
+            resvg::render(tree, transform, &mut pixmap.as_mut());
+        }
+    }
+}
+
+fn polygon_path(points: &[(f32, f32)]) -> Option<resvg::tiny_skia::Path> {
+    let (first, rest) = points.split_first()?;
+    let mut pb = PathBuilder::new();
+    pb.move_to(first.0, first.1);
+    for &(x, y) in rest {
+        pb.line_to(x, y);
+    }
+    pb.close();
+    pb.finish()
+}
+
+/// 解析 `#rrggbb` 或 `#rrggbbaa` 格式的颜色
+pub fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some(Color::from_rgba8(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+/// `#rrggbb` 格式，忽略透明度
+pub fn color_to_hex(color: Color) -> String {
+    let c = color.to_color_u8();
+    format!("#{:02x}{:02x}{:02x}", c.red(), c.green(), c.blue())
+}
@@ -0,0 +1,222 @@
+//! 容器：任意多边形或 SVG 轮廓作为静态边界，图标倒进去堆积在里面（例如罐子、笔记本屏幕、字母）。
+//! 前端 Wall.tsx 只有地面和两侧三个矩形
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use resvg::tiny_skia::Transform;
+use serde::{Deserialize, Serialize};
+
+use crate::compose::{color_to_hex, parse_color, Layer, Source};
+use crate::mask::{fit_tree, ShapeMask};
+use crate::overlay::Outline;
+use crate::png::svg_options;
+
+/// 内置容器，SVG 路径
+const BUILTIN: &[(&str, &str)] = &[
+    (
+        "jar",
+        "M32 0 L68 0 L68 14 Q96 20 96 48 L96 112 Q96 124 84 124 L16 124 Q4 124 4 112 L4 48 Q4 20 32 14 Z",
+    ),
+    ("bowl", "M0 0 L120 0 Q120 70 60 70 Q0 70 0 0 Z"),
+    ("laptop", "M0 0 L160 0 L160 100 L0 100 Z"),
+    ("funnel", "M0 0 L120 0 L72 70 L72 110 L48 110 L48 70 Z"),
+];
+
+/// 容器的外观，描边宽度为画布像素
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Style {
+    #[serde(default)]
+    pub fill: Option<String>,
+    #[serde(default)]
+    pub stroke: Option<Outline>,
+}
+
+#[derive(Clone)]
+pub struct Container {
+    /// 内部可以放置图标的区域，不含描边
+    pub mask: ShapeMask,
+    layer: Arc<Source>,
+    transform: Transform,
+}
+
+impl Container {
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(name, _)| *name)
+    }
+
+    pub fn builtin(name: &str, style: &Style, width: u32, height: u32) -> Option<Result<Container>> {
+        let (_, d) = BUILTIN.iter().find(|(n, _)| *n == name)?;
+        Some(Container::from_path(d, style, width, height))
+    }
+
+    /// 闭合的 SVG 路径，可以是凹多边形，例如 "M0 0 L40 0 L40 80 L80 80 L80 0 L120 0 L120 120 L0 120 Z"
+    pub fn from_path(d: &str, style: &Style, width: u32, height: u32) -> Result<Container> {
+        if d.contains(['"', '<', '>', '&']) {
+            bail!("invalid path data");
+        }
+        let opt = svg_options();
+        let path = |view: &str, attrs: &str| {
+            format!(r#"<svg xmlns="http://www.w3.org/2000/svg" {}><path d="{}" {}/></svg>"#, view, d, attrs)
+        };
+        let Some(bbox) = usvg::Tree::from_str(&path("", ""), &opt)?
+            .root()
+            .abs_bounding_box()
+            .to_non_zero_rect()
+        else {
+            bail!("container is empty");
+        };
+        // 描边画在轮廓上，四周留出描边的宽度
+        let stroke = style.stroke.as_ref().map_or(0.0, |s| s.width.max(0.0));
+        let margin = stroke.ceil();
+        let scale = ((width as f32 - margin * 2.0) / bbox.width())
+            .min((height as f32 - margin * 2.0) / bbox.height())
+            .max(f32::EPSILON);
+        let pad = margin / scale;
+        let (vw, vh) = (bbox.width() + pad * 2.0, bbox.height() + pad * 2.0);
+        let view = format!(
+            r#"width="{}" height="{}" viewBox="{} {} {} {}""#,
+            vw,
+            vh,
+            bbox.x() - pad,
+            bbox.y() - pad,
+            vw,
+            vh
+        );
+        let shape = |attrs: &str| path(&view, attrs);
+        let transform = Transform::from_row(
+            scale,
+            0.0,
+            0.0,
+            scale,
+            (width as f32 - vw * scale) / 2.0,
+            (height as f32 - vh * scale) / 2.0,
+        );
+        let filled = usvg::Tree::from_str(&shape(""), &opt)?;
+        let mut mask = ShapeMask::render(&filled, transform, width, height)?;
+
+        let fill = match &style.fill {
+            Some(color) => paint("fill", color)?,
+            None => r#"fill="none""#.to_string(),
+        };
+        let outline = match &style.stroke {
+            Some(s) => format!(
+                r#"{} stroke-width="{}" stroke-linejoin="round""#,
+                paint("stroke", &s.color)?,
+                s.width / scale
+            ),
+            None => String::new(),
+        };
+        let styled = usvg::Tree::from_str(&shape(&format!("{} {}", fill, outline)), &opt)?;
+        if !outline.is_empty() {
+            let ring = usvg::Tree::from_str(&shape(&format!(r#"fill="none" {}"#, outline)), &opt)?;
+            mask.subtract(&ShapeMask::render(&ring, transform, width, height)?);
+        }
+        Ok(Container {
+            mask,
+            layer: Arc::new(Source::Vector(Box::new(styled))),
+            transform,
+        })
+    }
+
+    /// SVG 中填充的部分作为容器内部，SVG 原样绘制
+    pub fn from_svg(data: &[u8], width: u32, height: u32) -> Result<Container> {
+        let tree = usvg::Tree::from_data(data, &svg_options())?;
+        let transform = fit_tree(&tree, width, height)?;
+        Ok(Container {
+            mask: ShapeMask::render(&tree, transform, width, height)?,
+            layer: Arc::new(Source::Vector(Box::new(tree))),
+            transform,
+        })
+    }
+
+    /// 绘制容器的图层，放在图标下面
+    pub fn layer(&self) -> Layer {
+        let (w, h) = self.layer.size();
+        Layer {
+            source: self.layer.clone(),
+            x: self.transform.tx,
+            y: self.transform.ty,
+            width: w * self.transform.sx,
+            height: h * self.transform.sy,
+            clip: None,
+            rotation: 0.0,
+        }
+    }
+}
+
+fn paint(name: &str, text: &str) -> Result<String> {
+    let Some(color) = parse_color(text) else {
+        bail!("invalid color: {}", text);
+    };
+    Ok(format!(r#"{0}="{1}" {0}-opacity="{2}""#, name, color_to_hex(color), color.alpha()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::align::{polygons_overlap, Icon, Position};
+    use crate::frame::Frame;
+    use crate::physics::settle;
+
+    fn squares(count: usize, size: u32) -> Vec<Position> {
+        let polygon = vec![(0, 0), (size, 0), (size, size), (0, size)];
+        (0..count)
+            .map(|_| Position::new(Icon::new(size, size, polygon.clone(), 1.0)))
+            .collect()
+    }
+
+    #[test]
+    fn parse_and_layer() {
+        // 160 x 100 的屏幕放大两倍铺满画布
+        let laptop = Container::builtin("laptop", &Style::default(), 320, 200).unwrap().unwrap();
+        assert_eq!(laptop.mask.bounds(), Some((0, 0, 320, 200)));
+        let layer = laptop.layer();
+        assert_eq!((layer.x, layer.y, layer.width, layer.height), (0.0, 0.0, 320.0, 200.0));
+
+        // 描边占用的部分不能放图标
+        let style = Style {
+            fill: Some("#ffffff".to_string()),
+            stroke: Some(Outline {
+                color: "#000000".to_string(),
+                width: 10.0,
+            }),
+        };
+        let framed = Container::builtin("laptop", &style, 320, 200).unwrap().unwrap();
+        let (x0, y0, x1, y1) = framed.mask.bounds().unwrap();
+        assert!(x0 >= 10 && y0 >= 10 && x1 <= 310 && y1 <= 190, "{:?}", (x0, y0, x1, y1));
+
+        assert!(Container::builtin("teapot", &Style::default(), 100, 100).is_none());
+        assert!(Container::from_path(r#"M0 0"/>"#, &Style::default(), 100, 100).is_err());
+        let invalid = Style {
+            fill: Some("white".to_string()),
+            stroke: None,
+        };
+        assert!(Container::from_path("M0 0 L10 0 L10 10 Z", &invalid, 100, 100).is_err());
+
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg"><rect x="10" y="10" width="50" height="100"/></svg>"#;
+        let container = Container::from_svg(svg, 100, 100).unwrap();
+        assert_eq!(container.mask.bounds(), Some((25, 0, 75, 100)));
+    }
+
+    #[test]
+    fn piled_icons_stay_inside() {
+        let jar = Container::builtin("jar", &Style::default(), 200, 240).unwrap().unwrap();
+        let mut items = squares(12, 20);
+        let overflow = settle(&mut items, 200, 240, 5, &Frame::default(), Some(&jar.mask));
+        assert!(overflow.len() < items.len());
+        let placed: Vec<&Position> = (0..items.len()).filter(|i| !overflow.contains(i)).map(|i| &items[i]).collect();
+        for item in &placed {
+            for y in item.y..item.y + 20 {
+                for x in item.x..item.x + 20 {
+                    assert!(jar.mask.contains(x, y), "({}, {}) outside the jar", x, y);
+                }
+            }
+        }
+        for (i, a) in placed.iter().enumerate() {
+            for b in &placed[i + 1..] {
+                let polygon = a.icon.scaled_polygon();
+                assert!(!polygons_overlap(&polygon, &b.icon.scaled_polygon(), (a.x, a.y), (b.x, b.y)));
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! 画框：四周留白、圆角和保留区域（例如名字或头像的位置），排布时图标不会进入这些区域。
+//! 对应前端 `Frame` 的 `left`、`right`
+use serde::{Deserialize, Serialize};
+
+use crate::align::Polygon;
+
+// 每个圆角用几段折线近似
+const CORNER_SEGMENTS: u32 = 4;
+
+/// 四边留白，像素
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Insets {
+    #[serde(default)]
+    pub top: u32,
+    #[serde(default)]
+    pub right: u32,
+    #[serde(default)]
+    pub bottom: u32,
+    #[serde(default)]
+    pub left: u32,
+}
+
+impl Insets {
+    /// 与 CSS 的 margin 相同："10"、"10,20"、"10,20,30"、"10,20,30,40"（上右下左）
+    pub fn parse(text: &str) -> Option<Insets> {
+        let values = text
+            .split(',')
+            .map(|v| v.trim().parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        let (top, right, bottom, left) = match values[..] {
+            [a] => (a, a, a, a),
+            [a, b] => (a, b, a, b),
+            [a, b, c] => (a, b, c, b),
+            [a, b, c, d] => (a, b, c, d),
+            _ => return None,
+        };
+        Some(Insets { top, right, bottom, left })
+    }
+}
+
+/// 不允许放置图标的矩形区域
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// "x,y,width,height"
+    pub fn parse(text: &str) -> Option<Region> {
+        let values = text
+            .split(',')
+            .map(|v| v.trim().parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        let [x, y, width, height] = values[..] else {
+            return None;
+        };
+        Some(Region { x, y, width, height })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Frame {
+    #[serde(default)]
+    pub insets: Insets,
+    /// 留白以内区域的圆角半径
+    #[serde(default)]
+    pub radius: u32,
+    #[serde(default)]
+    pub keep_out: Vec<Region>,
+}
+
+impl Frame {
+    /// 可以放置图标的区域 (min_x, min_y, max_x, max_y)，留白超过画布时为空
+    pub fn area(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let Insets { top, right, bottom, left } = self.insets;
+        let max_x = width.saturating_sub(right).max(left);
+        let max_y = height.saturating_sub(bottom).max(top);
+        (left.min(max_x), top.min(max_y), max_x, max_y)
+    }
+
+    /// 圆角外侧和保留区域转换成的凸多边形，坐标为画布绝对坐标
+    pub fn obstacles(&self, width: u32, height: u32) -> Vec<(Polygon, (u32, u32))> {
+        let mut rs: Vec<(Polygon, (u32, u32))> = self
+            .keep_out
+            .iter()
+            .filter(|r| r.width > 0 && r.height > 0)
+            .map(|r| {
+                let polygon = vec![(0, 0), (r.width, 0), (r.width, r.height), (0, r.height)];
+                (polygon, (r.x, r.y))
+            })
+            .collect();
+        let (x0, y0, x1, y1) = self.area(width, height);
+        let radius = self.radius.min((x1 - x0) / 2).min((y1 - y0) / 2);
+        if radius == 0 {
+            return rs;
+        }
+        // 角点、圆心，以及从角点看去的方向
+        let corners = [
+            ((x0, y0), (x0 + radius, y0 + radius)),
+            ((x1, y0), (x1 - radius, y0 + radius)),
+            ((x1, y1), (x1 - radius, y1 - radius)),
+            ((x0, y1), (x0 + radius, y1 - radius)),
+        ];
+        for (corner, center) in corners {
+            let sign = |a: u32, b: u32| if a < b { -1.0 } else { 1.0 };
+            let (sx, sy) = (sign(corner.0, center.0), sign(corner.1, center.1));
+            let arc: Vec<(u32, u32)> = (0..=CORNER_SEGMENTS)
+                .map(|i| {
+                    let t = std::f32::consts::FRAC_PI_2 * i as f32 / CORNER_SEGMENTS as f32;
+                    let x = center.0 as f32 + sx * radius as f32 * t.cos();
+                    let y = center.1 as f32 + sy * radius as f32 * t.sin();
+                    (x.round() as u32, y.round() as u32)
+                })
+                .collect();
+            // 圆弧与角点之间的区域是凹的，拆成以角点为顶点的三角形
+            for pair in arc.windows(2) {
+                rs.push((vec![corner, pair[0], pair[1]], (0, 0)));
+            }
+        }
+        rs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_insets() {
+        let insets = |top, right, bottom, left| Some(Insets { top, right, bottom, left });
+        assert_eq!(Insets::parse("10"), insets(10, 10, 10, 10));
+        assert_eq!(Insets::parse("10, 20"), insets(10, 20, 10, 20));
+        assert_eq!(Insets::parse("10,20,30"), insets(10, 20, 30, 20));
+        assert_eq!(Insets::parse("10,20,30,40"), insets(10, 20, 30, 40));
+        assert_eq!(Insets::parse("1,2,3,4,5"), None);
+        assert_eq!(Insets::parse("-1"), None);
+        assert_eq!(Insets::parse(""), None);
+    }
+
+    #[test]
+    fn parse_region() {
+        assert_eq!(Region::parse("1, 2,3,4"), Some(Region { x: 1, y: 2, width: 3, height: 4 }));
+        assert_eq!(Region::parse("1,2,3"), None);
+        assert_eq!(Region::parse("1,2,3,x"), None);
+    }
+}
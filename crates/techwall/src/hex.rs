@@ -0,0 +1,352 @@
+//! 六边形贴纸墙：图标放在蜂窝网格的六边形格子中间，格子可以填充颜色和描边，图标裁剪在格子里
+use std::f32::consts::PI;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::Result;
+use resvg::tiny_skia::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::align::{polygons_overlap, Polygon};
+use crate::compose::{color_to_hex, Layer, Source};
+use crate::overlay::{color, paint_attrs, Outline};
+use crate::png::svg_options;
+use crate::recolor::dominant;
+
+const SQRT3: f32 = 1.732_050_8;
+// 自动计算格子大小时的最小半径
+const MIN_RADIUS: f32 = 8.0;
+
+fn default_gap() -> f32 {
+    6.0
+}
+
+fn default_padding() -> f32 {
+    0.2
+}
+
+/// 六边形格子的外观
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Hex {
+    /// 格子的外接圆半径，为空时按图标数量自动计算
+    #[serde(default)]
+    pub radius: Option<f32>,
+    /// 相邻格子之间的间距
+    #[serde(default = "default_gap")]
+    pub gap: f32,
+    /// 图标到格子边缘的距离，格子半径的比例
+    #[serde(default = "default_padding")]
+    pub padding: f32,
+    /// "#rrggbb"、图标主色 "dominant" 或主色的浅色 "tint"，为空时不填充
+    #[serde(default)]
+    pub fill: Option<String>,
+    #[serde(default)]
+    pub border: Option<Outline>,
+}
+
+impl Default for Hex {
+    fn default() -> Self {
+        Hex {
+            radius: None,
+            gap: default_gap(),
+            padding: default_padding(),
+            fill: None,
+            border: None,
+        }
+    }
+}
+
+/// 尖顶六边形的顶点
+pub fn hexagon(cx: f32, cy: f32, r: f32) -> Vec<(f32, f32)> {
+    (0..6)
+        .map(|i| {
+            let a = PI / 3.0 * i as f32 - PI / 2.0;
+            (cx + r * a.cos(), cy + r * a.sin())
+        })
+        .collect()
+}
+
+// 蜂窝网格，奇数行错开半格，整体在区域内居中
+fn grid(pitch: f32, area: (u32, u32, u32, u32)) -> Vec<(f32, f32)> {
+    let (x0, y0, x1, y1) = area;
+    let (w, h) = ((x1 - x0) as f32, (y1 - y0) as f32);
+    let dx = SQRT3 * pitch;
+    let dy = 1.5 * pitch;
+    if h < 2.0 * pitch || w < dx {
+        return Vec::new();
+    }
+    let rows = ((h - 2.0 * pitch) / dy).floor() as usize + 1;
+    let shift = if rows > 1 { dx / 2.0 } else { 0.0 };
+    let cols = ((w - shift) / dx).floor() as usize;
+    let left = x0 as f32 + (w - dx * cols as f32 - shift) / 2.0 + dx / 2.0;
+    let top = y0 as f32 + (h - 2.0 * pitch - dy * (rows - 1) as f32) / 2.0 + pitch;
+    let mut rs = Vec::with_capacity(rows * cols);
+    for row in 0..rows {
+        let offset = if row % 2 == 1 { shift } else { 0.0 };
+        for col in 0..cols {
+            rs.push((left + offset + dx * col as f32, top + dy * row as f32));
+        }
+    }
+    rs
+}
+
+impl Hex {
+    /// 格子本身的半径，扣除间距
+    pub fn tile(&self, pitch: f32) -> f32 {
+        (pitch - self.gap.max(0.0) / SQRT3).max(1.0)
+    }
+
+    /// 格子中心，避开障碍物；`count` 个图标时选最靠近中心的格子，按由内向外排列。
+    /// 返回网格的外接圆半径和格子中心
+    pub fn slots(
+        &self,
+        count: usize,
+        area: (u32, u32, u32, u32),
+        obstacles: &[(Polygon, (u32, u32))],
+    ) -> (f32, Vec<(f32, f32)>) {
+        let free = |pitch: f32| -> Vec<(f32, f32)> {
+            let r = self.tile(pitch);
+            grid(pitch, area)
+                .into_iter()
+                .filter(|&(cx, cy)| {
+                    let polygon: Polygon = hexagon(cx, cy, r)
+                        .into_iter()
+                        .map(|(x, y)| (x.max(0.0).round() as u32, y.max(0.0).round() as u32))
+                        .collect();
+                    !obstacles.iter().any(|(p, o)| polygons_overlap(&polygon, p, (0, 0), *o))
+                })
+                .collect()
+        };
+        let pitch = match self.radius {
+            Some(r) => r.max(1.0),
+            None => {
+                // 二分查找放得下所有图标的最大半径
+                let (w, h) = ((area.2 - area.0) as f32, (area.3 - area.1) as f32);
+                let (mut lo, mut hi) = (MIN_RADIUS, (w / SQRT3).min(h / 2.0).max(MIN_RADIUS));
+                for _ in 0..24 {
+                    let mid = (lo + hi) / 2.0;
+                    if free(mid).len() >= count {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                lo
+            }
+        };
+        let mut slots = free(pitch);
+        let cx = (area.0 + area.2) as f32 / 2.0;
+        let cy = (area.1 + area.3) as f32 / 2.0;
+        let distance = |&(x, y): &(f32, f32)| (x - cx).powi(2) + (y - cy).powi(2);
+        slots.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        slots.truncate(count);
+        (pitch, slots)
+    }
+
+    /// 图标在格子里的缩放，保持比例放进内缩后的六边形
+    pub fn scale(&self, pitch: f32, width: u32, height: u32) -> f32 {
+        let border = self.border.as_ref().map_or(0.0, |b| b.width.max(0.0));
+        let r = (self.tile(pitch) - border) * (1.0 - self.padding.clamp(0.0, 0.9));
+        let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+        // 矩形的半宽 a、半高 b 需满足 a <= r * sqrt(3) / 2 且 b + a / sqrt(3) <= r
+        (r * SQRT3 / w).min(r / (h / 2.0 + w / (2.0 * SQRT3))).max(0.0)
+    }
+
+    /// 图标的裁剪区域，描边以内的六边形
+    pub fn clip(&self, pitch: f32, center: (f32, f32)) -> Vec<(f32, f32)> {
+        let border = self.border.as_ref().map_or(0.0, |b| b.width.max(0.0));
+        hexagon(center.0, center.1, (self.tile(pitch) - border / 2.0).max(0.5))
+    }
+
+    /// 每个格子的填充色
+    pub fn fill_color(&self, source: &Source) -> Result<Option<Color>> {
+        let Some(fill) = &self.fill else {
+            return Ok(None);
+        };
+        let color = match fill.as_str() {
+            "dominant" => dominant(source),
+            "tint" => dominant(source).map(|c| mix(c, Color::WHITE, 0.8)),
+            text => Some(color(text)?),
+        };
+        Ok(color)
+    }
+
+    /// 格子的填充层，`tiles` 为中心和颜色
+    pub fn fill_layer(&self, pitch: f32, tiles: &[((f32, f32), Color)], width: u32, height: u32) -> Result<Layer> {
+        let r = self.tile(pitch);
+        self.layer(width, height, |svg| {
+            for &((cx, cy), color) in tiles {
+                write!(
+                    svg,
+                    r#"<polygon points="{}" fill="{}" fill-opacity="{}"/>"#,
+                    points(&hexagon(cx, cy, r)),
+                    color_to_hex(color),
+                    color.alpha()
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// 格子的描边层，画在图标上面
+    pub fn border_layer(&self, pitch: f32, centers: &[(f32, f32)], width: u32, height: u32) -> Result<Option<Layer>> {
+        let Some(border) = self.border.as_ref().filter(|b| b.width > 0.0) else {
+            return Ok(None);
+        };
+        let r = self.tile(pitch) - border.width / 2.0;
+        let stroke = paint_attrs("stroke", &border.color)?;
+        let layer = self.layer(width, height, |svg| {
+            for &(cx, cy) in centers {
+                write!(
+                    svg,
+                    r#"<polygon points="{}" fill="none" {} stroke-width="{}" stroke-linejoin="round"/>"#,
+                    points(&hexagon(cx, cy, r)),
+                    stroke,
+                    border.width
+                )?;
+            }
+            Ok(())
+        })?;
+        Ok(Some(layer))
+    }
+
+    fn layer(&self, width: u32, height: u32, body: impl FnOnce(&mut String) -> Result<()>) -> Result<Layer> {
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height
+        )?;
+        body(&mut svg)?;
+        svg.push_str("</svg>");
+        let tree = usvg::Tree::from_str(&svg, &svg_options())?;
+        Ok(Layer {
+            source: Arc::new(Source::Vector(Box::new(tree))),
+            x: 0.0,
+            y: 0.0,
+            width: width as f32,
+            height: height as f32,
+            clip: None,
+            rotation: 0.0,
+        })
+    }
+}
+
+fn points(polygon: &[(f32, f32)]) -> String {
+    let mut rs = String::new();
+    for (x, y) in polygon {
+        let _ = write!(rs, "{},{} ", x, y);
+    }
+    rs
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let lerp = |x: f32, y: f32| x + (y - x) * t;
+    Color::from_rgba(lerp(a.red(), b.red()), lerp(a.green(), b.green()), lerp(a.blue(), b.blue()), 1.0)
+        .unwrap_or(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use resvg::tiny_skia::Pixmap;
+
+    const AREA: (u32, u32, u32, u32) = (0, 0, 300, 200);
+
+    // 凸多边形内（含边界），顶点按顺时针排列
+    fn inside(polygon: &[(f32, f32)], (x, y): (f32, f32)) -> bool {
+        (0..polygon.len()).all(|i| {
+            let (ax, ay) = polygon[i];
+            let (bx, by) = polygon[(i + 1) % polygon.len()];
+            (bx - ax) * (y - ay) - (by - ay) * (x - ax) >= -1e-3
+        })
+    }
+
+    #[test]
+    fn slots_fill_from_centre() {
+        let hex = Hex::default();
+        let (pitch, slots) = hex.slots(7, AREA, &[]);
+        assert_eq!(slots.len(), 7);
+        assert!(pitch >= MIN_RADIUS);
+        let distance = |(x, y): (f32, f32)| (x - 150.0).hypot(y - 100.0);
+        assert!(slots.windows(2).all(|w| distance(w[0]) <= distance(w[1])));
+        for (i, &(ax, ay)) in slots.iter().enumerate() {
+            // 格子在区域内且互不重叠
+            for (x, y) in hexagon(ax, ay, hex.tile(pitch)) {
+                assert!((-0.5..=300.5).contains(&x) && (-0.5..=200.5).contains(&y));
+            }
+            for &(bx, by) in &slots[i + 1..] {
+                assert!((ax - bx).hypot(ay - by) >= SQRT3 * pitch - 0.01);
+            }
+        }
+
+        let fixed = Hex {
+            radius: Some(40.0),
+            ..Hex::default()
+        };
+        let (pitch, slots) = fixed.slots(100, AREA, &[]);
+        assert_eq!(pitch, 40.0);
+        assert!(!slots.is_empty() && slots.len() < 100);
+    }
+
+    #[test]
+    fn slots_avoid_obstacles() {
+        let hex = Hex {
+            radius: Some(20.0),
+            ..Hex::default()
+        };
+        let obstacle = (vec![(0, 0), (150, 0), (150, 200), (0, 200)], (0, 0));
+        let (pitch, slots) = hex.slots(100, AREA, &[obstacle]);
+        assert!(!slots.is_empty());
+        for (cx, _) in slots {
+            assert!(cx - hex.tile(pitch) * SQRT3 / 2.0 >= 149.0, "{}", cx);
+        }
+    }
+
+    #[test]
+    fn icons_fit_clip() {
+        let hex = Hex {
+            padding: 0.0,
+            border: Some(Outline {
+                color: "#000000".to_string(),
+                width: 4.0,
+            }),
+            ..Hex::default()
+        };
+        let pitch = 50.0;
+        let clip = hex.clip(pitch, (0.0, 0.0));
+        assert!((clip[0].1 + hex.tile(pitch) - 2.0).abs() < 1e-3);
+        for (w, h) in [(100, 100), (300, 60), (40, 200)] {
+            let scale = hex.scale(pitch, w, h);
+            let (hw, hh) = (w as f32 * scale / 2.0, h as f32 * scale / 2.0);
+            for corner in [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)] {
+                assert!(inside(&clip, corner), "{}x{} corner {:?}", w, h, corner);
+            }
+        }
+    }
+
+    #[test]
+    fn tile_colors() {
+        let mut pix = Pixmap::new(4, 4).unwrap();
+        pix.fill(Color::from_rgba8(255, 0, 0, 255));
+        let source = Source::Raster(pix);
+        let color_of = |fill: Option<&str>| {
+            let hex = Hex {
+                fill: fill.map(str::to_string),
+                ..Hex::default()
+            };
+            hex.fill_color(&source).map(|c| c.map(|c| c.to_color_u8()))
+        };
+        let rgb = |c: Option<resvg::tiny_skia::ColorU8>| c.map(|c| (c.red(), c.green(), c.blue()));
+        assert_eq!(rgb(color_of(None).unwrap()), None);
+        assert_eq!(rgb(color_of(Some("#336699")).unwrap()), Some((0x33, 0x66, 0x99)));
+        assert_eq!(rgb(color_of(Some("dominant")).unwrap()), Some((255, 0, 0)));
+        assert_eq!(rgb(color_of(Some("tint")).unwrap()), Some((255, 204, 204)));
+        assert!(color_of(Some("red-ish")).is_err());
+
+        let tiles = [((50.0, 50.0), Color::from_rgba8(255, 0, 0, 255))];
+        let layer = Hex::default().fill_layer(40.0, &tiles, 100, 100).unwrap();
+        assert_eq!((layer.width, layer.height), (100.0, 100.0));
+        assert_eq!(dominant(&layer.source).map(|c| c.to_color_u8().red()), Some(255));
+    }
+}
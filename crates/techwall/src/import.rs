@@ -0,0 +1,191 @@
+//! 导入自定义图标（例如内部产品的 logo）：校验、清理 SVG、生成栅格和轮廓
+use anyhow::{anyhow, bail, Result};
+use resvg::tiny_skia::Pixmap;
+
+use crate::catalog::{display_name, Category, Entry, Variant, VariantKind};
+use crate::meta::{Meta, MetaData};
+use crate::png::svg_options;
+
+/// 导入时的校验条件
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// 文件大小上限，字节
+    pub max_bytes: usize,
+    /// 原图最短边
+    pub min_size: f32,
+    /// 原图最长边
+    pub max_size: f32,
+    /// 长边与短边之比的上限
+    pub max_aspect: f32,
+    /// 栅格化后可见像素的最低占比
+    pub min_coverage: f32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_bytes: 2 * 1024 * 1024,
+            min_size: 16.0,
+            max_size: 8192.0,
+            max_aspect: 8.0,
+            min_coverage: 0.01,
+        }
+    }
+}
+
+pub struct Imported {
+    pub meta: Meta,
+    pub png: Vec<u8>,
+    /// 清理后的 SVG，导入 PNG 时为空
+    pub svg: Option<String>,
+}
+
+fn is_png(data: &[u8]) -> bool {
+    data.starts_with(b"\x89PNG\r\n\x1a\n")
+}
+
+/// 图标名只允许小写字母、数字和 `-`、`_`、`.`，会用作文件名
+pub fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!("invalid icon name: {:?}", name);
+    }
+    Ok(())
+}
+
+fn check_size(width: f32, height: f32, limits: &Limits) -> Result<()> {
+    let (short, long) = (width.min(height), width.max(height));
+    if short < limits.min_size {
+        bail!("icon too small: {}x{}, at least {} required", width, height, limits.min_size);
+    }
+    if long > limits.max_size {
+        bail!("icon too large: {}x{}, at most {} allowed", width, height, limits.max_size);
+    }
+    if long / short > limits.max_aspect {
+        bail!("aspect ratio {:.1} exceeds {}", long / short, limits.max_aspect);
+    }
+    Ok(())
+}
+
+fn check_alpha(png: &[u8], limits: &Limits) -> Result<()> {
+    let pix = Pixmap::decode_png(png)?;
+    let visible = pix.pixels().iter().filter(|p| p.alpha() > 0).count();
+    let total = (pix.width() * pix.height()) as usize;
+    if visible == 0 || (visible as f32) < total as f32 * limits.min_coverage {
+        bail!("icon is empty or fully transparent");
+    }
+    Ok(())
+}
+
+/// 经 usvg 解析再输出，去掉脚本、事件属性、`foreignObject` 和外部引用，
+/// 文字转成路径。只保留 data URL 形式的内嵌图片
+pub fn sanitize_svg(data: &[u8]) -> Result<(String, usvg::Size)> {
+    let text = std::str::from_utf8(data).map_err(|_| anyhow!("SVG is not valid UTF-8"))?;
+    let mut opt = svg_options();
+    opt.resources_dir = None;
+    opt.image_href_resolver.resolve_string = Box::new(|_, _| None);
+    let tree = usvg::Tree::from_str(text, &opt)?;
+    let svg = tree.to_string(&usvg::WriteOptions::default());
+    Ok((svg, tree.size()))
+}
+
+/// 校验并导入 SVG 或 PNG，返回 meta、栅格 PNG 和清理后的 SVG
+pub fn import(name: &str, data: &[u8], limits: &Limits) -> Result<Imported> {
+    check_name(name)?;
+    if data.len() > limits.max_bytes {
+        bail!("file too large: {} bytes, at most {} allowed", data.len(), limits.max_bytes);
+    }
+    let imported = if is_png(data) {
+        let pix = Pixmap::decode_png(data)?;
+        check_size(pix.width() as f32, pix.height() as f32, limits)?;
+        let (meta, png) = Meta::from_png(name, data)?;
+        Imported { meta, png, svg: None }
+    } else {
+        let (svg, size) = sanitize_svg(data)?;
+        check_size(size.width(), size.height(), limits)?;
+        let (mut meta, png) = Meta::from_svg(name, svg.clone().into_bytes())?;
+        meta.svg = Some(format!("{}.svg", name));
+        Imported {
+            meta,
+            png,
+            svg: Some(svg),
+        }
+    };
+    check_alpha(&imported.png, limits)?;
+    Ok(imported)
+}
+
+/// 导入的图标在目录中的条目
+pub fn entry(name: &str, display: Option<&str>, category: Category, aliases: Vec<String>) -> Entry {
+    Entry {
+        name: name.to_string(),
+        display: display.map(|d| d.to_string()).unwrap_or_else(|| display_name(name)),
+        aliases,
+        category,
+        score: 0,
+        popularity: 0.0,
+        variants: vec![Variant {
+            name: name.to_string(),
+            kind: VariantKind::of(name),
+        }],
+    }
+}
+
+/// 把导入结果合并进用户目录
+pub fn add_to(user: &mut MetaData, imported: &Imported, entry: Entry) {
+    user.insert(imported.meta.clone());
+    user.catalog.insert(entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert!(check_name("acme-logo_2.1").is_ok());
+        for name in ["", ".hidden", "Acme", "a/b", "../x", "a b", &"a".repeat(65)] {
+            assert!(check_name(name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        let limits = Limits::default();
+        assert!(check_size(64.0, 32.0, &limits).is_ok());
+        assert!(check_size(8.0, 64.0, &limits).is_err());
+        assert!(check_size(10000.0, 2000.0, &limits).is_err());
+        assert!(check_size(1000.0, 100.0, &limits).is_err());
+    }
+
+    #[test]
+    fn transparent_png_is_rejected() {
+        let mut pix = Pixmap::new(20, 20).unwrap();
+        let limits = Limits::default();
+        assert!(check_alpha(&pix.encode_png().unwrap(), &limits).is_err());
+        pix.fill(resvg::tiny_skia::Color::BLACK);
+        assert!(check_alpha(&pix.encode_png().unwrap(), &limits).is_ok());
+    }
+
+    #[test]
+    fn sanitize_strips_active_content() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="32" height="32" onload="alert(1)">
+            <script>alert(2)</script>
+            <rect width="32" height="32" fill="red" onclick="alert(3)"/>
+            <image href="https://example.com/x.png" width="32" height="32"/>
+            <image xlink:href="file:///etc/passwd" width="32" height="32"/>
+            <foreignObject width="32" height="32"><div>hi</div></foreignObject>
+        </svg>"#;
+        let (clean, size) = sanitize_svg(svg).unwrap();
+        assert_eq!((size.width(), size.height()), (32.0, 32.0));
+        for needle in ["script", "alert", "onload", "onclick", "example.com", "passwd", "foreignObject"] {
+            assert!(!clean.contains(needle), "{} in {}", needle, clean);
+        }
+        assert!(clean.contains("path"));
+    }
+}
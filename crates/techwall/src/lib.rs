@@ -1 +1,29 @@
-pub mod png;
\ No newline at end of file
+pub mod align;
+pub mod analyze;
+pub mod animate;
+pub mod background;
+pub mod badge;
+pub mod banner;
+pub mod catalog;
+pub mod compose;
+pub mod container;
+pub mod frame;
+pub mod hex;
+pub mod import;
+pub mod mask;
+pub mod meta;
+pub mod metrics;
+pub mod optimize;
+pub mod orbit;
+pub mod overlay;
+pub mod physics;
+pub mod png;
+pub mod recolor;
+pub mod resolve;
+pub mod search;
+pub mod shelves;
+pub mod sizing;
+pub mod spiral;
+pub mod tile;
+pub mod treemap;
+mod rng;
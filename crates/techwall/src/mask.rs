@@ -0,0 +1,475 @@
+//! 形状填充：图标只放在目标形状（文字、爱心、任意透明图或 SVG 路径）内部，
+//! 像词云一样从中心向外排，图标大小随剩余空间缩小，直到填满
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use resvg::tiny_skia::{Pixmap, Transform};
+use usvg::fontdb;
+
+use crate::align::{Icon, Polygon, Position};
+use crate::overlay::check_fonts;
+use crate::png::{svg_options, to_pixmap};
+
+// 图标外接矩形总面积占形状面积的目标比例
+const DENSITY: f32 = 0.6;
+// 每次放不下时的缩小比例
+const SHRINK: f32 = 0.8;
+// 图标短边的最小像素
+const MIN_SIDE: f32 = 12.0;
+// 图标之间的间隙
+const GAP: u32 = 2;
+// 重复填充时最多放置的图标数
+const MAX_ITEMS: usize = 400;
+
+/// 内置形状，100 x 100 的 SVG 路径
+const BUILTIN: &[(&str, &str)] = &[
+    ("heart", "M50 90 L12 52 A22 22 0 0 1 50 18 A22 22 0 0 1 88 52 Z"),
+    ("circle", "M50 2 A48 48 0 1 1 49.99 2 Z"),
+    ("star", "M50 2 L62 38 L98 38 L69 60 L80 96 L50 74 L20 96 L31 60 L2 38 L38 38 Z"),
+    ("hexagon", "M50 2 L92 26 L92 74 L50 98 L8 74 L8 26 Z"),
+    ("diamond", "M50 2 L98 50 L50 98 L2 50 Z"),
+];
+
+/// 与画布同尺寸的形状，`true` 表示可以放置图标
+#[derive(Clone, Debug)]
+pub struct ShapeMask {
+    pub width: u32,
+    pub height: u32,
+    inside: Vec<bool>,
+}
+
+impl ShapeMask {
+    /// 整个画布
+    pub fn full(width: u32, height: u32) -> ShapeMask {
+        ShapeMask {
+            width,
+            height,
+            inside: vec![true; (width * height) as usize],
+        }
+    }
+
+    /// 内置形状的名字
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(name, _)| *name)
+    }
+
+    pub fn builtin(name: &str, width: u32, height: u32) -> Option<ShapeMask> {
+        let (_, d) = BUILTIN.iter().find(|(n, _)| *n == name)?;
+        ShapeMask::from_path(d, width, height).ok()
+    }
+
+    /// SVG 路径数据，例如 "M0 0 L100 0 L50 80 Z"
+    pub fn from_path(d: &str, width: u32, height: u32) -> Result<ShapeMask> {
+        if d.contains(['"', '<', '>', '&']) {
+            bail!("invalid path data");
+        }
+        let svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg"><path d="{}"/></svg>"#, d);
+        ShapeMask::from_svg(svg.as_bytes(), width, height)
+    }
+
+    /// SVG 中所有可见内容的并集
+    pub fn from_svg(data: &[u8], width: u32, height: u32) -> Result<ShapeMask> {
+        let tree = usvg::Tree::from_data(data, &svg_options())?;
+        ShapeMask::from_tree(&tree, width, height)
+    }
+
+    /// 文字的轮廓，例如姓名缩写
+    pub fn from_text(text: &str, fonts: &Arc<fontdb::Database>, width: u32, height: u32) -> Result<ShapeMask> {
+        check_fonts(fonts)?;
+        let escaped: String = text
+            .chars()
+            .map(|c| match c {
+                '&' => "&amp;".to_string(),
+                '<' => "&lt;".to_string(),
+                '>' => "&gt;".to_string(),
+                _ => c.to_string(),
+            })
+            .collect();
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><text y="200" font-family="sans-serif" font-weight="900" font-size="200">{}</text></svg>"#,
+            escaped
+        );
+        let opt = usvg::Options {
+            fontdb: fonts.clone(),
+            ..Default::default()
+        };
+        ShapeMask::from_tree(&usvg::Tree::from_str(&svg, &opt)?, width, height)
+    }
+
+    /// 带透明度的图片取不透明部分，完全不透明的图片（例如 JPG 剪影）取深色部分
+    pub fn from_image(data: &[u8], width: u32, height: u32) -> Result<ShapeMask> {
+        let img = to_pixmap(&image::load_from_memory(data)?.to_rgba8())?;
+        let opaque = img.pixels().iter().all(|p| p.alpha() == 255);
+        let (w, h) = (img.width() as f32, img.height() as f32);
+        let mut pixmap = render_target(width, height)?;
+        pixmap.draw_pixmap(0, 0, img.as_ref(), &Default::default(), fit(w, h, 0.0, 0.0, width, height), None);
+        let inside = pixmap
+            .pixels()
+            .iter()
+            .map(|p| {
+                let c = p.demultiply();
+                if opaque {
+                    c.alpha() >= 128 && (c.red() as u32 + c.green() as u32 + c.blue() as u32) < 384
+                } else {
+                    c.alpha() >= 128
+                }
+            })
+            .collect();
+        Ok(ShapeMask { width, height, inside })
+    }
+
+    // 按内容的外接矩形居中缩放到画布
+    fn from_tree(tree: &usvg::Tree, width: u32, height: u32) -> Result<ShapeMask> {
+        ShapeMask::render(tree, fit_tree(tree, width, height)?, width, height)
+    }
+
+    /// 按指定变换栅格化，不透明部分为形状
+    pub(crate) fn render(tree: &usvg::Tree, transform: Transform, width: u32, height: u32) -> Result<ShapeMask> {
+        let mut pixmap = render_target(width, height)?;
+        resvg::render(tree, transform, &mut pixmap.as_mut());
+        let inside = pixmap.pixels().iter().map(|p| p.alpha() >= 128).collect();
+        Ok(ShapeMask { width, height, inside })
+    }
+
+    /// 去掉 `other` 中的部分
+    pub(crate) fn subtract(&mut self, other: &ShapeMask) {
+        for (a, b) in self.inside.iter_mut().zip(&other.inside) {
+            *a = *a && !b;
+        }
+    }
+
+    /// 只保留 [x0, x1) x [y0, y1) 以内的部分，用于画框留白
+    pub fn clip(&mut self, x0: u32, y0: u32, x1: u32, y1: u32) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if x < x0 || x >= x1 || y < y0 || y >= y1 {
+                    self.inside[(y * self.width + x) as usize] = false;
+                }
+            }
+        }
+    }
+
+    /// 加上顶部开口上方的空间：在最上面几行有内部像素的列，其上方可以落下图标，其余均为容器壁
+    pub(crate) fn with_opening(&self) -> ShapeMask {
+        let mut rs = self.clone();
+        let Some((_, top, _, _)) = self.bounds() else {
+            return rs;
+        };
+        // 抗锯齿的边缘可能低一两个像素
+        let rows = top..(top + 3).min(self.height);
+        for x in 0..self.width {
+            if rows.clone().any(|y| self.contains(x, y)) {
+                for y in 0..top {
+                    rs.inside[(y * self.width + x) as usize] = true;
+                }
+            }
+        }
+        rs
+    }
+
+    /// 形状的外接矩形 (min_x, min_y, max_x, max_y)，不含 max
+    pub fn bounds(&self) -> Option<(u32, u32, u32, u32)> {
+        let mut rs: Option<(u32, u32, u32, u32)> = None;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.contains(x, y) {
+                    let b = rs.get_or_insert((x, y, x + 1, y + 1));
+                    *b = (b.0.min(x), b.1.min(y), b.2.max(x + 1), b.3.max(y + 1));
+                }
+            }
+        }
+        rs
+    }
+
+    pub(crate) fn contains(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.inside[(y * self.width + x) as usize]
+    }
+}
+
+fn render_target(width: u32, height: u32) -> Result<Pixmap> {
+    Pixmap::new(width, height).ok_or(anyhow!("incorrect size"))
+}
+
+/// 把 SVG 的可见内容等比缩放并居中到画布的变换
+pub(crate) fn fit_tree(tree: &usvg::Tree, width: u32, height: u32) -> Result<Transform> {
+    let Some(bbox) = tree.root().abs_stroke_bounding_box().to_non_zero_rect() else {
+        bail!("shape is empty");
+    };
+    Ok(fit(bbox.width(), bbox.height(), bbox.x(), bbox.y(), width, height))
+}
+
+// 把 (x, y, w, h) 的内容等比缩放并居中到画布
+fn fit(w: f32, h: f32, x: f32, y: f32, width: u32, height: u32) -> Transform {
+    let scale = (width as f32 / w).min(height as f32 / h);
+    let tx = (width as f32 - w * scale) / 2.0 - x * scale;
+    let ty = (height as f32 - h * scale) / 2.0 - y * scale;
+    Transform::from_row(scale, 0.0, 0.0, scale, tx, ty)
+}
+
+// 多边形在每一行的水平范围，凹多边形取最左和最右的交点
+pub(crate) fn spans(polygon: &Polygon, height: u32) -> Vec<Option<(u32, u32)>> {
+    (0..height)
+        .map(|row| {
+            let y = row as f32 + 0.5;
+            let mut range: Option<(f32, f32)> = None;
+            for i in 0..polygon.len() {
+                let (x1, y1) = (polygon[i].0 as f32, polygon[i].1 as f32);
+                let j = (i + 1) % polygon.len();
+                let (x2, y2) = (polygon[j].0 as f32, polygon[j].1 as f32);
+                if (y1 > y) == (y2 > y) {
+                    continue;
+                }
+                let x = x1 + (y - y1) * (x2 - x1) / (y2 - y1);
+                range = Some(range.map_or((x, x), |(a, b)| (a.min(x), b.max(x))));
+            }
+            range.map(|(a, b)| (a.max(0.0) as u32, b.ceil() as u32))
+        })
+        .collect()
+}
+
+/// 每行被占用像素数的前缀和，判断一段是否空闲是 O(1)
+pub(crate) struct Occupancy {
+    width: u32,
+    height: u32,
+    blocked: Vec<bool>,
+    prefix: Vec<u32>,
+}
+
+impl Occupancy {
+    pub(crate) fn new(mask: &ShapeMask) -> Occupancy {
+        let mut rs = Occupancy {
+            width: mask.width,
+            height: mask.height,
+            blocked: mask.inside.iter().map(|i| !i).collect(),
+            prefix: vec![0; ((mask.width + 1) * mask.height) as usize],
+        };
+        for y in 0..rs.height {
+            rs.update_row(y);
+        }
+        rs
+    }
+
+    fn update_row(&mut self, y: u32) {
+        let w = self.width as usize;
+        let row = &self.blocked[y as usize * w..(y as usize + 1) * w];
+        let prefix = &mut self.prefix[y as usize * (w + 1)..(y as usize + 1) * (w + 1)];
+        for x in 0..w {
+            prefix[x + 1] = prefix[x] + row[x] as u32;
+        }
+    }
+
+    // [x0, x1) 是否全部空闲
+    fn free(&self, y: u32, x0: u32, x1: u32) -> bool {
+        if y >= self.height || x1 > self.width {
+            return false;
+        }
+        let base = (y * (self.width + 1)) as usize;
+        self.prefix[base + x1 as usize] == self.prefix[base + x0 as usize]
+    }
+
+    pub(crate) fn fits(&self, spans: &[Option<(u32, u32)>], (x, y): (u32, u32)) -> bool {
+        spans.iter().enumerate().all(|(row, span)| match span {
+            Some((a, b)) => self.free(y + row as u32, x + a, x + (*b).max(a + 1)),
+            None => true,
+        })
+    }
+
+    // 标记为占用，四周留出间隙
+    fn mark(&mut self, spans: &[Option<(u32, u32)>], (x, y): (u32, u32), gap: u32) {
+        let rows = spans.len() as u32;
+        for row in 0..rows + gap * 2 {
+            let Some(ty) = (y + row).checked_sub(gap).filter(|&ty| ty < self.height) else {
+                continue;
+            };
+            // 上下间隙取最近一行的范围
+            let source = row.saturating_sub(gap).min(rows.saturating_sub(1));
+            let Some(Some((a, b))) = spans.get(source as usize) else {
+                continue;
+            };
+            let x0 = (x + a).saturating_sub(gap);
+            let x1 = (x + b + gap).min(self.width);
+            let w = self.width as usize;
+            for tx in x0..x1 {
+                self.blocked[ty as usize * w + tx as usize] = true;
+            }
+            self.update_row(ty);
+        }
+    }
+
+    fn free_area(&self) -> usize {
+        self.blocked.iter().filter(|b| !**b).count()
+    }
+}
+
+/// 把图标填进形状，`obstacles` 为画框的圆角和保留区域。
+/// `repeat` 为 true 时第一轮之后循环使用图标，用越来越小的尺寸填满剩余空间。
+/// 返回 (图标下标, 位置)，位置中的 icon 带有实际缩放
+pub fn fill_shape(
+    icons: &[Icon],
+    mask: &ShapeMask,
+    obstacles: &[(Polygon, (u32, u32))],
+    step: u32,
+    repeat: bool,
+) -> Vec<(usize, Position)> {
+    let mut occupancy = Occupancy::new(mask);
+    for (polygon, offset) in obstacles {
+        let height = polygon.iter().map(|p| p.1).max().unwrap_or(0);
+        occupancy.mark(&spans(polygon, height), *offset, 0);
+    }
+    let area = occupancy.free_area() as f32;
+    let total: f32 = icons
+        .iter()
+        .map(|icon| {
+            let (w, h) = icon.scaled_size();
+            (w * h) as f32
+        })
+        .sum();
+    if area <= 0.0 || total <= 0.0 {
+        return Vec::new();
+    }
+    let base = (DENSITY * area / total).sqrt();
+
+    // 候选中心点按到形状重心的距离排序，先填中间
+    let step = step.max(1);
+    let mut candidates: Vec<(u32, u32)> = (0..mask.height)
+        .step_by(step as usize)
+        .flat_map(|y| (0..mask.width).step_by(step as usize).map(move |x| (x, y)))
+        .filter(|&(x, y)| mask.contains(x, y))
+        .collect();
+    let count = candidates.len().max(1) as f32;
+    let cx = candidates.iter().map(|p| p.0 as f32).sum::<f32>() / count;
+    let cy = candidates.iter().map(|p| p.1 as f32).sum::<f32>() / count;
+    let distance = |&(x, y): &(u32, u32)| (x as f32 - cx).powi(2) + (y as f32 - cy).powi(2);
+    candidates.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+
+    let mut placed = Vec::new();
+    let mut place = |index: usize, factor: f32, occupancy: &mut Occupancy| -> bool {
+        let mut icon = icons[index].clone();
+        icon.scale *= factor;
+        let (w, h) = icon.scaled_size();
+        if (w.min(h) as f32) < MIN_SIDE {
+            return false;
+        }
+        let spans = spans(&icon.scaled_polygon(), h);
+        let found = candidates
+            .iter()
+            .filter(|&&(x, y)| x >= w / 2 && y >= h / 2)
+            .map(|&(x, y)| (x - w / 2, y - h / 2))
+            .filter(|&(x, y)| occupancy.free(y + h / 2, x + w / 2, x + w / 2 + 1))
+            .find(|&offset| occupancy.fits(&spans, offset));
+        let Some((x, y)) = found else {
+            return false;
+        };
+        occupancy.mark(&spans, (x, y), GAP);
+        placed.push((index, Position { x, y, icon }));
+        true
+    };
+
+    let mut smallest = base;
+    let mut count = 0;
+    for index in 0..icons.len() {
+        let mut factor = base;
+        while factor >= base * 0.3 {
+            if place(index, factor, &mut occupancy) {
+                smallest = smallest.min(factor);
+                count += 1;
+                break;
+            }
+            factor *= SHRINK;
+        }
+    }
+    if repeat && !icons.is_empty() {
+        let mut factor = smallest * SHRINK;
+        while count < MAX_ITEMS {
+            let mut any = false;
+            for index in 0..icons.len() {
+                if count >= MAX_ITEMS {
+                    break;
+                }
+                if place(index, factor, &mut occupancy) {
+                    any = true;
+                    count += 1;
+                }
+            }
+            if !any {
+                factor *= SHRINK;
+                // 最大的图标也小于最小尺寸时结束
+                let largest = icons
+                    .iter()
+                    .map(|icon| {
+                        let (w, h) = icon.scaled_size();
+                        w.min(h) as f32 * factor
+                    })
+                    .fold(0.0, f32::max);
+                if largest < MIN_SIDE {
+                    break;
+                }
+            }
+        }
+    }
+    placed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::align::polygons_overlap;
+
+    fn square(size: u32) -> Icon {
+        Icon::new(size, size, vec![(0, 0), (size, 0), (size, size), (0, size)], 1.0)
+    }
+
+    #[test]
+    fn parse_shapes() {
+        // 100 x 100 的正方形等比缩放后居中
+        let mask = ShapeMask::from_path("M0 0 L100 0 L100 100 L0 100 Z", 200, 100).unwrap();
+        assert_eq!(mask.bounds(), Some((50, 0, 150, 100)));
+        let mut clipped = mask.clone();
+        clipped.clip(0, 10, 100, 100);
+        assert_eq!(clipped.bounds(), Some((50, 10, 100, 100)));
+
+        assert!(ShapeMask::from_path(r#"M0 0"/><script/>"#, 100, 100).is_err());
+        assert!(ShapeMask::from_path("M0 0", 100, 100).is_err());
+        assert!(ShapeMask::builtin("triangle", 100, 100).is_none());
+        for name in ShapeMask::builtin_names() {
+            let mask = ShapeMask::builtin(name, 100, 100).unwrap();
+            let (x0, y0, x1, y1) = mask.bounds().unwrap();
+            // 等比缩放，至少一边填满画布
+            assert!((x1 - x0).max(y1 - y0) >= 98, "{} is too small", name);
+            // 形状中心在内部，角落在外部
+            assert!(mask.contains(50, 50) && !mask.contains(0, 0), "{}", name);
+        }
+    }
+
+    #[test]
+    fn icons_stay_inside_mask() {
+        let mask = ShapeMask::builtin("diamond", 200, 200).unwrap();
+        let icons = vec![square(40), square(30), square(30), square(20)];
+        let placed = fill_shape(&icons, &mask, &[], 4, false);
+        assert_eq!(placed.len(), icons.len());
+        for (_, pos) in &placed {
+            let (w, h) = pos.icon.scaled_size();
+            for y in pos.y..pos.y + h {
+                for x in pos.x..pos.x + w {
+                    assert!(mask.contains(x, y), "({}, {}) outside the diamond", x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn icons_do_not_overlap() {
+        let mask = ShapeMask::builtin("circle", 200, 200).unwrap();
+        // 右半边是保留区域
+        let obstacle = (vec![(0, 0), (100, 0), (100, 200), (0, 200)], (100, 0));
+        let placed = fill_shape(&[square(30), square(20)], &mask, std::slice::from_ref(&obstacle), 4, true);
+        assert!(placed.len() > 2);
+        for (n, (_, a)) in placed.iter().enumerate() {
+            let pa = a.icon.scaled_polygon();
+            assert!(!polygons_overlap(&pa, &obstacle.0, (a.x, a.y), obstacle.1));
+            for (_, b) in &placed[n + 1..] {
+                assert!(!polygons_overlap(&pa, &b.icon.scaled_polygon(), (a.x, a.y), (b.x, b.y)));
+            }
+        }
+    }
+}
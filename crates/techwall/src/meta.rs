@@ -0,0 +1,143 @@
+use anyhow::{anyhow, bail, Result};
+use resvg::tiny_skia::{FilterQuality, Pixmap, PixmapPaint, Transform};
+use serde::{Deserialize, Serialize};
+use usvg::Size;
+
+use crate::align::Icon;
+use crate::catalog::Catalog;
+use crate::png::{convert_svg_to_png, get_scale, is_monochrome, load};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Point {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl From<(u32, u32)> for Point {
+    fn from((x, y): (u32, u32)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl From<Point> for (u32, u32) {
+    fn from(p: Point) -> Self {
+        (p.x, p.y)
+    }
+}
+
+/// 单个图标的栅格尺寸与轮廓，由 crawler 生成
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Meta {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub scale: f32,
+    pub polygon: Vec<Point>,
+    /// 是否为单色图标，旧的 meta 中没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mono: Option<bool>,
+    /// 导入的矢量图标清理后的 SVG 文件名，与 PNG 在同一目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub svg: Option<String>,
+}
+
+impl Meta {
+    /// 栅格化 SVG 并提取轮廓，返回 meta 和 PNG 内容
+    pub fn from_svg(name: &str, data: Vec<u8>) -> Result<(Meta, Vec<u8>)> {
+        let (pix, scale) = convert_svg_to_png(data)?;
+        Meta::from_pixmap(name, pix, scale)
+    }
+
+    /// 把 PNG 缩放到与 SVG 栅格相同的面积并提取轮廓
+    pub fn from_png(name: &str, data: &[u8]) -> Result<(Meta, Vec<u8>)> {
+        let source = Pixmap::decode_png(data)?;
+        let size = Size::from_wh(source.width() as f32, source.height() as f32).ok_or(anyhow!("incorrect size"))?;
+        let (scale, _) = get_scale(size);
+        let width = ((source.width() as f32 * scale).round() as u32).max(1);
+        let height = ((source.height() as f32 * scale).round() as u32).max(1);
+        let mut pix = Pixmap::new(width, height).ok_or(anyhow!("incorrect size"))?;
+        let paint = PixmapPaint {
+            quality: FilterQuality::Bicubic,
+            ..PixmapPaint::default()
+        };
+        pix.draw_pixmap(0, 0, source.as_ref(), &paint, Transform::from_scale(scale, scale), None);
+        Meta::from_pixmap(name, pix, scale)
+    }
+
+    fn from_pixmap(name: &str, pix: Pixmap, scale: f32) -> Result<(Meta, Vec<u8>)> {
+        // 轮廓提取需要至少 2x2 像素；小于 10 像素的图标步长为 1，否则 `load` 中 step_by(0) 会 panic
+        if pix.width() < 2 || pix.height() < 2 {
+            bail!("icon is too small: {}x{}", pix.width(), pix.height());
+        }
+        let w = (pix.width() / 10).max(1);
+        let h = (pix.height() / 10).max(1);
+        let content = pix.encode_png()?;
+        let polygon = load(&content, (w as usize, h as usize))?;
+        let meta = Meta {
+            name: name.to_string(),
+            width: pix.width(),
+            height: pix.height(),
+            scale,
+            polygon: polygon.into_iter().map(Point::from).collect(),
+            mono: Some(is_monochrome(pix.data())),
+            svg: None,
+        };
+        Ok((meta, content))
+    }
+
+    /// 转成布局用的图标，`scale` 为摆放时的缩放
+    pub fn icon(&self, scale: f32) -> Icon {
+        let polygon = self.polygon.iter().map(|p| (*p).into()).collect();
+        Icon::new(self.width, self.height, polygon, scale)
+    }
+}
+
+/// `resource/meta` 文件的内容
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct MetaData {
+    pub items: Vec<Meta>,
+    #[serde(default)]
+    pub catalog: Catalog,
+}
+
+impl MetaData {
+    pub fn find(&self, name: &str) -> Option<&Meta> {
+        self.items.iter().find(|m| m.name == name)
+    }
+
+    /// 添加或替换同名图标
+    pub fn insert(&mut self, meta: Meta) {
+        match self.items.iter_mut().find(|m| m.name == meta.name) {
+            Some(item) => *item = meta,
+            None => self.items.push(meta),
+        }
+    }
+
+    /// 合并用户目录，同名的图标和技术以用户的为准
+    pub fn merge(&mut self, user: MetaData) {
+        for meta in user.items {
+            self.insert(meta);
+        }
+        for entry in user.catalog.entries {
+            self.catalog.insert(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiny_png_has_polygon() {
+        for size in [2, 4, 9] {
+            let mut pix = Pixmap::new(size, size).unwrap();
+            pix.fill(resvg::tiny_skia::Color::BLACK);
+            let (meta, _) = Meta::from_png("tiny", &pix.encode_png().unwrap()).unwrap();
+            assert_eq!((meta.width, meta.height), (size, size));
+            assert!(!meta.polygon.is_empty());
+        }
+        let pix = Pixmap::new(1, 1).unwrap();
+        assert!(Meta::from_png("dot", &pix.encode_png().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+//! 排布质量指标：覆盖率、最大空白矩形、间距分布、左右和上下的平衡、相邻图标颜色相近的比例。
+//! 在 4 像素的网格上统计，优化时可以反复计算。保留区域和圆角所在的格子不算空白，也不计入覆盖率的分母
+use resvg::tiny_skia::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::align::{bounds, Polygon, Position};
+use crate::frame::Region;
+
+// 网格格子的边长
+const CELL: u32 = 4;
+// 外接矩形之间的距离不超过它时算相邻
+const ADJACENT: f32 = 24.0;
+// 归一化的 RGB 距离小于它时算颜色相近
+const SIMILAR: f32 = 0.2;
+
+/// 每个图标到最近的图标的距离（外接矩形之间）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Gaps {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// 标准差，越小间距越均匀
+    pub deviation: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Metrics {
+    /// 图标覆盖的面积占排布区域的比例
+    pub coverage: f32,
+    /// 最大的空白矩形
+    pub largest_empty: Region,
+    pub gaps: Gaps,
+    /// 图标重心的水平偏移，-1 为全在左边，1 为全在右边
+    pub balance_x: f32,
+    /// 图标重心的垂直偏移，-1 为全在上边，1 为全在下边
+    pub balance_y: f32,
+    /// 相邻的图标中颜色相近的比例
+    pub same_color: f32,
+}
+
+// 网格上被图标覆盖的格子和被障碍物占据的格子
+struct Grid {
+    cols: u32,
+    rows: u32,
+    covered: Vec<bool>,
+    blocked: Vec<bool>,
+}
+
+impl Grid {
+    fn new(items: &[Position], area: (u32, u32, u32, u32), obstacles: &[(Polygon, (u32, u32))]) -> Grid {
+        let (x0, y0, x1, y1) = area;
+        let cols = (x1 - x0).div_ceil(CELL);
+        let rows = (y1 - y0).div_ceil(CELL);
+        let mut grid = Grid {
+            cols,
+            rows,
+            covered: vec![false; (cols * rows) as usize],
+            blocked: vec![false; (cols * rows) as usize],
+        };
+        for item in items {
+            let polygon = item.icon.scaled_polygon();
+            for cell in grid.cells(&polygon, (item.x, item.y), area) {
+                grid.covered[cell] = true;
+            }
+        }
+        for (polygon, offset) in obstacles {
+            for cell in grid.cells(polygon, *offset, area) {
+                grid.blocked[cell] = true;
+            }
+        }
+        grid
+    }
+
+    // 中心落在多边形内的格子
+    fn cells(&self, polygon: &Polygon, offset: (u32, u32), area: (u32, u32, u32, u32)) -> Vec<usize> {
+        let (x0, y0, _, y1) = area;
+        let mut rs = Vec::new();
+        if polygon.is_empty() {
+            return rs;
+        }
+        let (_, top, _, bottom) = bounds(polygon, offset);
+        if bottom < y0 || top >= y1 {
+            return rs;
+        }
+        let first = top.saturating_sub(y0) / CELL;
+        let last = ((bottom - y0) / CELL + 1).min(self.rows);
+        for row in first..last {
+            // 取格子中心所在的扫描线
+            let y = (y0 + row * CELL) as f32 + CELL as f32 / 2.0;
+            let mut range: Option<(f32, f32)> = None;
+            for i in 0..polygon.len() {
+                let j = (i + 1) % polygon.len();
+                let (ax, ay) = ((polygon[i].0 + offset.0) as f32, (polygon[i].1 + offset.1) as f32);
+                let (bx, by) = ((polygon[j].0 + offset.0) as f32, (polygon[j].1 + offset.1) as f32);
+                if (ay > y) == (by > y) {
+                    continue;
+                }
+                let x = ax + (y - ay) * (bx - ax) / (by - ay);
+                range = Some(range.map_or((x, x), |(a, b)| (a.min(x), b.max(x))));
+            }
+            let Some((a, b)) = range else {
+                continue;
+            };
+            // 中心落在 [a, b] 之内的格子
+            let start = ((a - x0 as f32) / CELL as f32 - 0.5).ceil().max(0.0) as u32;
+            let end = (((b - x0 as f32) / CELL as f32 - 0.5).floor() + 1.0).clamp(0.0, self.cols as f32) as u32;
+            rs.extend((start..end).map(|col| (row * self.cols + col) as usize));
+        }
+        rs
+    }
+
+    // 最大的全空矩形 (左, 上, 宽, 高)，单位为格子，逐行用直方图求最大矩形
+    fn largest_empty(&self) -> (u32, u32, u32, u32) {
+        let mut heights = vec![0u32; self.cols as usize];
+        let mut best = (0, 0, 0, 0);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let h = &mut heights[col as usize];
+                let cell = (row * self.cols + col) as usize;
+                *h = if self.covered[cell] || self.blocked[cell] { 0 } else { *h + 1 };
+            }
+            // 单调栈，栈中为高度递增的列
+            let mut stack: Vec<u32> = Vec::new();
+            for col in 0..=self.cols {
+                let h = if col < self.cols { heights[col as usize] } else { 0 };
+                while let Some(&top) = stack.last() {
+                    if heights[top as usize] < h {
+                        break;
+                    }
+                    stack.pop();
+                    let height = heights[top as usize];
+                    let left = stack.last().map_or(0, |&l| l + 1);
+                    let width = col - left;
+                    if width * height > best.2 * best.3 {
+                        best = (left, row + 1 - height, width, height);
+                    }
+                }
+                stack.push(col);
+            }
+        }
+        best
+    }
+}
+
+// 外接矩形之间的距离，重叠时为 0
+fn distance(a: (u32, u32, u32, u32), b: (u32, u32, u32, u32)) -> f32 {
+    let dx = (a.0.max(b.0) as f32 - a.2.min(b.2) as f32).max(0.0);
+    let dy = (a.1.max(b.1) as f32 - a.3.min(b.3) as f32).max(0.0);
+    dx.hypot(dy)
+}
+
+fn similar(a: Color, b: Color) -> bool {
+    let d = (a.red() - b.red()).powi(2) + (a.green() - b.green()).powi(2) + (a.blue() - b.blue()).powi(2);
+    (d / 3.0).sqrt() < SIMILAR
+}
+
+/// 统计 `area` (min_x, min_y, max_x, max_y) 以内的排布，`colors` 为每个图标的主色，
+/// 没有颜色的图标不参与颜色的统计。`obstacles` 为画框的圆角和保留区域
+pub fn measure(
+    items: &[Position],
+    colors: &[Option<Color>],
+    area: (u32, u32, u32, u32),
+    obstacles: &[(Polygon, (u32, u32))],
+) -> Metrics {
+    let (x0, y0, x1, y1) = area;
+    let grid = Grid::new(items, area, obstacles);
+    let total = grid.blocked.iter().filter(|&&b| !b).count();
+    let mut count = 0usize;
+    let (mut sx, mut sy) = (0.0f32, 0.0f32);
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            let cell = (row * grid.cols + col) as usize;
+            if grid.covered[cell] && !grid.blocked[cell] {
+                count += 1;
+                sx += col as f32 + 0.5;
+                sy += row as f32 + 0.5;
+            }
+        }
+    }
+    let (balance_x, balance_y) = if count > 0 {
+        (
+            (sx / count as f32 / grid.cols as f32 * 2.0 - 1.0).clamp(-1.0, 1.0),
+            (sy / count as f32 / grid.rows as f32 * 2.0 - 1.0).clamp(-1.0, 1.0),
+        )
+    } else {
+        (0.0, 0.0)
+    };
+
+    let (left, top, width, height) = grid.largest_empty();
+    let largest_empty = Region {
+        x: x0 + left * CELL,
+        y: y0 + top * CELL,
+        width: (width * CELL).min(x1 - x0 - left * CELL),
+        height: (height * CELL).min(y1 - y0 - top * CELL),
+    };
+
+    let boxes: Vec<(u32, u32, u32, u32)> = items
+        .iter()
+        .map(|item| bounds(&item.icon.scaled_polygon(), (item.x, item.y)))
+        .collect();
+    let nearest: Vec<f32> = (0..boxes.len())
+        .filter_map(|i| {
+            (0..boxes.len())
+                .filter(|&j| j != i)
+                .map(|j| distance(boxes[i], boxes[j]))
+                .min_by(f32::total_cmp)
+        })
+        .collect();
+    let gaps = if nearest.is_empty() {
+        Gaps::default()
+    } else {
+        let n = nearest.len() as f32;
+        let mean = nearest.iter().sum::<f32>() / n;
+        Gaps {
+            min: nearest.iter().copied().fold(f32::INFINITY, f32::min),
+            max: nearest.iter().copied().fold(0.0, f32::max),
+            mean,
+            deviation: (nearest.iter().map(|d| (d - mean).powi(2)).sum::<f32>() / n).sqrt(),
+        }
+    };
+
+    let (mut adjacent, mut same) = (0usize, 0usize);
+    for i in 0..boxes.len() {
+        for j in i + 1..boxes.len() {
+            let (Some(Some(a)), Some(Some(b))) = (colors.get(i), colors.get(j)) else {
+                continue;
+            };
+            if distance(boxes[i], boxes[j]) <= ADJACENT {
+                adjacent += 1;
+                same += similar(*a, *b) as usize;
+            }
+        }
+    }
+
+    Metrics {
+        coverage: if total > 0 { count as f32 / total as f32 } else { 0.0 },
+        largest_empty,
+        gaps,
+        balance_x,
+        balance_y,
+        same_color: if adjacent > 0 { same as f32 / adjacent as f32 } else { 0.0 },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::align::Icon;
+
+    fn square(x: u32, y: u32, size: u32) -> Position {
+        Position {
+            x,
+            y,
+            icon: Icon::new(size, size, vec![(0, 0), (size, 0), (size, size), (0, size)], 1.0),
+        }
+    }
+
+    #[test]
+    fn largest_empty_rectangle() {
+        let empty = measure(&[], &[], (0, 0, 100, 60), &[]);
+        assert_eq!(empty.largest_empty, Region { x: 0, y: 0, width: 100, height: 60 });
+        assert_eq!(empty.coverage, 0.0);
+
+        // 左边一列 40 宽的图标，剩下右边 60 x 60
+        let items = [square(0, 0, 40), square(0, 20, 40)];
+        let metrics = measure(&items, &[], (0, 0, 100, 60), &[]);
+        assert_eq!(metrics.largest_empty, Region { x: 40, y: 0, width: 60, height: 60 });
+        assert!(metrics.balance_x < 0.0);
+
+        // 中间一个图标，左右两侧 40 x 60 比上下的整条大，面积相同时取先找到的
+        let items = [square(40, 20, 20)];
+        let metrics = measure(&items, &[], (0, 0, 100, 60), &[]);
+        assert_eq!(metrics.largest_empty, Region { x: 0, y: 0, width: 40, height: 60 });
+    }
+
+    #[test]
+    fn keep_out_is_not_empty_space() {
+        // 右边 60 像素是保留区域，剩下的左边一半被图标覆盖
+        let keep_out = vec![(vec![(0, 0), (60, 0), (60, 60), (0, 60)], (40, 0))];
+        let items = [square(0, 0, 40), square(0, 20, 40)];
+        let metrics = measure(&items, &[], (0, 0, 100, 60), &keep_out);
+        assert_eq!(metrics.coverage, 1.0);
+        assert_eq!(metrics.largest_empty.width * metrics.largest_empty.height, 0);
+    }
+
+    #[test]
+    fn gaps_between_neighbours() {
+        let items = [square(0, 0, 10), square(20, 0, 10), square(50, 0, 10)];
+        let gaps = measure(&items, &[], (0, 0, 100, 20), &[]).gaps;
+        // 最近距离分别为 10、10、20
+        assert_eq!((gaps.min, gaps.max), (10.0, 20.0));
+        assert!((gaps.mean - 40.0 / 3.0).abs() < 0.01);
+    }
+}
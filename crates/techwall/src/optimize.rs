@@ -0,0 +1,290 @@
+//! 排布优化：从任意排布（包括物理堆积的结果）出发做模拟退火，每一步随机移动一个图标、把它移到任意位置、
+//! 交换两个图标的位置或者绕中心旋转一个图标，始终保持不重叠，提高选定的指标。
+//! 图标的缩放不变，所以覆盖率不作为目标
+use resvg::tiny_skia::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::align::{polygons_overlap, Polygon, Position};
+use crate::metrics::{measure, Metrics};
+use crate::rng::Rng;
+
+// 初始温度，单位与目标分数相同
+const TEMPERATURE: f32 = 0.05;
+// 交换两个图标的概率
+const SWAP: f32 = 0.3;
+// 把图标移到区域内任意位置的概率，可以跳出局部的空隙
+const JUMP: f32 = 0.1;
+// 开启旋转时旋转一个图标的概率
+const ROTATE: f32 = 0.2;
+// 旋转角度的范围，正负各这么多度
+const MAX_ANGLE: f32 = 30.0;
+
+/// 优化的目标，分数越高越好
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Objective {
+    /// 最大空白矩形尽量小，图标分布均匀
+    Empty,
+    /// 重心尽量在中间
+    Balance,
+    /// 相邻图标的间距尽量一致
+    Gaps,
+    /// 颜色相近的图标尽量不相邻
+    Color,
+    /// 以上各项之和
+    #[default]
+    All,
+}
+
+impl Objective {
+    pub fn parse(name: &str) -> Option<Objective> {
+        match name {
+            "empty" => Some(Objective::Empty),
+            "balance" => Some(Objective::Balance),
+            "gaps" => Some(Objective::Gaps),
+            "color" => Some(Objective::Color),
+            "all" => Some(Objective::All),
+            _ => None,
+        }
+    }
+
+    /// 指标的分数，`area` 为排布区域的面积
+    pub fn score(self, metrics: &Metrics, area: f32) -> f32 {
+        let empty = -(metrics.largest_empty.width as f32 * metrics.largest_empty.height as f32) / area.max(1.0);
+        let balance = -(metrics.balance_x.abs() + metrics.balance_y.abs());
+        let gaps = -metrics.gaps.deviation / (metrics.gaps.mean + 1.0);
+        let color = -metrics.same_color;
+        match self {
+            Objective::Empty => empty,
+            Objective::Balance => balance,
+            Objective::Gaps => gaps,
+            Objective::Color => color,
+            Objective::All => empty + balance + gaps + color,
+        }
+    }
+}
+
+fn default_iterations() -> u32 {
+    2000
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Optimize {
+    #[serde(default)]
+    pub objective: Objective,
+    /// 尝试的次数
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+    /// 同时旋转图标，角度不超过正负 30 度
+    #[serde(default)]
+    pub rotate: bool,
+}
+
+impl Default for Optimize {
+    fn default() -> Self {
+        Optimize {
+            objective: Objective::All,
+            iterations: default_iterations(),
+            rotate: false,
+        }
+    }
+}
+
+// 图标放在 `at` 是否在区域内且不与障碍物和其他图标重叠，`skip` 中的图标不检查
+fn fits(
+    items: &[Position],
+    index: usize,
+    at: (u32, u32),
+    skip: &[usize],
+    area: (u32, u32, u32, u32),
+    obstacles: &[(Polygon, (u32, u32))],
+) -> bool {
+    let (x0, y0, x1, y1) = area;
+    let (w, h) = items[index].icon.scaled_size();
+    if at.0 < x0 || at.1 < y0 || at.0 + w > x1 || at.1 + h > y1 {
+        return false;
+    }
+    let polygon = items[index].icon.scaled_polygon();
+    !obstacles.iter().any(|(p, o)| polygons_overlap(&polygon, p, at, *o))
+        && !items
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != index && !skip.contains(j))
+            .any(|(_, other)| polygons_overlap(&polygon, &other.icon.scaled_polygon(), at, (other.x, other.y)))
+}
+
+impl Optimize {
+    /// 在 `area` 内优化 `items` 的位置，`colors` 为每个图标的主色，`obstacles` 为画框的圆角和保留区域。
+    /// 返回优化前后的分数
+    pub fn run(
+        &self,
+        items: &mut [Position],
+        colors: &[Option<Color>],
+        area: (u32, u32, u32, u32),
+        obstacles: &[(Polygon, (u32, u32))],
+        seed: u64,
+    ) -> (f32, f32) {
+        let (x0, y0, x1, y1) = area;
+        let size = ((x1 - x0) * (y1 - y0)) as f32;
+        let score = |items: &[Position]| self.objective.score(&measure(items, colors, area, obstacles), size);
+        let mut current = score(items);
+        let start = current;
+        if items.is_empty() {
+            return (start, current);
+        }
+        let mut best = (current, items.to_vec());
+        let mut rng = Rng::new(seed);
+        let reach = ((x1 - x0).max(y1 - y0) as f32 / 4.0).max(1.0);
+        let iterations = self.iterations.max(1);
+
+        for k in 0..iterations {
+            // 温度和移动范围随进度线性减小
+            let progress = k as f32 / iterations as f32;
+            let temperature = TEMPERATURE * (1.0 - progress);
+            let radius = (reach * (1.0 - progress)).max(2.0);
+            let i = rng.below(items.len() as u32) as usize;
+            let old: Vec<(usize, (u32, u32), f32)>;
+            if self.rotate && rng.unit() < ROTATE {
+                // 绕中心旋转，转动范围随进度减小
+                let (w, h) = items[i].icon.scaled_size();
+                let (cx, cy) = (items[i].x as f32 + w as f32 / 2.0, items[i].y as f32 + h as f32 / 2.0);
+                let before = items[i].icon.angle;
+                let spread = (MAX_ANGLE * 2.0 * (1.0 - progress)).max(1.0);
+                items[i].icon.angle = (before + (rng.unit() * 2.0 - 1.0) * spread).clamp(-MAX_ANGLE, MAX_ANGLE);
+                let (w, h) = items[i].icon.scaled_size();
+                let at = (
+                    (cx - w as f32 / 2.0).max(0.0).round() as u32,
+                    (cy - h as f32 / 2.0).max(0.0).round() as u32,
+                );
+                if !fits(items, i, at, &[], area, obstacles) {
+                    items[i].icon.angle = before;
+                    continue;
+                }
+                old = vec![(i, (items[i].x, items[i].y), before)];
+                (items[i].x, items[i].y) = at;
+            } else if items.len() > 1 && rng.unit() < SWAP {
+                // 交换两个图标的中心
+                let j = (i + 1 + rng.below(items.len() as u32 - 1) as usize) % items.len();
+                let center = |p: &Position| {
+                    let (w, h) = p.icon.scaled_size();
+                    (p.x as f32 + w as f32 / 2.0, p.y as f32 + h as f32 / 2.0)
+                };
+                let corner = |p: &Position, (cx, cy): (f32, f32)| {
+                    let (w, h) = p.icon.scaled_size();
+                    ((cx - w as f32 / 2.0).max(0.0).round() as u32, (cy - h as f32 / 2.0).max(0.0).round() as u32)
+                };
+                let (a, b) = (corner(&items[i], center(&items[j])), corner(&items[j], center(&items[i])));
+                old = vec![
+                    (i, (items[i].x, items[i].y), items[i].icon.angle),
+                    (j, (items[j].x, items[j].y), items[j].icon.angle),
+                ];
+                if !fits(items, i, a, &[j], area, obstacles) || !fits(items, j, b, &[i], area, obstacles) {
+                    continue;
+                }
+                (items[i].x, items[i].y) = a;
+                (items[j].x, items[j].y) = b;
+                // 交换后两个图标之间也不能重叠
+                if !fits(items, i, a, &[], area, obstacles) {
+                    for &(index, at, _) in &old {
+                        (items[index].x, items[index].y) = at;
+                    }
+                    continue;
+                }
+            } else {
+                let at = if rng.unit() < JUMP {
+                    let (w, h) = items[i].icon.scaled_size();
+                    (
+                        x0 + rng.below((x1 - x0).saturating_sub(w) + 1),
+                        y0 + rng.below((y1 - y0).saturating_sub(h) + 1),
+                    )
+                } else {
+                    let dx = (rng.unit() * 2.0 - 1.0) * radius;
+                    let dy = (rng.unit() * 2.0 - 1.0) * radius;
+                    (
+                        (items[i].x as f32 + dx).max(0.0).round() as u32,
+                        (items[i].y as f32 + dy).max(0.0).round() as u32,
+                    )
+                };
+                if !fits(items, i, at, &[], area, obstacles) {
+                    continue;
+                }
+                old = vec![(i, (items[i].x, items[i].y), items[i].icon.angle)];
+                (items[i].x, items[i].y) = at;
+            }
+            let next = score(items);
+            let delta = next - current;
+            if delta >= 0.0 || (temperature > 0.0 && rng.unit() < (delta / temperature).exp()) {
+                current = next;
+                if current > best.0 {
+                    best = (current, items.to_vec());
+                }
+            } else {
+                for (index, at, angle) in old {
+                    (items[index].x, items[index].y) = at;
+                    items[index].icon.angle = angle;
+                }
+            }
+        }
+        items.clone_from_slice(&best.1);
+        (start, best.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::align::Icon;
+
+    #[test]
+    fn keeps_icons_apart() {
+        let square = vec![(0, 0), (30, 0), (30, 30), (0, 30)];
+        // 全部挤在左上角
+        let mut items: Vec<Position> = (0..6)
+            .map(|i| Position {
+                x: (i % 3) * 30,
+                y: (i / 3) * 30,
+                icon: Icon::new(30, 30, square.clone(), 1.0),
+            })
+            .collect();
+        let obstacles = vec![(square.clone(), (200, 100))];
+        let config = Optimize {
+            objective: Objective::Balance,
+            ..Default::default()
+        };
+        let (before, after) = config.run(&mut items, &[], (0, 0, 300, 200), &obstacles, 1);
+        assert!(after > before);
+        for (i, a) in items.iter().enumerate() {
+            assert!(fits(&items, i, (a.x, a.y), &[], (0, 0, 300, 200), &obstacles));
+        }
+    }
+
+    #[test]
+    fn rotated_icons_stay_apart() {
+        let bar = vec![(0, 0), (60, 0), (60, 12), (0, 12)];
+        let mut items: Vec<Position> = (0..8)
+            .map(|i| Position {
+                x: (i % 2) * 60,
+                y: (i / 2) * 12,
+                icon: Icon::new(60, 12, bar.clone(), 1.0),
+            })
+            .collect();
+        let config = Optimize {
+            objective: Objective::Empty,
+            rotate: true,
+            ..Default::default()
+        };
+        config.run(&mut items, &[], (0, 0, 200, 120), &[], 5);
+        assert!(items.iter().any(|p| p.icon.angle != 0.0));
+        for (i, a) in items.iter().enumerate() {
+            assert!(a.icon.angle.abs() <= MAX_ANGLE);
+            assert!(fits(&items, i, (a.x, a.y), &[], (0, 0, 200, 120), &[]));
+        }
+    }
+
+    #[test]
+    fn orbit_is_rejected() {
+        let mut banner = crate::banner::Banner::new(300, 200);
+        banner.arrange = crate::banner::Arrange::Orbit;
+        assert!(banner.optimize(&[], &Optimize::default()).is_err());
+    }
+}
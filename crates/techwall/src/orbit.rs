@@ -0,0 +1,331 @@
+//! 环绕排布：主要技术放大放在中间，其余图标在同心的椭圆轨道上，可以按权重或分组分配轨道。
+//! 轨道旋转得到动画的每一帧
+use std::f32::consts::{PI, TAU};
+
+use serde::{Deserialize, Serialize};
+
+use crate::align::{polygons_overlap, Icon, Polygon, Position};
+use crate::frame::Frame;
+
+// 碰撞时沿轨道微调的次数，每次为间隔的 1/8
+const NUDGE_STEPS: usize = 4;
+// 微调无效时缩小图标的次数
+const SHRINK_STEPS: usize = 4;
+const SHRINK: f32 = 0.9;
+// 自动计算时最多的轨道数
+const MAX_RINGS: usize = 5;
+
+/// 图标分配到轨道的方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RingBy {
+    /// 按排布顺序（有权重时为权重）从内到外，外圈放得多
+    #[default]
+    Weight,
+    /// 每个分组（例如目录中的分类）一条轨道
+    Group,
+}
+
+impl RingBy {
+    pub fn parse(name: &str) -> Option<RingBy> {
+        match name {
+            "weight" => Some(RingBy::Weight),
+            "group" | "category" => Some(RingBy::Group),
+            _ => None,
+        }
+    }
+}
+
+fn default_center() -> f32 {
+    0.4
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Orbit {
+    /// 轨道数，0 为自动
+    #[serde(default)]
+    pub rings: u32,
+    #[serde(default)]
+    pub by: RingBy,
+    /// 中心图标的直径，画框短边的比例
+    #[serde(default = "default_center")]
+    pub center: f32,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Orbit {
+            rings: 0,
+            by: RingBy::Weight,
+            center: default_center(),
+        }
+    }
+}
+
+/// 椭圆轨道，动画中每一圈转一周，相邻轨道方向相反
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ring {
+    pub rx: f32,
+    pub ry: f32,
+    pub direction: f32,
+}
+
+/// 排布结果，可以按相位重新计算每个图标的位置
+#[derive(Clone, Debug, Default)]
+pub struct Orbits {
+    pub center: (f32, f32),
+    pub rings: Vec<Ring>,
+    /// 每个图标所在的轨道和初始角度，中心图标为 None
+    pub slots: Vec<Option<(usize, f32)>>,
+}
+
+impl Orbits {
+    /// `phase` 在 [0, 1) 之间时图标左上角的位置，`size` 为缩放后的尺寸
+    pub fn position(&self, index: usize, size: (u32, u32), phase: f32) -> (u32, u32) {
+        let (cx, cy) = match self.slots.get(index).copied().flatten() {
+            None => self.center,
+            Some((ring, angle)) => {
+                let r = self.rings[ring];
+                let a = angle + r.direction * TAU * phase;
+                (self.center.0 + r.rx * a.cos(), self.center.1 + r.ry * a.sin())
+            }
+        };
+        (
+            (cx - size.0 as f32 / 2.0).max(0.0).round() as u32,
+            (cy - size.1 as f32 / 2.0).max(0.0).round() as u32,
+        )
+    }
+}
+
+// 按周长的比例分配每条轨道的数量，至少一个
+fn split(count: usize, perimeters: &[f32]) -> Vec<usize> {
+    let total: f32 = perimeters.iter().sum();
+    let mut counts: Vec<usize> = perimeters
+        .iter()
+        .map(|p| ((count as f32 * p / total).floor() as usize).max(1))
+        .collect();
+    // 多出或不足的从外圈开始调整
+    let mut k = counts.len();
+    while counts.iter().sum::<usize>() > count && k > 0 {
+        k -= 1;
+        counts[k] = counts[k].saturating_sub(1).max(1);
+    }
+    let mut k = counts.len();
+    while counts.iter().sum::<usize>() < count {
+        k = if k == 0 { counts.len() - 1 } else { k - 1 };
+        counts[k] += 1;
+    }
+    counts
+}
+
+// Ramanujan 的椭圆周长近似
+fn perimeter(rx: f32, ry: f32) -> f32 {
+    PI * (3.0 * (rx + ry) - ((3.0 * rx + ry) * (rx + 3.0 * ry)).sqrt())
+}
+
+// 轨道转一周时图标是否始终不碰到障碍物，按约 2 像素的弧长取样
+fn sweep_clear(orbits: &Orbits, index: usize, polygon: &Polygon, size: (u32, u32), obstacles: &[(Polygon, (u32, u32))]) -> bool {
+    let Some((ring, _)) = orbits.slots[index] else {
+        return true;
+    };
+    let r = orbits.rings[ring];
+    let samples = (perimeter(r.rx, r.ry) / 2.0).ceil().max(1.0) as usize;
+    (0..samples).all(|s| {
+        let offset = orbits.position(index, size, s as f32 / samples as f32);
+        !obstacles.iter().any(|(p, o)| polygons_overlap(polygon, p, offset, *o))
+    })
+}
+
+/// 第一个图标放在中间，其余图标分配到轨道上，会修改图标的缩放。
+/// 轨道上的图标转动时会扫过整条轨道，扫过保留区域或圆角的图标缩小后仍然碰到时放不下。
+/// `groups` 与 `items` 一一对应，仅在按分组排布时使用。返回轨道和放不下的图标下标
+pub fn orbit(
+    items: &mut [Position],
+    groups: &[Option<&str>],
+    width: u32,
+    height: u32,
+    frame: &Frame,
+    config: &Orbit,
+) -> (Orbits, Vec<usize>) {
+    let (x0, y0, x1, y1) = frame.area(width, height);
+    let (a, b) = ((x1 - x0) as f32 / 2.0, (y1 - y0) as f32 / 2.0);
+    let mut orbits = Orbits {
+        center: (x0 as f32 + a, y0 as f32 + b),
+        rings: Vec::new(),
+        slots: vec![None; items.len()],
+    };
+    let mut overflow = Vec::new();
+    let Some((first, rest)) = items.split_first_mut() else {
+        return (orbits, overflow);
+    };
+    let fit = |icon: &Icon, diameter: f32| {
+        let (w, h) = (icon.width.max(1) as f32, icon.height.max(1) as f32);
+        diameter / (w * w + h * h).sqrt()
+    };
+    let r0 = a.min(b) * config.center.clamp(0.05, 1.0);
+    first.icon.scale = fit(&first.icon, r0 * 2.0);
+    let obstacles = frame.obstacles(width, height);
+    let mut placed: Vec<(Polygon, (u32, u32))> = Vec::new();
+    // 中心图标碰到障碍物时缩小，仍然碰到时不显示
+    for _ in 0..=SHRINK_STEPS {
+        let polygon = first.icon.scaled_polygon();
+        (first.x, first.y) = orbits.position(0, first.icon.scaled_size(), 0.0);
+        if !obstacles.iter().any(|(p, o)| polygons_overlap(&polygon, p, (first.x, first.y), *o)) {
+            placed.push((polygon, (first.x, first.y)));
+            break;
+        }
+        first.icon.scale *= SHRINK;
+    }
+    if placed.is_empty() {
+        overflow.push(0);
+    }
+
+    // 每条轨道上的图标下标（相对于 rest）
+    let members: Vec<Vec<usize>> = match config.by {
+        RingBy::Group => {
+            let mut names: Vec<Option<&str>> = Vec::new();
+            for g in groups.iter().skip(1).take(rest.len()) {
+                if !names.contains(g) {
+                    names.push(*g);
+                }
+            }
+            let limit = match config.rings {
+                0 => MAX_RINGS,
+                n => n as usize,
+            }
+            .max(1);
+            let last = names.len().clamp(1, limit) - 1;
+            let mut rs = vec![Vec::new(); last + 1];
+            // 分组比轨道多时，剩下的分组都放在最外圈
+            for i in 0..rest.len() {
+                let g = groups.get(i + 1).copied().flatten();
+                let k = names.iter().position(|n| *n == g).unwrap_or(0);
+                rs[k.min(last)].push(i);
+            }
+            rs.retain(|m| !m.is_empty());
+            rs
+        }
+        RingBy::Weight => {
+            let count = match config.rings {
+                0 => ((rest.len() as f32 / 4.0).sqrt().round() as usize).clamp(1, MAX_RINGS),
+                n => n as usize,
+            }
+            .min(rest.len())
+            .max(1);
+            let perimeters: Vec<f32> = (0..count)
+                .map(|k| {
+                    let t = (k as f32 + 0.5) / count as f32;
+                    perimeter(r0 + (a - r0) * t, r0 + (b - r0) * t)
+                })
+                .collect();
+            let mut next = 0;
+            split(rest.len(), &perimeters)
+                .into_iter()
+                .map(|n| {
+                    let rs: Vec<usize> = (next..(next + n).min(rest.len())).collect();
+                    next += n;
+                    rs
+                })
+                .collect()
+        }
+    };
+    if rest.is_empty() || members.is_empty() {
+        return (orbits, overflow);
+    }
+
+    let count = members.len();
+    let band = ((b - r0) / count as f32).max(1.0);
+    for (k, member) in members.iter().enumerate() {
+        let t = (k as f32 + 0.5) / count as f32;
+        let ring = Ring {
+            rx: r0 + (a - r0) * t,
+            ry: r0 + (b - r0) * t,
+            direction: if k % 2 == 0 { 1.0 } else { -1.0 },
+        };
+        orbits.rings.push(ring);
+        let spacing = TAU / member.len() as f32;
+        // 图标的外接圆不超过轨道间距和相邻图标的弧长
+        let diameter = band.min(perimeter(ring.rx, ring.ry) / member.len() as f32) * 0.85;
+        // 相邻轨道错开半个间隔，避免排成一条线
+        let start = -PI / 2.0 + spacing * 0.5 * k as f32;
+        for (j, &i) in member.iter().enumerate() {
+            let item = &mut rest[i];
+            let base = start + spacing * j as f32;
+            item.icon.scale = fit(&item.icon, diameter);
+            let mut found = None;
+            'shrink: for _ in 0..=SHRINK_STEPS {
+                let polygon = item.icon.scaled_polygon();
+                let size = item.icon.scaled_size();
+                orbits.slots[i + 1] = Some((k, base));
+                if !sweep_clear(&orbits, i + 1, &polygon, size, &obstacles) {
+                    item.icon.scale *= SHRINK;
+                    continue;
+                }
+                // 先试原位置，再向两侧微调
+                for step in 0..=NUDGE_STEPS * 2 {
+                    let sign = if step % 2 == 0 { 1.0 } else { -1.0 };
+                    let angle = base + sign * spacing / 8.0 * step.div_ceil(2) as f32;
+                    orbits.slots[i + 1] = Some((k, angle));
+                    let offset = orbits.position(i + 1, size, 0.0);
+                    if !placed.iter().any(|(p, o)| polygons_overlap(&polygon, p, offset, *o)) {
+                        found = Some((polygon, offset));
+                        break 'shrink;
+                    }
+                }
+                item.icon.scale *= SHRINK;
+            }
+            match found {
+                Some((polygon, offset)) => {
+                    (item.x, item.y) = offset;
+                    placed.push((polygon, offset));
+                }
+                None => {
+                    orbits.slots[i + 1] = None;
+                    overflow.push(i + 1);
+                }
+            }
+        }
+    }
+    (orbits, overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Region;
+
+    fn square(size: u32) -> Position {
+        Position::new(Icon::new(size, size, vec![(0, 0), (size, 0), (size, size), (0, size)], 1.0))
+    }
+
+    #[test]
+    fn rings_avoid_keep_out_while_turning() {
+        let mut items: Vec<Position> = (0..13).map(|_| square(40)).collect();
+        let frame = Frame {
+            keep_out: vec![Region { x: 0, y: 0, width: 160, height: 120 }],
+            ..Frame::default()
+        };
+        let (orbits, overflow) = orbit(&mut items, &[], 600, 400, &frame, &Orbit::default());
+        assert!(!overflow.is_empty());
+        let obstacles = frame.obstacles(600, 400);
+        for f in 0..60 {
+            let phase = f as f32 / 60.0;
+            for (i, item) in items.iter().enumerate().filter(|(i, _)| !overflow.contains(i)) {
+                let polygon = item.icon.scaled_polygon();
+                let offset = orbits.position(i, item.icon.scaled_size(), phase);
+                assert!(!obstacles.iter().any(|(p, o)| polygons_overlap(&polygon, p, offset, *o)));
+            }
+        }
+    }
+
+    #[test]
+    fn center_avoids_keep_out() {
+        let mut items = vec![square(40), square(40)];
+        let frame = Frame {
+            keep_out: vec![Region { x: 250, y: 150, width: 100, height: 100 }],
+            ..Frame::default()
+        };
+        let (_, overflow) = orbit(&mut items, &[], 600, 400, &frame, &Orbit::default());
+        assert!(overflow.contains(&0));
+    }
+}
@@ -0,0 +1,415 @@
+//! 文字和头像图层，放在图标墙上面，排布时作为固定的障碍物。
+//! 文字经 usvg 排版（字体来自 fontdb），与图标一样以矢量图层输出
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use resvg::tiny_skia::{Color, FillRule, FilterQuality, Mask, Paint, PathBuilder, Pixmap, PixmapPaint, Stroke, Transform};
+use serde::{Deserialize, Serialize};
+use usvg::fontdb;
+
+use crate::compose::{color_to_hex, parse_color, Layer, Source};
+use crate::frame::Region;
+use crate::png::{svg_options, to_pixmap};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    fn anchor(self) -> &'static str {
+        match self {
+            Align::Left => "start",
+            Align::Center => "middle",
+            Align::Right => "end",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Shadow {
+    pub color: String,
+    #[serde(default)]
+    pub dx: f32,
+    #[serde(default)]
+    pub dy: f32,
+    #[serde(default)]
+    pub blur: f32,
+}
+
+/// 描边
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Outline {
+    pub color: String,
+    pub width: f32,
+}
+
+fn default_size() -> f32 {
+    32.0
+}
+
+fn default_weight() -> u16 {
+    400
+}
+
+fn default_color() -> String {
+    "#000000".to_string()
+}
+
+fn default_line_height() -> f32 {
+    1.25
+}
+
+/// 文字图层，`(x, y)` 为文字框左上角
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Text {
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+    /// 超过该宽度时按词换行，`\n` 总是换行
+    #[serde(default)]
+    pub width: Option<f32>,
+    #[serde(default = "default_size")]
+    pub size: f32,
+    /// 字体族，默认 sans-serif
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default = "default_weight")]
+    pub weight: u16,
+    #[serde(default = "default_color")]
+    pub color: String,
+    #[serde(default)]
+    pub align: Align,
+    /// 行高，字号的倍数
+    #[serde(default = "default_line_height")]
+    pub line_height: f32,
+    #[serde(default)]
+    pub shadow: Option<Shadow>,
+    #[serde(default)]
+    pub stroke: Option<Outline>,
+}
+
+/// 头像，按 `size` 裁成圆形，`(x, y)` 为左上角
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Avatar {
+    pub x: f32,
+    pub y: f32,
+    pub size: f32,
+    /// 外圈描边
+    #[serde(default)]
+    pub border: Option<Outline>,
+}
+
+/// 系统字体，浏览器中为空
+pub fn system_fonts() -> Arc<fontdb::Database> {
+    let mut fonts = svg_options().fontdb;
+    default_families(Arc::make_mut(&mut fonts));
+    fonts
+}
+
+/// 没有字体时 usvg 会直接丢掉文字，浏览器中需要先用 `load_font` 加载
+pub(crate) fn check_fonts(fonts: &fontdb::Database) -> Result<()> {
+    if fonts.is_empty() {
+        bail!("no fonts available, call load_font first");
+    }
+    Ok(())
+}
+
+/// fontdb 默认把 sans-serif 映射到 Arial，没有时改用已加载的无衬线字体
+pub(crate) fn default_families(db: &mut fontdb::Database) {
+    let query = fontdb::Query {
+        families: &[fontdb::Family::SansSerif],
+        ..Default::default()
+    };
+    if db.query(&query).is_some() {
+        return;
+    }
+    let families: Vec<String> = db
+        .faces()
+        .filter_map(|face| face.families.first().map(|(name, _)| name.clone()))
+        .collect();
+    let plain = |name: &&String| !["Mono", "Math", "Serif"].iter().any(|k| name.contains(k));
+    let fallback = families
+        .iter()
+        .filter(plain)
+        .find(|name| name.contains("Sans"))
+        .or_else(|| families.iter().find(plain))
+        .or(families.first());
+    if let Some(name) = fallback {
+        db.set_sans_serif_family(name.clone());
+    }
+}
+
+pub(crate) fn color(text: &str) -> Result<Color> {
+    parse_color(text).ok_or(anyhow!("invalid color: {}", text))
+}
+
+// fill="#rrggbb" fill-opacity="a"，只输出解析过的颜色，避免注入
+pub(crate) fn paint_attrs(name: &str, text: &str) -> Result<String> {
+    let c = color(text)?;
+    Ok(format!(r#"{0}="{1}" {0}-opacity="{2}""#, name, color_to_hex(c), c.alpha()))
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut rs = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => rs.push_str("&amp;"),
+            '<' => rs.push_str("&lt;"),
+            '>' => rs.push_str("&gt;"),
+            '"' => rs.push_str("&quot;"),
+            _ => rs.push(c),
+        }
+    }
+    rs
+}
+
+impl Text {
+    fn family(&self) -> String {
+        escape(self.family.as_deref().unwrap_or("sans-serif"))
+    }
+
+    pub(crate) fn options(fonts: &Arc<fontdb::Database>) -> usvg::Options<'static> {
+        usvg::Options {
+            fontdb: fonts.clone(),
+            ..Default::default()
+        }
+    }
+
+    // 一行文字的排版宽度，包含空格。字体中没有任何字形时报错，而不是得到看不见的文字
+    pub(crate) fn measure(&self, line: &str, opt: &usvg::Options) -> Result<f32> {
+        check_fonts(&opt.fontdb)?;
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1"><text xml:space="preserve" font-family="{}" font-size="{}" font-weight="{}">{}</text></svg>"#,
+            self.family(),
+            self.size,
+            self.weight,
+            escape(line)
+        );
+        let tree = usvg::Tree::from_str(&svg, opt)?;
+        let width = tree
+            .root()
+            .children()
+            .iter()
+            .find_map(|node| match node {
+                usvg::Node::Text(text) => Some(text.bounding_box().width()),
+                _ => None,
+            })
+            .unwrap_or(0.0);
+        if width <= 0.0 && !line.trim().is_empty() {
+            bail!("no loaded font can render {:?}, call load_font with a font that covers it", line);
+        }
+        Ok(width)
+    }
+
+    // 按宽度贪心换行，单个词超宽时独占一行
+    fn lines(&self, opt: &usvg::Options) -> Result<Vec<(String, f32)>> {
+        let mut rs = Vec::new();
+        for paragraph in self.text.lines() {
+            let Some(max) = self.width else {
+                rs.push((paragraph.to_string(), self.measure(paragraph, opt)?));
+                continue;
+            };
+            let mut line = String::new();
+            let mut width = 0.0;
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+                let w = self.measure(&candidate, opt)?;
+                if w > max && !line.is_empty() {
+                    rs.push((std::mem::take(&mut line), width));
+                    width = self.measure(word, opt)?;
+                    line = word.to_string();
+                } else {
+                    line = candidate;
+                    width = w;
+                }
+            }
+            rs.push((line, width));
+        }
+        Ok(rs)
+    }
+
+    /// 排版并生成图层，图层包含阴影和描边的范围
+    pub fn layer(&self, fonts: &Arc<fontdb::Database>) -> Result<Layer> {
+        if self.size <= 0.0 || self.line_height <= 0.0 {
+            bail!("invalid text size: {}", self.size);
+        }
+        let opt = Text::options(fonts);
+        let lines = self.lines(&opt)?;
+        let content = self
+            .width
+            .unwrap_or_else(|| lines.iter().map(|(_, w)| *w).fold(0.0, f32::max))
+            .max(1.0);
+        let line_height = self.size * self.line_height;
+        let stroke = self.stroke.as_ref().map_or(0.0, |s| s.width.max(0.0));
+        let shadow = self
+            .shadow
+            .as_ref()
+            .map_or(0.0, |s| s.blur.max(0.0) * 3.0 + s.dx.abs().max(s.dy.abs()));
+        let pad = (stroke + shadow).ceil();
+        let width = content + pad * 2.0;
+        let height = line_height * lines.len().max(1) as f32 + pad * 2.0;
+
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height
+        )?;
+        let mut filter = "";
+        if let Some(s) = &self.shadow {
+            let c = color(&s.color)?;
+            write!(
+                svg,
+                r#"<filter id="shadow" filterUnits="userSpaceOnUse" x="0" y="0" width="{}" height="{}"><feDropShadow dx="{}" dy="{}" stdDeviation="{}" flood-color="{}" flood-opacity="{}"/></filter>"#,
+                width,
+                height,
+                s.dx,
+                s.dy,
+                s.blur.max(0.0),
+                color_to_hex(c),
+                c.alpha()
+            )?;
+            filter = r#" filter="url(#shadow)""#;
+        }
+        let x = match self.align {
+            Align::Left => pad,
+            Align::Center => pad + content / 2.0,
+            Align::Right => pad + content,
+        };
+        let stroke_attrs = match &self.stroke {
+            Some(s) => format!(
+                r#" {} stroke-width="{}" stroke-linejoin="round" paint-order="stroke""#,
+                paint_attrs("stroke", &s.color)?,
+                s.width * 2.0
+            ),
+            None => String::new(),
+        };
+        write!(
+            svg,
+            r#"<g{} font-family="{}" font-size="{}" font-weight="{}" text-anchor="{}" {}{}>"#,
+            filter,
+            self.family(),
+            self.size,
+            self.weight,
+            self.align.anchor(),
+            paint_attrs("fill", &self.color)?,
+            stroke_attrs
+        )?;
+        for (index, (line, _)) in lines.iter().enumerate() {
+            // 基线大致在行框内字号的 0.8 处
+            let baseline = pad + line_height * index as f32 + (line_height - self.size) / 2.0 + self.size * 0.8;
+            write!(
+                svg,
+                r#"<text xml:space="preserve" x="{}" y="{}">{}</text>"#,
+                x,
+                baseline,
+                escape(line)
+            )?;
+        }
+        svg.push_str("</g></svg>");
+        let tree = usvg::Tree::from_str(&svg, &opt)?;
+        Ok(Layer {
+            source: Arc::new(Source::Vector(Box::new(tree))),
+            x: self.x - pad,
+            y: self.y - pad,
+            width,
+            height,
+            clip: None,
+            rotation: 0.0,
+        })
+    }
+}
+
+impl Avatar {
+    /// `data` 为 PNG、JPG 等图片，居中裁成正方形后再裁成圆形
+    pub fn layer(&self, data: &[u8]) -> Result<Layer> {
+        let size = self.size.round() as u32;
+        let mut pixmap = Pixmap::new(size, size).ok_or(anyhow!("invalid avatar size: {}", self.size))?;
+        let source = to_pixmap(&image::load_from_memory(data)?.to_rgba8())?;
+        let (w, h) = (source.width() as f32, source.height() as f32);
+        let s = size as f32;
+        let scale = (s / w).max(s / h);
+        let transform = Transform::from_row(scale, 0.0, 0.0, scale, (s - w * scale) / 2.0, (s - h * scale) / 2.0);
+
+        let half = s / 2.0;
+        let border = self.border.as_ref().map_or(0.0, |b| b.width.max(0.0));
+        let circle = PathBuilder::from_circle(half, half, half).ok_or(anyhow!("invalid avatar size"))?;
+        let mut mask = Mask::new(size, size).ok_or(anyhow!("invalid avatar size"))?;
+        mask.fill_path(&circle, FillRule::Winding, true, Transform::identity());
+        let paint = PixmapPaint {
+            quality: FilterQuality::Bicubic,
+            ..Default::default()
+        };
+        pixmap.draw_pixmap(0, 0, source.as_ref(), &paint, transform, Some(&mask));
+
+        if let Some(b) = self.border.as_ref().filter(|_| border > 0.0) {
+            let ring = PathBuilder::from_circle(half, half, (half - border / 2.0).max(0.5))
+                .ok_or(anyhow!("invalid avatar border"))?;
+            let mut paint = Paint::default();
+            paint.set_color(color(&b.color)?);
+            paint.anti_alias = true;
+            let stroke = Stroke {
+                width: border,
+                ..Default::default()
+            };
+            pixmap.stroke_path(&ring, &paint, &stroke, Transform::identity(), None);
+        }
+        Ok(Layer {
+            source: Arc::new(Source::Raster(pixmap)),
+            x: self.x,
+            y: self.y,
+            width: s,
+            height: s,
+            clip: None,
+            rotation: 0.0,
+        })
+    }
+}
+
+/// 图层覆盖的矩形，排布时图标避开这里
+pub fn region(layer: &Layer) -> Region {
+    let x = layer.x.max(0.0).floor();
+    let y = layer.y.max(0.0).floor();
+    Region {
+        x: x as u32,
+        y: y as u32,
+        width: (layer.x + layer.width - x).max(0.0).ceil() as u32,
+        height: (layer.y + layer.height - y).max(0.0).ceil() as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(content: &str) -> Text {
+        serde_json::from_value(serde_json::json!({ "text": content, "x": 0, "y": 0 })).unwrap()
+    }
+
+    #[test]
+    fn text_needs_fonts() {
+        let empty = Arc::new(fontdb::Database::new());
+        let Err(error) = text("Jane Doe").layer(&empty) else {
+            panic!("text rendered without fonts");
+        };
+        assert!(error.to_string().contains("load_font"), "{}", error);
+    }
+
+    #[test]
+    fn text_layer_has_width() {
+        let fonts = system_fonts();
+        if fonts.is_empty() {
+            return;
+        }
+        let layer = text("Jane Doe").layer(&fonts).unwrap();
+        assert!(layer.width > 40.0 && layer.height > 0.0);
+        // 只有空白的行宽度为 0，不算错误
+        assert!(text(" ").layer(&fonts).is_ok());
+    }
+}
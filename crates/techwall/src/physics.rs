@@ -0,0 +1,202 @@
+//! 无头物理堆叠：模拟前端 matter-js 的掉落效果，但结果是确定的
+use crate::align::{polygons_overlap, Polygon, Position};
+use crate::frame::Frame;
+use crate::mask::{spans, Occupancy, ShapeMask};
+use crate::rng::Rng;
+
+// 粗略下落的步长
+const FALL_STEP: u32 = 4;
+// 每个图标最多滚动的次数
+const ROLL_LIMIT: usize = 32;
+
+struct Pile<'a> {
+    placed: &'a [(Polygon, (u32, u32))],
+    /// 容器内部，静止时图标必须完整地在里面
+    boundary: Option<&'a Occupancy>,
+    /// 容器内部和顶部开口上方，下落和滚动时不能穿过容器壁
+    passage: Option<&'a Occupancy>,
+    /// 当前图标每一行的范围，用于边界判断
+    spans: &'a [Option<(u32, u32)>],
+}
+
+impl Pile<'_> {
+    fn hit(&self, polygon: &Polygon, offset: (u32, u32)) -> bool {
+        if self.passage.is_some_and(|b| !b.fits(self.spans, offset)) {
+            return true;
+        }
+        self.placed
+            .iter()
+            .any(|(p, o)| polygons_overlap(polygon, p, offset, *o))
+    }
+
+    // 从 (x, top) 落下，返回静止时的 y；起点已被堵住或停在容器外（例如罐子的肩上）时返回 None
+    fn fall(&self, polygon: &Polygon, x: u32, top: u32, bottom: u32) -> Option<u32> {
+        if self.hit(polygon, (x, top)) {
+            return None;
+        }
+        let mut y = top;
+        while y < bottom {
+            let next = (y + FALL_STEP).min(bottom);
+            if self.hit(polygon, (x, next)) {
+                break;
+            }
+            y = next;
+        }
+        while y < bottom && !self.hit(polygon, (x, y + 1)) {
+            y += 1;
+        }
+        if self.boundary.is_some_and(|b| !b.fits(self.spans, (x, y))) {
+            return None;
+        }
+        Some(y)
+    }
+}
+
+/// 让图标依次从画布顶部落下并堆积在底部，`seed` 决定每个图标的下落位置。
+/// `frame` 的留白是墙壁，圆角和保留区域是固定的障碍物。
+/// `boundary` 为容器内部，图标从上方经顶部开口倒进容器并堆积在里面。返回放不下的图标下标。
+pub fn settle(
+    items: &mut [Position],
+    width: u32,
+    height: u32,
+    seed: u64,
+    frame: &Frame,
+    boundary: Option<&ShapeMask>,
+) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    let (mut x0, y0, mut x1, y1) = frame.area(width, height);
+    // 只在容器的宽度范围内投放
+    if let Some((bx0, _, bx1, _)) = boundary.and_then(ShapeMask::bounds) {
+        x0 = x0.max(bx0);
+        x1 = x1.min(bx1).max(x0);
+    }
+    let passage = boundary.map(|b| Occupancy::new(&b.with_opening()));
+    let boundary = boundary.map(Occupancy::new);
+    let mut placed: Vec<(Polygon, (u32, u32))> = frame.obstacles(width, height);
+    let mut overflow = Vec::new();
+
+    for (index, item) in items.iter_mut().enumerate() {
+        let polygon = item.icon.scaled_polygon();
+        let (w, h) = item.icon.scaled_size();
+        if w > x1 - x0 || h > y1 - y0 {
+            overflow.push(index);
+            continue;
+        }
+        let (left, right) = (x0, x1 - w);
+        let (top, bottom) = (y0, y1 - h);
+        let spans = match boundary {
+            Some(_) => spans(&polygon, h),
+            None => Vec::new(),
+        };
+        let pile = Pile {
+            placed: &placed,
+            boundary: boundary.as_ref(),
+            passage: passage.as_ref(),
+            spans: &spans,
+        };
+
+        let mut x = left + rng.below(right - left + 1);
+        let mut y = match pile.fall(&polygon, x, top, bottom) {
+            Some(y) => y,
+            None => {
+                // 落点被堵住，从左到右找一个能落下的位置
+                let found = (left..=right)
+                    .step_by(FALL_STEP as usize)
+                    .find_map(|x| pile.fall(&polygon, x, top, bottom).map(|y| (x, y)));
+                match found {
+                    Some((fx, fy)) => {
+                        x = fx;
+                        fy
+                    }
+                    None => {
+                        overflow.push(index);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        // 向两侧滚动，直到不能再往下；每次最多移动一个图标宽，可以从下面图标的顶上滑下去
+        let roll = (w / 4).max(1);
+        for _ in 0..ROLL_LIMIT {
+            let candidates = (1..=4).flat_map(|k| [x.saturating_sub(roll * k).max(left), (x + roll * k).min(right)]);
+            let best = candidates
+                .filter(|&cx| cx != x)
+                .filter_map(|cx| pile.fall(&polygon, cx, y, bottom).map(|cy| (cx, cy)))
+                .max_by_key(|&(_, cy)| cy);
+            match best {
+                Some((cx, cy)) if cy > y + 1 => {
+                    x = cx;
+                    y = cy;
+                }
+                _ => break,
+            }
+        }
+
+        item.x = x;
+        item.y = y;
+        placed.push((polygon, (x, y)));
+    }
+    overflow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::align::Icon;
+    use crate::container::{Container, Style};
+
+    fn squares(count: usize, size: u32) -> Vec<Position> {
+        let polygon = vec![(0, 0), (size, 0), (size, size), (0, size)];
+        (0..count)
+            .map(|_| Position::new(Icon::new(size, size, polygon.clone(), 1.0)))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_pile() {
+        let frame = Frame::default();
+        let mut a = squares(20, 24);
+        let mut b = squares(20, 24);
+        assert_eq!(settle(&mut a, 300, 150, 7, &frame, None), settle(&mut b, 300, 150, 7, &frame, None));
+        let positions = |items: &[Position]| items.iter().map(|p| (p.x, p.y)).collect::<Vec<_>>();
+        assert_eq!(positions(&a), positions(&b));
+    }
+
+    #[test]
+    fn piled_icons_do_not_overlap() {
+        let mut items = squares(30, 24);
+        let overflow = settle(&mut items, 300, 150, 3, &Frame::default(), None);
+        let placed: Vec<&Position> = items
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !overflow.contains(i))
+            .map(|(_, p)| p)
+            .collect();
+        assert!(!placed.is_empty());
+        for (i, a) in placed.iter().enumerate() {
+            assert!(a.x + 24 <= 300 && a.y + 24 <= 150);
+            for b in &placed[i + 1..] {
+                let polygon = a.icon.scaled_polygon();
+                assert!(!polygons_overlap(&polygon, &b.icon.scaled_polygon(), (a.x, a.y), (b.x, b.y)));
+            }
+        }
+    }
+
+    #[test]
+    fn icons_pour_through_the_neck() {
+        // 瓶口宽 20，瓶身宽 200
+        let bottle = "M90 0 L110 0 L110 40 L200 40 L200 200 L0 200 L0 40 L90 40 Z";
+        let container = Container::from_path(bottle, &Style::default(), 200, 200).unwrap();
+        let frame = Frame::default();
+        // 比瓶口宽的图标进不去，不会穿过瓶肩
+        let mut items = squares(3, 30);
+        assert_eq!(settle(&mut items, 200, 200, 1, &frame, Some(&container.mask)), [0, 1, 2]);
+
+        let mut items = squares(6, 16);
+        assert!(settle(&mut items, 200, 200, 1, &frame, Some(&container.mask)).is_empty());
+        for item in &items {
+            assert!(item.y >= 40 && item.y + 16 <= 200, "{:?}", (item.x, item.y));
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use image::{DynamicImage, GenericImageView, RgbaImage};
-use anyhow::{bail, Result};
-use std::sync::Arc;
+use anyhow::{anyhow, bail, Result};
+use std::sync::{Arc, OnceLock};
 
-use resvg::tiny_skia::Pixmap;
+use resvg::tiny_skia::{ColorU8, Pixmap};
 use usvg::{fontdb, Size, Transform};
 // const OUTPUT_WIDTH: u32 = 1200;
 // const OUTPUT_HEIGHT: u32 = 600;
@@ -86,7 +86,7 @@ where
             if (x_tail..last_x_index).contains(&_x) ||  (last_x_index..x_tail).contains(&_x)  {
                 break 'line;
             }
-            if not_empty(&image, (_x, _y)) {
+            if not_empty(image, (_x, _y)) {
                 if _x == x_head {
                     return (points, (_x, _y));
                 }
@@ -103,24 +103,24 @@ fn extract_edge_points(image: &RgbaImage, steps: (usize, usize)) -> Vec<Point> {
     let mut points = Vec::new();
     let (width, height) = image.dimensions();
     
-    let (mut ll, right_top) = curv(&image, 0..height, (0..width).rev(), steps.clone());
+    let (mut ll, right_top) = curv(image, 0..height, (0..width).rev(), steps);
     
     points.append(&mut ll); 
     points.push(right_top);
     if right_top.1 < height - 1 {
-        let (mut rl2, bottom) = curv(&image, (right_top.1..height).rev(), (0..width).rev(), steps.clone());
+        let (mut rl2, bottom) = curv(image, (right_top.1..height).rev(), (0..width).rev(), steps);
         
         rl2.reverse();
         points.push(bottom);
         points.append(&mut rl2);
 
     }
-    let (mut rl, left) = curv(&image, (0..height).rev(), 0..width, steps.clone());
+    let (mut rl, left) = curv(image, (0..height).rev(), 0..width, steps);
     points.append(&mut rl); 
     points.push(left);
 
     if left.1 > 0 {
-        let (mut ll, top) = curv(&image, 0..left.1, 0..width, steps.clone());
+        let (mut ll, top) = curv(image, 0..left.1, 0..width, steps);
         ll.reverse();
         points.push(top);
         points.append(&mut ll); 
@@ -176,13 +176,13 @@ fn _is_ccw(p1: Point, p2: Point, p3: Point, rev: bool) -> bool {
         if y3 >= y2 {
             return true;
         }
-        return x_1() * (y1 - y3) > (y1 - y2) * x_2()
+        x_1() * (y1 - y3) > (y1 - y2) * x_2()
         //
     } else {
         if y3 <= y2 {
             return false;
         }
-        return x_1() * (y3 -y1) > (y2 - y1) * x_2()
+        x_1() * (y3 -y1) > (y2 - y1) * x_2()
     }
     
     // if y3 >= y2 && y2 <= y1 {
@@ -250,24 +250,14 @@ type Point = (u32, u32);
 
 pub fn load(data: &[u8], steps: (usize, usize)) -> Result<Vec<(u32, u32)>>{
     let img = image::load_from_memory(data)?;
-    match img {
-        DynamicImage::ImageRgba8(trimmed) => {
-            // println!("Trimmed image dimensions: {:?}", trimmed.dimensions());
-            let polygon = generate_polygon(&trimmed, steps);
-            return Ok(polygon);
-            // println!("-- {:?}", polygon);
-            // let _image = crop_to_polygon(&trimmed, &polygon);
-
-            // let mut buffer = Cursor::new(Vec::new());
-            // _image.write_to(&mut buffer, image::ImageFormat::Png)?;
-            // return Ok(buffer.into_inner());
-        }
-        _ => {}
+    if let DynamicImage::ImageRgba8(trimmed) = img {
+        return Ok(generate_polygon(&trimmed, steps));
     }
     bail!("")
 }
 
-fn get_scale(size: Size) -> (f32, f32) {
+// 缩放到约 10000 像素的面积，小图标保持原样
+pub(crate) fn get_scale(size: Size) -> (f32, f32) {
     let stsize = 10000f32;
     let total = size.width() * size.height();
     if total < stsize {
@@ -278,12 +268,71 @@ fn get_scale(size: Size) -> (f32, f32) {
     (ff,ff)
 }
 
+/// 除黑白灰外最多只有一种色相时视为单色图标
+pub fn is_monochrome(rgba: &[u8]) -> bool {
+    let mut opaque = 0usize;
+    let mut hues = [0usize; 12];
+    for px in rgba.chunks_exact(4) {
+        if px[3] < 128 {
+            continue;
+        }
+        opaque += 1;
+        let (r, g, b) = (px[0] as i32, px[1] as i32, px[2] as i32);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        if max - min < 48 {
+            continue;
+        }
+        // 粗略的色相分桶，只需要区分不同颜色
+        let hue = if max == r {
+            (g - b) as f32 / (max - min) as f32
+        } else if max == g {
+            2.0 + (b - r) as f32 / (max - min) as f32
+        } else {
+            4.0 + (r - g) as f32 / (max - min) as f32
+        };
+        hues[((hue + 6.0) * 2.0) as usize % 12] += 1;
+    }
+    // 超过 5% 像素的色相桶数，相邻桶算同一种颜色
+    let threshold = opaque / 20;
+    let colors = (0..12)
+        .filter(|&i| hues[i] > threshold && hues[(i + 11) % 12] <= threshold)
+        .count();
+    // 所有桶都超过阈值时没有起点，是一圈连续的色相，按多色处理
+    if colors == 0 && hues.iter().any(|&h| h > threshold) {
+        return false;
+    }
+    colors <= 1
+}
+
+/// 转成 tiny-skia 使用的预乘格式
+pub(crate) fn to_pixmap(img: &RgbaImage) -> Result<Pixmap> {
+    let mut pixmap = Pixmap::new(img.width(), img.height()).ok_or(anyhow!("incorrect size"))?;
+    for (p, px) in pixmap.pixels_mut().iter_mut().zip(img.pixels()) {
+        *p = ColorU8::from_rgba(px[0], px[1], px[2], px[3]).premultiply();
+    }
+    Ok(pixmap)
+}
+
+// 系统字体只扫描一次，之后共享同一个数据库
+static SYSTEM_FONTS: OnceLock<Arc<fontdb::Database>> = OnceLock::new();
+
+/// 解析 SVG 使用的选项，使用共享的系统字体
+pub fn svg_options() -> usvg::Options<'static> {
+    let fonts = SYSTEM_FONTS.get_or_init(|| {
+        let mut fdb = fontdb::Database::new();
+        fdb.load_system_fonts();
+        Arc::new(fdb)
+    });
+    usvg::Options {
+        fontdb: fonts.clone(),
+        ..Default::default()
+    }
+}
+
 pub fn convert_svg_to_png(data: Vec<u8>) -> Result<(Pixmap, f32)> {
     let svg_data = String::from_utf8(data)?;
-    let mut opt = usvg::Options::default();
-    let mut fdb = fontdb::Database::new();
-    fdb.load_system_fonts();
-    opt.fontdb = Arc::new(fdb);
+    let opt = svg_options();
     let tree = usvg::Tree::from_str(&svg_data, &opt)?;
     
     let svg_size = tree.size();
@@ -297,7 +346,7 @@ pub fn convert_svg_to_png(data: Vec<u8>) -> Result<(Pixmap, f32)> {
     let target_width = (original_width * scale.0) as u32;
     let target_height = (original_height * scale.1) as u32;
     // println!("target_width: {}, target_height: {}", target_width, target_height);
-    let mut pixmap = Pixmap::new(target_width, target_height).ok_or("incorrect size").unwrap();
+    let mut pixmap = Pixmap::new(target_width, target_height).ok_or(anyhow!("incorrect size"))?;
     
     let transform = Transform::from_scale(scale.0, scale.1);
     // transform.
@@ -309,3 +358,35 @@ pub fn convert_svg_to_png(data: Vec<u8>) -> Result<(Pixmap, f32)> {
     );
     Ok((pixmap, scale.0))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels(colors: &[[u8; 3]]) -> Vec<u8> {
+        colors.iter().flat_map(|c| [c[0], c[1], c[2], 255]).collect()
+    }
+
+    #[test]
+    fn monochrome() {
+        assert!(is_monochrome(&pixels(&[[0, 0, 0], [255, 255, 255], [128, 128, 128]])));
+        assert!(is_monochrome(&pixels(&[[200, 30, 30], [180, 20, 20], [0, 0, 0]])));
+        assert!(!is_monochrome(&pixels(&[[200, 30, 30], [30, 30, 200]])));
+    }
+
+    #[test]
+    fn full_hue_wheel_is_multicolor() {
+        // 12 个色相各占一个桶
+        let wheel: Vec<[u8; 3]> = (0..12)
+            .map(|i| {
+                let h = i as f32 / 2.0 + 0.25;
+                let f = |n: f32| {
+                    let k = (n + h) % 6.0;
+                    (255.0 * (1.0 - (k.min(4.0 - k).clamp(0.0, 1.0)))) as u8
+                };
+                [f(5.0), f(3.0), f(1.0)]
+            })
+            .collect();
+        assert!(!is_monochrome(&pixels(&wheel)));
+    }
+}
@@ -0,0 +1,354 @@
+//! 图标重新着色。矢量图标在 usvg 树上修改颜色，SVG 输出也保持一致
+use anyhow::Result;
+use resvg::tiny_skia::{Color, ColorU8, Pixmap, Transform};
+
+use crate::compose::{color_to_hex, parse_color, Source};
+use crate::png::svg_options;
+
+type Rgb = (u8, u8, u8);
+
+// 计算主色时矢量图标栅格化的尺寸
+const SAMPLE: f32 = 64.0;
+
+/// 着色方式
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Recolor {
+    #[default]
+    Original,
+    /// 所有颜色替换为同一种颜色，保留透明度
+    Monochrome(Color),
+    Grayscale,
+    /// 按亮度在两种颜色之间插值，分别对应最暗和最亮
+    Duotone(Color, Color),
+    /// 图标与背景对比度太低时反转亮度，保留色相
+    AutoInvert,
+}
+
+impl Recolor {
+    /// "original"、"grayscale"、"auto-invert"、"mono:#ffffff"、"duotone:#1e1e2e:#89b4fa"
+    pub fn parse(spec: &str) -> Option<Recolor> {
+        let mut parts = spec.split(':');
+        let rs = match parts.next()? {
+            "original" => Recolor::Original,
+            "grayscale" | "gray" => Recolor::Grayscale,
+            "auto-invert" | "invert" => Recolor::AutoInvert,
+            "mono" | "monochrome" => Recolor::Monochrome(parse_color(parts.next()?)?),
+            "duotone" => Recolor::Duotone(parse_color(parts.next()?)?, parse_color(parts.next()?)?),
+            _ => return None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(rs)
+    }
+
+    /// 返回着色后的来源，`background` 用于 auto-invert 判断对比度
+    pub fn apply(&self, source: &Source, background: Option<Color>) -> Result<Source> {
+        match source {
+            Source::Raster(pix) => {
+                let mut pix = pix.clone();
+                self.apply_pixmap(&mut pix, background);
+                Ok(Source::Raster(pix))
+            }
+            Source::Vector(tree) => Ok(Source::Vector(Box::new(self.apply_tree(tree, background)?))),
+        }
+    }
+
+    pub fn apply_pixmap(&self, pix: &mut Pixmap, background: Option<Color>) {
+        if *self == Recolor::Original {
+            return;
+        }
+        let colors: Vec<(Rgb, u8)> = pix
+            .pixels()
+            .iter()
+            .map(|p| p.demultiply())
+            .map(|c| ((c.red(), c.green(), c.blue()), c.alpha()))
+            .collect();
+        let invert = self.should_invert(colors.iter().filter(|(_, a)| *a > 0).map(|(c, _)| *c), background);
+        for (p, (c, a)) in pix.pixels_mut().iter_mut().zip(colors) {
+            if a == 0 {
+                continue;
+            }
+            let (r, g, b) = self.map(c, invert);
+            *p = ColorU8::from_rgba(r, g, b, a).premultiply();
+        }
+    }
+
+    /// usvg 输出的颜色都是 `#rrggbb` 形式，替换后重新解析
+    pub fn apply_tree(&self, tree: &usvg::Tree, background: Option<Color>) -> Result<usvg::Tree> {
+        let text = tree.to_string(&usvg::WriteOptions::default());
+        let invert = self.should_invert(hex_colors(&text).into_iter().map(|(_, c)| c), background);
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for (index, c) in hex_colors(&text) {
+            let (r, g, b) = self.map(c, invert);
+            out.push_str(&text[last..index]);
+            out.push_str(&color_to_hex(Color::from_rgba8(r, g, b, 255)));
+            last = index + 7;
+        }
+        out.push_str(&text[last..]);
+        Ok(usvg::Tree::from_str(&out, &svg_options())?)
+    }
+
+    fn should_invert(&self, colors: impl Iterator<Item = Rgb>, background: Option<Color>) -> bool {
+        if *self != Recolor::AutoInvert {
+            return false;
+        }
+        let Some(background) = background else {
+            return false;
+        };
+        let (mut sum, mut count) = (0.0, 0);
+        for c in colors {
+            sum += luminance(c);
+            count += 1;
+        }
+        if count == 0 {
+            return false;
+        }
+        let bg = background.to_color_u8();
+        let bg = luminance((bg.red(), bg.green(), bg.blue()));
+        (sum / count as f32 - bg).abs() < 0.3
+    }
+
+    fn map(&self, c: Rgb, invert: bool) -> Rgb {
+        match *self {
+            Recolor::Original => c,
+            Recolor::Monochrome(color) => rgb(color),
+            Recolor::Grayscale => {
+                let l = (luminance(c) * 255.0).round() as u8;
+                (l, l, l)
+            }
+            Recolor::Duotone(dark, light) => {
+                let t = luminance(c);
+                let (d, l) = (rgb(dark), rgb(light));
+                let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+                (lerp(d.0, l.0), lerp(d.1, l.1), lerp(d.2, l.2))
+            }
+            Recolor::AutoInvert if invert => invert_lightness(c),
+            Recolor::AutoInvert => c,
+        }
+    }
+}
+
+fn rgb(color: Color) -> Rgb {
+    let c = color.to_color_u8();
+    (c.red(), c.green(), c.blue())
+}
+
+// 相对亮度，0 到 1
+pub(crate) fn luminance((r, g, b): Rgb) -> f32 {
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+}
+
+// HSL 中 L 取反，色相和饱和度不变
+fn invert_lightness((r, g, b): Rgb) -> Rgb {
+    let max = r.max(g).max(b) as f32;
+    let min = r.min(g).min(b) as f32;
+    // 色度不变，只把 (max + min) / 2 移到 255 - (max + min) / 2
+    let shift = 255.0 - (max + min);
+    let channel = |c: u8| (c as f32 + shift).clamp(0.0, 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+// 可以重新着色的属性，`href="#id"` 之类的引用不能改
+const PAINT: [&str; 4] = ["fill", "stroke", "stop-color", "flood-color"];
+
+// 颜色属性值中所有 `#rrggbb` 的位置和颜色
+fn hex_colors(text: &str) -> Vec<(usize, Rgb)> {
+    let mut rs = Vec::new();
+    for (i, _) in text.match_indices("=\"#") {
+        let name = text[..i].trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '-');
+        if !PAINT.contains(&&text[name.len()..i]) || text.as_bytes().get(i + 9) != Some(&b'"') {
+            continue;
+        }
+        if let Some(color) = text.get(i + 2..i + 9).and_then(parse_color) {
+            rs.push((i + 2, rgb(color)));
+        }
+    }
+    rs
+}
+
+/// 图标的主色：不透明像素按颜色分桶，优先取有彩色的桶，黑白灰图标取最多的颜色
+pub fn dominant(source: &Source) -> Option<Color> {
+    let sample;
+    let pixmap = match source {
+        Source::Raster(pix) => pix,
+        Source::Vector(tree) => {
+            let size = tree.size();
+            let scale = SAMPLE / size.width().max(size.height());
+            let mut pix = Pixmap::new(
+                (size.width() * scale).ceil().max(1.0) as u32,
+                (size.height() * scale).ceil().max(1.0) as u32,
+            )?;
+            resvg::render(tree, Transform::from_scale(scale, scale), &mut pix.as_mut());
+            sample = pix;
+            &sample
+        }
+    };
+    // 每个通道取高 4 位，桶内累计颜色用于求平均
+    let mut buckets = vec![(0u32, [0u64; 3]); 4096];
+    let (mut colorful, mut opaque) = (0u32, 0u32);
+    for p in pixmap.pixels() {
+        let c = p.demultiply();
+        if c.alpha() < 128 {
+            continue;
+        }
+        let (r, g, b) = (c.red(), c.green(), c.blue());
+        let chroma = r.max(g).max(b) - r.min(g).min(b);
+        opaque += 1;
+        let index = (r as usize >> 4) << 8 | (g as usize >> 4) << 4 | b as usize >> 4;
+        let bucket = &mut buckets[index];
+        bucket.0 += 1;
+        bucket.1[0] += r as u64;
+        bucket.1[1] += g as u64;
+        bucket.1[2] += b as u64;
+        if chroma > 48 {
+            colorful += 1;
+        }
+    }
+    if opaque == 0 {
+        return None;
+    }
+    // 彩色像素超过一成时忽略黑白灰
+    let want_color = colorful * 10 > opaque;
+    let is_colorful = |index: usize| {
+        let (r, g, b) = (index >> 8, index >> 4 & 15, index & 15);
+        r.max(g).max(b) - r.min(g).min(b) >= 3
+    };
+    let (_, (count, sum)) = buckets
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !want_color || is_colorful(*index))
+        .max_by_key(|(_, (count, _))| *count)?;
+    if *count == 0 {
+        return None;
+    }
+    let n = *count as u64;
+    Some(Color::from_rgba8(
+        (sum[0] / n) as u8,
+        (sum[1] / n) as u8,
+        (sum[2] / n) as u8,
+        255,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgb = (255, 0, 0);
+
+    fn pixmap(colors: &[(Rgb, u8)]) -> Pixmap {
+        let mut pix = Pixmap::new(colors.len() as u32, 1).unwrap();
+        for (p, &((r, g, b), a)) in pix.pixels_mut().iter_mut().zip(colors) {
+            *p = ColorU8::from_rgba(r, g, b, a).premultiply();
+        }
+        pix
+    }
+
+    fn pixels(pix: &Pixmap) -> Vec<(Rgb, u8)> {
+        pix.pixels()
+            .iter()
+            .map(|p| p.demultiply())
+            .map(|c| ((c.red(), c.green(), c.blue()), c.alpha()))
+            .collect()
+    }
+
+    fn tree(fill: &str) -> usvg::Tree {
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><rect width="10" height="10" fill="{}"/></svg>"#,
+            fill
+        );
+        usvg::Tree::from_str(&svg, &svg_options()).unwrap()
+    }
+
+    fn tree_colors(tree: &usvg::Tree) -> Vec<Rgb> {
+        hex_colors(&tree.to_string(&usvg::WriteOptions::default())).into_iter().map(|(_, c)| c).collect()
+    }
+
+    fn color(hex: &str) -> Color {
+        parse_color(hex).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Recolor::parse("original"), Some(Recolor::Original));
+        assert_eq!(Recolor::parse("gray"), Some(Recolor::Grayscale));
+        assert_eq!(Recolor::parse("invert"), Some(Recolor::AutoInvert));
+        assert_eq!(Recolor::parse("mono:#ffffff"), Some(Recolor::Monochrome(Color::WHITE)));
+        assert_eq!(
+            Recolor::parse("duotone:#000000:#ffffff"),
+            Some(Recolor::Duotone(Color::BLACK, Color::WHITE))
+        );
+        assert_eq!(Recolor::parse("mono"), None);
+        assert_eq!(Recolor::parse("mono:#fff:#000"), None);
+        assert_eq!(Recolor::parse("sepia"), None);
+    }
+
+    #[test]
+    fn pixmap_modes() {
+        let source = [(RED, 255), ((0, 0, 0), 0)];
+        let apply = |recolor: Recolor, background: Option<Color>| {
+            let mut pix = pixmap(&source);
+            recolor.apply_pixmap(&mut pix, background);
+            pixels(&pix)
+        };
+        assert_eq!(apply(Recolor::Original, None), source);
+        // 透明像素保持不变
+        assert_eq!(apply(Recolor::Monochrome(Color::WHITE), None), [((255, 255, 255), 255), ((0, 0, 0), 0)]);
+        assert_eq!(apply(Recolor::Grayscale, None)[0], ((54, 54, 54), 255));
+        let duotone = Recolor::Duotone(color("#000000"), color("#0000ff"));
+        assert_eq!(apply(duotone, None)[0], ((0, 0, 54), 255));
+
+        let dark = [((10, 10, 10), 255)];
+        let invert = |background: Color| {
+            let mut pix = pixmap(&dark);
+            Recolor::AutoInvert.apply_pixmap(&mut pix, Some(background));
+            pixels(&pix)[0].0
+        };
+        // 深色图标放在深色背景上才反转
+        assert_eq!(invert(Color::BLACK), (245, 245, 245));
+        assert_eq!(invert(Color::WHITE), (10, 10, 10));
+    }
+
+    #[test]
+    fn tree_modes() {
+        let apply = |recolor: Recolor, fill: &str, background: Option<Color>| {
+            let source = Source::Vector(Box::new(tree(fill)));
+            match recolor.apply(&source, background).unwrap() {
+                Source::Vector(tree) => tree_colors(&tree),
+                Source::Raster(_) => panic!("vector icon rasterized"),
+            }
+        };
+        assert_eq!(apply(Recolor::Original, "#ff0000", None), [RED]);
+        assert_eq!(apply(Recolor::Monochrome(Color::WHITE), "#ff0000", None), [(255, 255, 255)]);
+        assert_eq!(apply(Recolor::Grayscale, "#ff0000", None), [(54, 54, 54)]);
+        let duotone = Recolor::Duotone(color("#000000"), color("#0000ff"));
+        assert_eq!(apply(duotone, "#ff0000", None), [(0, 0, 54)]);
+        assert_eq!(apply(Recolor::AutoInvert, "#0a0a0a", Some(Color::BLACK)), [(245, 245, 245)]);
+        assert_eq!(apply(Recolor::AutoInvert, "#0a0a0a", Some(Color::WHITE)), [(10, 10, 10)]);
+    }
+
+    #[test]
+    fn only_paint_attributes_are_colors() {
+        let text = r##"<use xlink:href="#abcdef"/><path id="#123456" fill="#ff0000" stroke="#00ff00"/><stop stop-color="#0000ff"/><feFlood flood-color="#ffffff"/>"##;
+        let colors: Vec<Rgb> = hex_colors(text).into_iter().map(|(_, c)| c).collect();
+        assert_eq!(colors, [RED, (0, 255, 0), (0, 0, 255), (255, 255, 255)]);
+    }
+
+    #[test]
+    fn dominant_prefers_colorful_pixels() {
+        // 黑色最多，但红色超过一成时取红色
+        let mut colors = vec![((0, 0, 0), 255); 8];
+        colors.extend([(RED, 255), (RED, 255), ((0, 255, 0), 0)]);
+        let raster = Source::Raster(pixmap(&colors));
+        assert_eq!(dominant(&raster).map(rgb), Some(RED));
+
+        let gray = Source::Raster(pixmap(&[((128, 128, 128), 255), ((0, 0, 0), 0)]));
+        assert_eq!(dominant(&gray).map(rgb), Some((128, 128, 128)));
+        assert_eq!(dominant(&Source::Raster(pixmap(&[((0, 0, 0), 0)]))), None);
+
+        let vector = Source::Vector(Box::new(tree("#3178c6")));
+        assert_eq!(dominant(&vector).map(rgb), Some((0x31, 0x78, 0xc6)));
+    }
+}
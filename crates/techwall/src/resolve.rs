@@ -0,0 +1,261 @@
+//! 把技术名解析成具体的图标文件，例如 "docker" 在小方格里用 "docker-icon"，横幅里用 "docker"
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::{Catalog, Entry, VariantKind};
+use crate::meta::MetaData;
+use crate::search::{search, MatchKind};
+
+// 宽高比超过该值视为横向图标，通常带文字
+const WIDE: f32 = 1.6;
+
+/// 图形还是文字
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mark {
+    #[default]
+    Any,
+    /// 只有图形
+    Symbol,
+    /// 带文字
+    Wordmark,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Tone {
+    #[default]
+    Any,
+    Color,
+    Mono,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Shape {
+    #[default]
+    Any,
+    Square,
+    Wide,
+}
+
+/// 变体偏好，每个 banner 可以单独设置
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Preference {
+    #[serde(default)]
+    pub mark: Mark,
+    #[serde(default)]
+    pub tone: Tone,
+    #[serde(default)]
+    pub shape: Shape,
+    /// 技术名 -> 图标文件，优先于其他规则
+    #[serde(default)]
+    pub overrides: BTreeMap<String, String>,
+}
+
+impl Preference {
+    /// 逗号分隔的规则，例如 "symbol,mono,square"
+    pub fn parse(rules: &str) -> Option<Preference> {
+        let mut preference = Preference::default();
+        for rule in rules.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            match rule {
+                "symbol" | "icon" => preference.mark = Mark::Symbol,
+                "wordmark" | "text" => preference.mark = Mark::Wordmark,
+                "color" => preference.tone = Tone::Color,
+                "mono" => preference.tone = Tone::Mono,
+                "square" => preference.shape = Shape::Square,
+                "wide" => preference.shape = Shape::Wide,
+                _ => return None,
+            }
+        }
+        Some(preference)
+    }
+}
+
+pub struct Resolver<'a> {
+    catalog: &'a Catalog,
+    meta: &'a MetaData,
+    pub preference: Preference,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(catalog: &'a Catalog, meta: &'a MetaData) -> Resolver<'a> {
+        Resolver {
+            catalog,
+            meta,
+            preference: Preference::default(),
+        }
+    }
+
+    /// 技术名、图标文件名或别名对应的目录条目
+    pub fn entry(&self, tech: &str) -> Option<&'a Entry> {
+        if let Some(entry) = self.catalog.get(tech).or_else(|| self.catalog.by_icon(tech)) {
+            return Some(entry);
+        }
+        let hit = search(self.catalog, tech, 1).into_iter().next()?;
+        if !matches!(hit.kind, MatchKind::Exact | MatchKind::Alias) {
+            return None;
+        }
+        self.catalog.get(hit.name)
+    }
+
+    /// 按偏好选出图标文件，目录中没有时原样返回已有的图标
+    pub fn resolve(&self, tech: &str) -> Option<&'a str> {
+        if let Some(icon) = self.override_for(tech) {
+            return Some(icon);
+        }
+        let Some(entry) = self.entry(tech) else {
+            return self.meta.find(tech).map(|m| m.name.as_str());
+        };
+        if let Some(icon) = self.override_for(&entry.name) {
+            return Some(icon);
+        }
+        let mut best: Option<(f32, &'a str)> = None;
+        for variant in &entry.variants {
+            let score = match self.meta.find(&variant.name) {
+                Some(meta) => self.score(variant.kind, meta.width as f32 / meta.height.max(1) as f32, meta.mono),
+                // 没有栅格化的变体无法使用
+                None if !self.meta.items.is_empty() => continue,
+                None => self.score(variant.kind, 1.0, None),
+            };
+            if best.is_none_or(|(s, _)| score > s) {
+                best = Some((score, &variant.name));
+            }
+        }
+        best.map(|(_, name)| name)
+    }
+
+    /// 解析 `tech` 时会比较的图标文件，用于只给这些图标补算单色标记
+    pub fn candidates(&self, tech: &str) -> Vec<&'a str> {
+        let mut rs: Vec<&'a str> = self.override_for(tech).into_iter().collect();
+        match self.entry(tech) {
+            Some(entry) => {
+                rs.extend(self.override_for(&entry.name));
+                rs.extend(entry.variants.iter().map(|v| v.name.as_str()));
+            }
+            None => rs.extend(self.meta.find(tech).map(|m| m.name.as_str())),
+        }
+        rs.sort_unstable();
+        rs.dedup();
+        rs
+    }
+
+    fn override_for(&self, tech: &str) -> Option<&'a str> {
+        let icon = self.preference.overrides.get(tech)?;
+        // 覆盖的图标也要在 meta 中
+        self.meta.find(icon).map(|m| m.name.as_str())
+    }
+
+    fn score(&self, kind: VariantKind, aspect: f32, mono: Option<bool>) -> f32 {
+        let wide = aspect >= WIDE;
+        let mut score = match (self.preference.mark, kind) {
+            (Mark::Any, _) => 0.0,
+            (Mark::Symbol, VariantKind::Icon) => 2.0,
+            (Mark::Symbol, VariantKind::Default) if !wide => 1.0,
+            (Mark::Symbol, VariantKind::Logo) => 0.5,
+            (Mark::Symbol, VariantKind::Wordmark) => -2.0,
+            (Mark::Wordmark, VariantKind::Wordmark) => 2.0,
+            (Mark::Wordmark, VariantKind::Logo) => 1.0,
+            (Mark::Wordmark, VariantKind::Default) if wide => 1.0,
+            (Mark::Wordmark, VariantKind::Icon) => -2.0,
+            _ => 0.0,
+        };
+        score += match (self.preference.tone, mono) {
+            (Tone::Any, _) | (_, None) => 0.0,
+            (Tone::Mono, Some(true)) | (Tone::Color, Some(false)) => 1.0,
+            _ => -1.0,
+        };
+        score += match self.preference.shape {
+            Shape::Any => 0.0,
+            Shape::Square => -aspect.ln().abs(),
+            Shape::Wide => aspect.ln().clamp(-1.0, 1.0),
+        };
+        // 同分时用与技术同名的文件
+        if kind == VariantKind::Default {
+            score += 0.01;
+        }
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::Meta;
+
+    fn meta(name: &str, width: u32, height: u32, mono: Option<bool>) -> Meta {
+        Meta {
+            name: name.to_string(),
+            width,
+            height,
+            scale: 1.0,
+            polygon: Vec::new(),
+            mono,
+            svg: None,
+        }
+    }
+
+    fn meta_data() -> MetaData {
+        let list = r#"[{"name":"docker","items":["docker","docker-icon","docker-wordmark"]},
+            {"name":"angular","items":["angular","angular-icon"]}]"#;
+        MetaData {
+            items: vec![
+                meta("docker", 200, 60, Some(false)),
+                meta("docker-icon", 60, 50, Some(false)),
+                meta("docker-wordmark", 240, 50, Some(true)),
+                meta("angular", 100, 100, Some(false)),
+                meta("angular-icon", 100, 100, Some(true)),
+                meta("custom", 100, 100, None),
+            ],
+            catalog: Catalog::from_list_json(list).unwrap(),
+        }
+    }
+
+    fn resolve(data: &MetaData, rules: &str, tech: &str) -> Option<String> {
+        let mut resolver = Resolver::new(&data.catalog, data);
+        resolver.preference = Preference::parse(rules).unwrap();
+        resolver.resolve(tech).map(str::to_string)
+    }
+
+    #[test]
+    fn parse() {
+        let preference = Preference::parse("symbol, mono,wide").unwrap();
+        assert_eq!((preference.mark, preference.tone, preference.shape), (Mark::Symbol, Tone::Mono, Shape::Wide));
+        assert!(Preference::parse("").is_some());
+        assert!(Preference::parse("symbol,shiny").is_none());
+    }
+
+    #[test]
+    fn variant_scoring() {
+        let data = meta_data();
+        // 没有偏好时用与技术同名的文件
+        assert_eq!(resolve(&data, "", "docker").as_deref(), Some("docker"));
+        assert_eq!(resolve(&data, "symbol", "docker").as_deref(), Some("docker-icon"));
+        assert_eq!(resolve(&data, "wordmark", "docker").as_deref(), Some("docker-wordmark"));
+        assert_eq!(resolve(&data, "square", "docker").as_deref(), Some("docker-icon"));
+        // 都足够宽时同分，用同名文件
+        assert_eq!(resolve(&data, "wide", "docker").as_deref(), Some("docker"));
+        assert_eq!(resolve(&data, "wide,mono", "docker").as_deref(), Some("docker-wordmark"));
+        assert_eq!(resolve(&data, "mono", "angular").as_deref(), Some("angular-icon"));
+        assert_eq!(resolve(&data, "color", "angular").as_deref(), Some("angular"));
+        // 图标文件名也能解析，目录外的图标原样返回
+        assert_eq!(resolve(&data, "symbol", "docker-wordmark").as_deref(), Some("docker-icon"));
+        assert_eq!(resolve(&data, "symbol", "custom").as_deref(), Some("custom"));
+        assert_eq!(resolve(&data, "", "missing"), None);
+    }
+
+    #[test]
+    fn overrides_win() {
+        let data = meta_data();
+        let mut resolver = Resolver::new(&data.catalog, &data);
+        resolver.preference = Preference::parse("symbol").unwrap();
+        resolver.preference.overrides.insert("docker".to_string(), "docker-wordmark".to_string());
+        resolver.preference.overrides.insert("angular".to_string(), "angular-missing".to_string());
+        assert_eq!(resolver.resolve("docker"), Some("docker-wordmark"));
+        assert_eq!(resolver.resolve("docker-icon"), Some("docker-wordmark"));
+        // 覆盖的图标不存在时按规则选
+        assert_eq!(resolver.resolve("angular"), Some("angular-icon"));
+        assert_eq!(resolver.candidates("docker"), ["docker", "docker-icon", "docker-wordmark"]);
+    }
+}
@@ -0,0 +1,228 @@
+use serde::Serialize;
+
+use crate::catalog::{Catalog, Category, Entry};
+
+/// 匹配方式，越靠前质量越高
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    Exact,
+    Alias,
+    Prefix,
+    Contains,
+    Fuzzy,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Hit<'a> {
+    pub name: &'a str,
+    pub display: &'a str,
+    pub category: Category,
+    pub score: u64,
+    /// 建议使用的图标文件
+    pub icon: &'a str,
+    pub kind: MatchKind,
+    /// 匹配质量，0 到 1
+    pub quality: f32,
+}
+
+// 小写并去掉分隔符："Next.js" -> "nextjs"，"c#" 保持不变
+fn normalize(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_' | '.' | ' '))
+        .collect()
+}
+
+// 受限的 Damerau-Levenshtein 距离，允许相邻字符交换
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let (n, m) = (a.len(), b.len());
+    let mut d = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[n][m]
+}
+
+// 单个候选词的匹配结果
+fn match_term(query: &str, term: &str, alias: bool) -> Option<(MatchKind, f32)> {
+    let term = normalize(term);
+    if term.is_empty() {
+        return None;
+    }
+    if term == query {
+        return Some(if alias {
+            (MatchKind::Alias, 0.95)
+        } else {
+            (MatchKind::Exact, 1.0)
+        });
+    }
+    if term.starts_with(query) {
+        // 前缀越完整分数越高
+        let ratio = query.chars().count() as f32 / term.chars().count() as f32;
+        return Some((MatchKind::Prefix, 0.6 + 0.2 * ratio));
+    }
+    if query.chars().count() >= 2 && term.contains(query) {
+        let ratio = query.chars().count() as f32 / term.chars().count() as f32;
+        return Some((MatchKind::Contains, 0.4 + 0.2 * ratio));
+    }
+    let q: Vec<char> = query.chars().collect();
+    let t: Vec<char> = term.chars().collect();
+    if q.len() < 3 {
+        return None;
+    }
+    let limit = (q.len().max(t.len()) / 4).max(1);
+    let distance = edit_distance(&q, &t);
+    if distance <= limit {
+        return Some((MatchKind::Fuzzy, 0.4 - 0.1 * distance as f32));
+    }
+    None
+}
+
+fn match_entry<'a>(query: &str, entry: &'a Entry) -> Option<Hit<'a>> {
+    let mut best: Option<(MatchKind, f32, &'a str)> = None;
+    let primary = entry.primary().unwrap_or(&entry.name);
+    let mut consider = |rs: Option<(MatchKind, f32)>, icon: &'a str| {
+        if let Some((kind, quality)) = rs {
+            if best.is_none_or(|(_, q, _)| quality > q) {
+                best = Some((kind, quality, icon));
+            }
+        }
+    };
+    consider(match_term(query, &entry.name, false), primary);
+    consider(match_term(query, &entry.display, false), primary);
+    for alias in &entry.aliases {
+        consider(match_term(query, alias, true), primary);
+    }
+    for variant in &entry.variants {
+        // 直接命中变体文件名时返回该变体
+        let rs = match_term(query, &variant.name, false).map(|(k, q)| (k, q - 0.01));
+        consider(rs, &variant.name);
+    }
+    let (kind, quality, icon) = best?;
+    Some(Hit {
+        name: &entry.name,
+        display: &entry.display,
+        category: entry.category,
+        score: entry.score,
+        icon,
+        kind,
+        quality,
+    })
+}
+
+// 热度加成，最多 0.1，让同等质量的匹配里热门的靠前。
+// 优先用清单里归一化的热度，没有时按目录分数的对数估计
+fn popularity(entry: &Entry, max: u64) -> f32 {
+    if entry.popularity > 0.0 {
+        return 0.1 * entry.popularity.min(1.0);
+    }
+    if max == 0 {
+        return 0.0;
+    }
+    0.1 * ((entry.score as f32).ln_1p() / (max as f32).ln_1p())
+}
+
+/// 模糊搜索目录，支持别名、显示名、变体名和拼写错误。
+/// 结果按匹配质量加热度排序
+pub fn search<'a>(catalog: &'a Catalog, query: &str, limit: usize) -> Vec<Hit<'a>> {
+    let query = normalize(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let max = catalog.entries.iter().map(|e| e.score).max().unwrap_or(0);
+    let mut hits: Vec<(f32, Hit)> = catalog
+        .entries
+        .iter()
+        .filter_map(|e| match_entry(&query, e).map(|hit| (hit.quality + popularity(e, max), hit)))
+        .collect();
+    hits.sort_by(|(ra, a), (rb, b)| rb.total_cmp(ra).then_with(|| a.name.cmp(b.name)));
+    hits.into_iter().take(limit).map(|(_, hit)| hit).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{Variant, VariantKind};
+
+    fn entry(name: &str, display: &str, category: Category, aliases: &[&str], score: u64) -> Entry {
+        Entry {
+            name: name.to_string(),
+            display: display.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            category,
+            score,
+            popularity: 0.0,
+            variants: vec![Variant {
+                name: name.to_string(),
+                kind: VariantKind::Default,
+            }],
+        }
+    }
+
+    fn catalog() -> Catalog {
+        Catalog {
+            entries: vec![
+                entry("javascript", "JavaScript", Category::Language, &["js"], 500),
+                entry("java", "Java", Category::Language, &[], 100),
+                entry("redux", "Redux", Category::Framework, &[], 1000),
+                entry("redis", "Redis", Category::Database, &[], 1),
+                entry("nextjs", "Next.js", Category::Framework, &[], 10),
+            ],
+        }
+    }
+
+    fn names<'a>(hits: &[Hit<'a>]) -> Vec<&'a str> {
+        hits.iter().map(|h| h.name).collect()
+    }
+
+    #[test]
+    fn distance() {
+        let d = |a: &str, b: &str| edit_distance(&a.chars().collect::<Vec<_>>(), &b.chars().collect::<Vec<_>>());
+        assert_eq!(d("kitten", "sitting"), 3);
+        assert_eq!(d("", "abc"), 3);
+        // 相邻字符交换算一次
+        assert_eq!(d("pyhton", "python"), 1);
+    }
+
+    #[test]
+    fn ranking() {
+        let catalog = catalog();
+        let hits = search(&catalog, "Java", 10);
+        assert_eq!(names(&hits), ["java", "javascript"]);
+        assert_eq!((hits[0].kind, hits[1].kind), (MatchKind::Exact, MatchKind::Prefix));
+
+        assert_eq!(search(&catalog, "js", 10)[0].kind, MatchKind::Alias);
+        assert_eq!(names(&search(&catalog, "Next.js", 10)), ["nextjs"]);
+        let typo = search(&catalog, "javsacript", 10);
+        assert_eq!((typo[0].name, typo[0].kind), ("javascript", MatchKind::Fuzzy));
+        // 匹配质量相同时热门的靠前
+        assert_eq!(names(&search(&catalog, "red", 10)), ["redux", "redis"]);
+        assert_eq!(names(&search(&catalog, "red", 1)), ["redux"]);
+        assert!(search(&catalog, " ", 10).is_empty());
+    }
+
+    #[test]
+    fn popularity_beats_raw_score() {
+        let mut catalog = catalog();
+        // 清单里的热度比目录分数更准，有热度时按热度排
+        catalog.entries[3].popularity = 0.9;
+        catalog.entries[2].popularity = 0.2;
+        assert_eq!(names(&search(&catalog, "red", 10)), ["redis", "redux"]);
+    }
+}
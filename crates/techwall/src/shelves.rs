@@ -0,0 +1,361 @@
+//! 分类书架：按目录分类（语言、框架、数据库……）分成上下排列的横向分区，每个分区上方有标题，
+//! 分区内的图标紧密排列或落下堆积在各自的分区底部
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use usvg::fontdb;
+
+use crate::align::{re_align, Position};
+use crate::catalog::Category;
+use crate::compose::{Layer, Source};
+use crate::frame::{Frame, Insets};
+use crate::overlay::{paint_attrs, Align, Outline, Text};
+use crate::physics::settle;
+use crate::png::svg_options;
+
+// 估算分区高度时，图标面积占分区面积的比例
+const FILL: f32 = 0.6;
+// 分区放不下时整体缩小的次数
+const SHRINK_STEPS: usize = 4;
+const SHRINK: f32 = 0.9;
+
+/// 分区内的排布方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShelfPack {
+    /// 按行扫描紧密排列
+    #[default]
+    Grid,
+    /// 落下堆积在分区底部
+    Pile,
+}
+
+impl ShelfPack {
+    pub fn parse(name: &str) -> Option<ShelfPack> {
+        match name {
+            "grid" => Some(ShelfPack::Grid),
+            "pile" => Some(ShelfPack::Pile),
+            _ => None,
+        }
+    }
+}
+
+fn default_size() -> f32 {
+    20.0
+}
+
+fn default_weight() -> u16 {
+    700
+}
+
+fn default_color() -> String {
+    "#000000".to_string()
+}
+
+fn default_gap() -> f32 {
+    12.0
+}
+
+/// 书架的排布方式和标题样式
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Shelves {
+    #[serde(default)]
+    pub pack: ShelfPack,
+    /// 标题字号，0 为不显示标题
+    #[serde(default = "default_size")]
+    pub size: f32,
+    #[serde(default = "default_weight")]
+    pub weight: u16,
+    #[serde(default = "default_color")]
+    pub color: String,
+    #[serde(default)]
+    pub family: Option<String>,
+    /// 分区之间的间距
+    #[serde(default = "default_gap")]
+    pub gap: f32,
+    /// 标题下方的分隔线
+    #[serde(default)]
+    pub divider: Option<Outline>,
+}
+
+impl Default for Shelves {
+    fn default() -> Self {
+        Shelves {
+            pack: ShelfPack::Grid,
+            size: default_size(),
+            weight: default_weight(),
+            color: default_color(),
+            family: None,
+            gap: default_gap(),
+            divider: None,
+        }
+    }
+}
+
+/// 一个分区：标题、标题的位置和放图标的区域
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shelf {
+    pub title: String,
+    /// 标题左上角
+    pub heading: (f32, f32),
+    /// 放图标的区域 (min_x, min_y, max_x, max_y)
+    pub area: (u32, u32, u32, u32),
+    /// 分区内的图标下标，保持原来的顺序
+    pub members: Vec<usize>,
+}
+
+impl Shelf {
+    /// 只包含分区区域的画框，保留区域不变
+    pub fn frame(&self, frame: &Frame, width: u32, height: u32) -> Frame {
+        let (x0, y0, x1, y1) = self.area;
+        Frame {
+            insets: Insets {
+                top: y0,
+                right: width.saturating_sub(x1),
+                bottom: height.saturating_sub(y1),
+                left: x0,
+            },
+            radius: 0,
+            keep_out: frame.keep_out.clone(),
+        }
+    }
+}
+
+// 目录分类的标题
+fn title(category: Category) -> &'static str {
+    match category {
+        Category::Language => "Languages",
+        Category::Framework => "Frameworks",
+        Category::Database => "Databases",
+        Category::Cloud => "Cloud & DevOps",
+        Category::Tool => "Tools",
+        Category::Other => "Other",
+    }
+}
+
+impl Shelves {
+    fn heading_height(&self) -> f32 {
+        if self.size > 0.0 {
+            self.size * 1.25 + self.divider.as_ref().map_or(0.0, |d| d.width.max(0.0) + 4.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// 按分组把图标分到各个分区。分组为目录分类名时按目录的顺序排列并使用对应的标题，
+    /// 其余分组按出现的顺序排在后面，没有分组的图标归入 "other"。
+    /// 分区高度按图标的面积分配
+    pub fn shelves(&self, items: &[Position], groups: &[Option<&str>], area: (u32, u32, u32, u32)) -> Vec<Shelf> {
+        let mut names: Vec<&str> = Vec::new();
+        for i in 0..items.len() {
+            let name = groups.get(i).copied().flatten().unwrap_or(Category::Other.name());
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        let rank = |name: &str| Category::ALL.iter().position(|c| c.name() == name).unwrap_or(Category::ALL.len());
+        // 稳定排序，非目录分类保持出现的顺序
+        names.sort_by_key(|n| rank(n));
+
+        let (x0, y0, x1, y1) = area;
+        let width = (x1 - x0).max(1) as f32;
+        let members: Vec<Vec<usize>> = names
+            .iter()
+            .map(|name| {
+                (0..items.len())
+                    .filter(|&i| groups.get(i).copied().flatten().unwrap_or(Category::Other.name()) == *name)
+                    .collect()
+            })
+            .collect();
+        // 每个分区需要的高度：按面积估算，至少放得下最高的图标
+        let needs: Vec<f32> = members
+            .iter()
+            .map(|m| {
+                let area: f32 = m
+                    .iter()
+                    .map(|&i| {
+                        let (w, h) = items[i].icon.scaled_size();
+                        (w * h) as f32
+                    })
+                    .sum();
+                let tallest = m.iter().map(|&i| items[i].icon.scaled_size().1).max().unwrap_or(0) as f32;
+                (area / width / FILL).max(tallest)
+            })
+            .collect();
+        let count = names.len() as f32;
+        let heading = self.heading_height();
+        let room = ((y1 - y0) as f32 - heading * count - self.gap.max(0.0) * (count - 1.0).max(0.0)).max(0.0);
+        let total: f32 = needs.iter().sum();
+
+        let mut top = y0 as f32;
+        names
+            .into_iter()
+            .zip(members)
+            .zip(needs)
+            .map(|((name, members), need)| {
+                let band = if total > 0.0 { room * need / total } else { 0.0 };
+                let title = Category::parse(name).map_or(name.to_string(), |c| title(c).to_string());
+                let start = top + heading;
+                let shelf = Shelf {
+                    title,
+                    heading: (x0 as f32, top),
+                    area: (x0, start.round() as u32, x1, ((start + band).round() as u32).min(y1)),
+                    members,
+                };
+                top = start + band + self.gap.max(0.0);
+                shelf
+            })
+            .collect()
+    }
+
+    /// 在分区的画框 (`Shelf::frame`) 内排布分区的图标，放不下时所有图标一起缩小后重试，
+    /// 返回仍然放不下的图标下标
+    pub fn pack(&self, items: &mut [Position], width: u32, height: u32, step: u32, seed: u64, frame: &Frame) -> Vec<usize> {
+        let mut overflow = Vec::new();
+        for attempt in 0..=SHRINK_STEPS {
+            if attempt > 0 {
+                for item in items.iter_mut() {
+                    item.icon.scale *= SHRINK;
+                }
+            }
+            overflow = match self.pack {
+                ShelfPack::Grid => re_align(items, width, height, step, frame),
+                ShelfPack::Pile => settle(items, width, height, seed, frame, None),
+            };
+            if overflow.is_empty() {
+                break;
+            }
+        }
+        overflow
+    }
+
+    /// 分区的标题，字号为 0 时返回 None
+    pub fn heading_layer(&self, shelf: &Shelf, fonts: &Arc<fontdb::Database>) -> Result<Option<Layer>> {
+        if self.size <= 0.0 {
+            return Ok(None);
+        }
+        let text = Text {
+            text: shelf.title.clone(),
+            x: shelf.heading.0,
+            y: shelf.heading.1,
+            width: None,
+            size: self.size,
+            family: self.family.clone(),
+            weight: self.weight,
+            color: self.color.clone(),
+            align: Align::Left,
+            line_height: 1.25,
+            shadow: None,
+            stroke: None,
+        };
+        text.layer(fonts).map(Some)
+    }
+
+    /// 所有分区标题下方的分隔线，画在一个矢量图层里
+    pub fn divider_layer(&self, shelves: &[Shelf], width: u32, height: u32) -> Result<Option<Layer>> {
+        let Some(divider) = self.divider.as_ref().filter(|d| d.width > 0.0 && self.size > 0.0) else {
+            return Ok(None);
+        };
+        let stroke = paint_attrs("stroke", &divider.color)?;
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height
+        )?;
+        for shelf in shelves {
+            let y = shelf.heading.1 + self.size * 1.25 + 2.0 + divider.width / 2.0;
+            write!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {} stroke-width="{}"/>"#,
+                shelf.area.0, y, shelf.area.2, y, stroke, divider.width
+            )?;
+        }
+        svg.push_str("</svg>");
+        let tree = usvg::Tree::from_str(&svg, &svg_options())?;
+        Ok(Some(Layer {
+            source: Arc::new(Source::Vector(Box::new(tree))),
+            x: 0.0,
+            y: 0.0,
+            width: width as f32,
+            height: height as f32,
+            clip: None,
+            rotation: 0.0,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::align::{bounds, polygons_overlap, Icon};
+
+    fn square(size: u32) -> Position {
+        Position::new(Icon::new(size, size, vec![(0, 0), (size, 0), (size, size), (0, size)], 1.0))
+    }
+
+    #[test]
+    fn groups_follow_catalog_order() {
+        let items: Vec<Position> = (0..5).map(|_| square(30)).collect();
+        let groups = [Some("framework"), Some("language"), None, Some("design"), Some("language")];
+        let shelves = Shelves::default();
+        // 区域很窄，两个图标的分区按面积需要更高
+        let rs = shelves.shelves(&items, &groups, (0, 0, 60, 400));
+        let titles: Vec<&str> = rs.iter().map(|s| s.title.as_str()).collect();
+        // 目录分类在前，其余分组按出现顺序排在后面
+        assert_eq!(titles, ["Languages", "Frameworks", "Other", "design"]);
+        let members: Vec<&[usize]> = rs.iter().map(|s| s.members.as_slice()).collect();
+        assert_eq!(members, [&[1, 4][..], &[0], &[2], &[3]]);
+        for pair in rs.windows(2) {
+            // 标题在分区上方，分区之间留出间距
+            assert!(pair[1].heading.1 >= pair[0].area.3 as f32 + shelves.gap - 1.0);
+            assert!(pair[1].area.1 as f32 >= pair[1].heading.1 + shelves.heading_height() - 1.0);
+        }
+        let height = |s: &Shelf| s.area.3 - s.area.1;
+        assert!(height(&rs[0]) > height(&rs[1]));
+    }
+
+    #[test]
+    fn packs_within_each_shelf() {
+        let items: Vec<Position> = (0..12).map(|i| square(if i % 2 == 0 { 40 } else { 24 })).collect();
+        let groups: Vec<Option<&str>> = (0..12).map(|i| Some(if i < 9 { "language" } else { "tool" })).collect();
+        let (width, height) = (300, 240);
+        let frame = Frame::default();
+        for pack in [ShelfPack::Grid, ShelfPack::Pile] {
+            let shelves = Shelves {
+                pack,
+                ..Shelves::default()
+            };
+            for shelf in shelves.shelves(&items, &groups, (0, 0, width, height)) {
+                let mut members: Vec<Position> = shelf.members.iter().map(|&i| items[i].clone()).collect();
+                let overflow = shelves.pack(&mut members, width, height, 4, 1, &shelf.frame(&frame, width, height));
+                assert!(overflow.is_empty(), "{:?} {}", pack, shelf.title);
+                let (x0, y0, x1, y1) = shelf.area;
+                for (i, a) in members.iter().enumerate() {
+                    let polygon = a.icon.scaled_polygon();
+                    let (ax0, ay0, ax1, ay1) = bounds(&polygon, (a.x, a.y));
+                    assert!(ax0 >= x0 && ay0 >= y0 && ax1 <= x1 && ay1 <= y1, "{:?} {}", pack, shelf.title);
+                    for b in &members[i + 1..] {
+                        assert!(!polygons_overlap(&polygon, &b.icon.scaled_polygon(), (a.x, a.y), (b.x, b.y)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shrinks_until_shelf_fits() {
+        let mut items: Vec<Position> = (0..4).map(|_| square(60)).collect();
+        let shelf = Shelf {
+            title: "Tools".to_string(),
+            heading: (0.0, 0.0),
+            area: (0, 0, 200, 50),
+            members: vec![0, 1, 2, 3],
+        };
+        let frame = shelf.frame(&Frame::default(), 200, 100);
+        assert!(Shelves::default().pack(&mut items, 200, 100, 2, 1, &frame).is_empty());
+        assert!(items.iter().all(|p| p.icon.scale < 1.0 && p.y + p.icon.scaled_size().1 <= 50));
+    }
+}
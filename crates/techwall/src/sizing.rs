@@ -0,0 +1,109 @@
+//! 按权重（语言占比、仓库数等）决定图标大小，主要技术更醒目
+use serde::{Deserialize, Serialize};
+
+/// 权重到大小的映射曲线
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase", tag = "kind")]
+pub enum Curve {
+    Linear,
+    /// 面积与权重成正比
+    Sqrt,
+    /// 压缩差距，适合仓库数这种跨度很大的值
+    Log,
+    /// 按排名分成若干档，与具体数值无关
+    Rank { buckets: u32 },
+}
+
+impl Curve {
+    /// "linear"、"sqrt"、"log"、"rank" 或 "rank:4"
+    pub fn parse(name: &str) -> Option<Curve> {
+        match name.split_once(':') {
+            Some(("rank", n)) => n.parse().ok().filter(|&b| b > 0).map(|buckets| Curve::Rank { buckets }),
+            Some(_) => None,
+            None => match name {
+                "linear" => Some(Curve::Linear),
+                "sqrt" => Some(Curve::Sqrt),
+                "log" => Some(Curve::Log),
+                "rank" => Some(Curve::Rank { buckets: 3 }),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Sizing {
+    pub curve: Curve,
+    /// 权重最小的图标的缩放
+    pub min: f32,
+    /// 权重最大的图标的缩放
+    pub max: f32,
+}
+
+impl Default for Sizing {
+    fn default() -> Self {
+        Sizing {
+            curve: Curve::Sqrt,
+            min: 0.75,
+            max: 2.5,
+        }
+    }
+}
+
+impl Sizing {
+    pub fn new(curve: Curve) -> Sizing {
+        Sizing {
+            curve,
+            ..Sizing::default()
+        }
+    }
+
+    /// 每个权重对应的缩放，负数按 0 处理
+    pub fn scales(&self, weights: &[f32]) -> Vec<f32> {
+        let weights: Vec<f32> = weights.iter().map(|w| w.max(0.0)).collect();
+        let max = weights.iter().copied().fold(0.0, f32::max);
+        let t: Vec<f32> = match self.curve {
+            _ if max <= 0.0 => vec![1.0; weights.len()],
+            Curve::Linear => weights.iter().map(|w| w / max).collect(),
+            Curve::Sqrt => weights.iter().map(|w| (w / max).sqrt()).collect(),
+            // 最大值映射到 1，最大值的 1/10 映射到约 0.3
+            Curve::Log => weights.iter().map(|w| (9.0 * w / max).ln_1p() / 10f32.ln()).collect(),
+            Curve::Rank { buckets } => rank_levels(&weights, buckets),
+        };
+        t.into_iter().map(|t| self.min + (self.max - self.min) * t).collect()
+    }
+}
+
+// 按名次把 n 个图标平均分到 `buckets` 档，第 k 档 (从 0 开始) 的值为 1 - k / (buckets - 1)。
+// 权重相同的取并列的最高名次；图标比档数少时档位不连续，排名最后的图标不一定为 0
+fn rank_levels(weights: &[f32], buckets: u32) -> Vec<f32> {
+    if buckets <= 1 {
+        return vec![1.0; weights.len()];
+    }
+    let mut order: Vec<usize> = (0..weights.len()).collect();
+    order.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]));
+    let n = weights.len();
+    let mut levels = vec![0.0; n];
+    let mut rank = 0;
+    for (i, &index) in order.iter().enumerate() {
+        if i > 0 && weights[index] < weights[order[i - 1]] {
+            rank = i;
+        }
+        let bucket = (rank * buckets as usize / n) as f32;
+        levels[index] = 1.0 - bucket / (buckets - 1) as f32;
+    }
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_levels_share_ties() {
+        assert_eq!(rank_levels(&[4.0, 3.0, 2.0, 1.0], 2), [1.0, 1.0, 0.0, 0.0]);
+        assert_eq!(rank_levels(&[1.0, 5.0, 5.0], 3), [0.0, 1.0, 1.0]);
+        // 图标少于档数时跳过中间的档
+        assert_eq!(rank_levels(&[2.0, 1.0], 5), [1.0, 0.5]);
+    }
+}
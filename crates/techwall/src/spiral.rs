@@ -0,0 +1,213 @@
+//! 螺旋排布：每个图标从画框中心出发，沿阿基米德螺线向外移动，放在第一个不重叠的位置，
+//! 先放的图标在中间，得到标签云的效果。已放置的多边形登记在均匀网格里，碰撞检测只检查附近的格子
+use crate::align::{bounds, polygons_overlap, Polygon, Position};
+use crate::frame::Frame;
+
+// 网格格子的边长
+const CELL: u32 = 48;
+
+/// 均匀网格空间索引，每个格子记录与它相交的多边形
+pub struct SpatialIndex {
+    cell: u32,
+    cols: u32,
+    rows: u32,
+    buckets: Vec<Vec<usize>>,
+    items: Vec<(Polygon, (u32, u32))>,
+}
+
+impl SpatialIndex {
+    pub fn new(width: u32, height: u32, cell: u32) -> SpatialIndex {
+        let cell = cell.max(1);
+        let cols = width.div_ceil(cell).max(1);
+        let rows = height.div_ceil(cell).max(1);
+        SpatialIndex {
+            cell,
+            cols,
+            rows,
+            buckets: vec![Vec::new(); (cols * rows) as usize],
+            items: Vec::new(),
+        }
+    }
+
+    // 外接矩形覆盖的格子，超出画布的部分归到边缘的格子
+    fn cells(&self, polygon: &Polygon, offset: (u32, u32)) -> impl Iterator<Item = usize> + '_ {
+        let (x0, y0, x1, y1) = bounds(polygon, offset);
+        let (c0, c1) = ((x0 / self.cell).min(self.cols - 1), (x1 / self.cell).min(self.cols - 1));
+        let (r0, r1) = ((y0 / self.cell).min(self.rows - 1), (y1 / self.cell).min(self.rows - 1));
+        (r0..=r1).flat_map(move |r| (c0..=c1).map(move |c| (r * self.cols + c) as usize))
+    }
+
+    pub fn insert(&mut self, polygon: Polygon, offset: (u32, u32)) {
+        if polygon.is_empty() {
+            return;
+        }
+        let index = self.items.len();
+        let cells: Vec<usize> = self.cells(&polygon, offset).collect();
+        for cell in cells {
+            self.buckets[cell].push(index);
+        }
+        self.items.push((polygon, offset));
+    }
+
+    /// 是否与已登记的多边形重叠
+    pub fn overlaps(&self, polygon: &Polygon, offset: (u32, u32)) -> bool {
+        if polygon.is_empty() {
+            return false;
+        }
+        let mut candidates: Vec<usize> = self
+            .cells(polygon, offset)
+            .flat_map(|c| self.buckets[c].iter().copied())
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates.into_iter().any(|i| {
+            let (p, o) = &self.items[i];
+            polygons_overlap(polygon, p, offset, *o)
+        })
+    }
+}
+
+/// 按顺序把图标放在螺线上第一个不重叠的位置，螺线按画框的长宽比拉伸，每圈向外扩展 `step`。
+/// 返回放不下的图标下标
+pub fn spiral(items: &mut [Position], width: u32, height: u32, step: u32, frame: &Frame) -> Vec<usize> {
+    let step = step.max(1) as f32;
+    let (x0, y0, x1, y1) = frame.area(width, height);
+    let (aw, ah) = ((x1 - x0) as f32, (y1 - y0) as f32);
+    let (cx, cy) = (x0 as f32 + aw / 2.0, y0 as f32 + ah / 2.0);
+    // 横向的拉伸比例，螺线的形状与画框相似
+    let ratio = if ah > 0.0 { (aw / ah).max(1.0) } else { 1.0 };
+    let squash = if aw > 0.0 { (ah / aw).max(1.0) } else { 1.0 };
+    let mut index = SpatialIndex::new(width, height, CELL);
+    for (polygon, offset) in frame.obstacles(width, height) {
+        index.insert(polygon, offset);
+    }
+    let mut overflow = Vec::new();
+
+    for (i, item) in items.iter_mut().enumerate() {
+        let polygon = item.icon.scaled_polygon();
+        let (w, h) = item.icon.scaled_size();
+        if w > x1 - x0 || h > y1 - y0 {
+            overflow.push(i);
+            continue;
+        }
+        // 图标中心走到画框外接圆以外时停止
+        let limit = (aw / ratio).hypot(ah / squash) / 2.0 + w.max(h) as f32;
+        let (mut r, mut t) = (0.0f32, 0.0f32);
+        let mut found = None;
+        while r <= limit {
+            let x = cx + r * ratio * t.cos() - w as f32 / 2.0;
+            let y = cy + r * squash * t.sin() - h as f32 / 2.0;
+            if x >= x0 as f32 && y >= y0 as f32 && x + w as f32 <= x1 as f32 && y + h as f32 <= y1 as f32 {
+                let offset = (x.round() as u32, y.round() as u32);
+                if !index.overlaps(&polygon, offset) {
+                    found = Some(offset);
+                    break;
+                }
+            }
+            // 沿螺线每次前进约两倍 `step` 的弧长，r = step * t / 2π
+            let dt = 2.0 * step / (r * ratio.max(squash)).max(step);
+            t += dt;
+            r = step * t / std::f32::consts::TAU;
+        }
+        match found {
+            Some(offset) => {
+                (item.x, item.y) = offset;
+                index.insert(polygon, offset);
+            }
+            None => overflow.push(i),
+        }
+    }
+    overflow
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::align::Icon;
+    use crate::banner::{Arrange, Banner};
+    use crate::compose::Source;
+    use crate::frame::Region;
+    use crate::meta::Meta;
+
+    fn square(size: u32) -> Position {
+        Position::new(Icon::new(size, size, vec![(0, 0), (size, 0), (size, size), (0, size)], 1.0))
+    }
+
+    fn assert_apart(items: &[Position], placed: &[usize]) {
+        for (n, &a) in placed.iter().enumerate() {
+            for &b in &placed[n + 1..] {
+                let (p, q) = (&items[a], &items[b]);
+                assert!(
+                    !polygons_overlap(&p.icon.scaled_polygon(), &q.icon.scaled_polygon(), (p.x, p.y), (q.x, q.y)),
+                    "{} and {} overlap",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn placed_icons_do_not_overlap() {
+        let mut items: Vec<Position> = (0..12).map(|i| square(20 + i % 3 * 10)).collect();
+        assert!(spiral(&mut items, 300, 200, 4, &Frame::default()).is_empty());
+        assert_apart(&items, &(0..items.len()).collect::<Vec<_>>());
+        for item in &items {
+            let (w, h) = item.icon.scaled_size();
+            assert!(item.x + w <= 300 && item.y + h <= 200);
+        }
+    }
+
+    #[test]
+    fn overflow_keeps_original_indices() {
+        // 第二个比画框大，100x100 里最多放四个 40 的方块
+        let mut items = vec![square(40), square(150), square(40), square(40), square(40), square(40), square(40)];
+        let overflow = spiral(&mut items, 100, 100, 4, &Frame::default());
+        assert_eq!(overflow[0], 1);
+        assert!(overflow.len() >= 3);
+        assert!(overflow.windows(2).all(|w| w[0] < w[1]));
+        let placed: Vec<usize> = (0..items.len()).filter(|i| !overflow.contains(i)).collect();
+        assert_apart(&items, &placed);
+    }
+
+    #[test]
+    fn obstacles_are_avoided() {
+        let region = Region { x: 100, y: 50, width: 100, height: 100 };
+        let frame = Frame {
+            keep_out: vec![region],
+            ..Frame::default()
+        };
+        let mut items: Vec<Position> = (0..6).map(|_| square(30)).collect();
+        assert!(spiral(&mut items, 300, 200, 4, &frame).is_empty());
+        for item in &items {
+            let inside_x = item.x < region.x + region.width && item.x + 30 > region.x;
+            let inside_y = item.y < region.y + region.height && item.y + 30 > region.y;
+            assert!(!(inside_x && inside_y), "icon at {:?} inside keep-out", (item.x, item.y));
+        }
+    }
+
+    #[test]
+    fn heaviest_icon_in_centre() {
+        let mut banner = Banner::new(300, 200);
+        banner.arrange = Arrange::Spiral;
+        for (name, weight) in [("light", 1.0), ("heavy", 10.0), ("medium", 5.0)] {
+            let meta = Meta {
+                name: name.to_string(),
+                width: 40,
+                height: 40,
+                scale: 1.0,
+                polygon: [(0, 0), (40, 0), (40, 40), (0, 40)].map(Into::into).to_vec(),
+                mono: None,
+                svg: None,
+            };
+            let source = Source::Raster(resvg::tiny_skia::Pixmap::new(40, 40).unwrap());
+            banner.add(meta, source, 1.0);
+            banner.set_weight(name, weight);
+        }
+        let placements = banner.layout();
+        let heavy = placements.iter().find(|p| p.name == "heavy").unwrap();
+        assert_eq!((heavy.x, heavy.y), (130, 80));
+        // 其余图标绕着中心向外
+        assert!(placements.iter().all(|p| p.name == "heavy" || (p.x, p.y) != (130, 80)));
+    }
+}
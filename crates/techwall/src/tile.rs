@@ -0,0 +1,154 @@
+//! 无缝平铺：把画布看作环面，图标超出右边（下边）的部分从左边（上边）出来，
+//! 碰撞检测同时检查对边的副本，合成时打开 `Compositor::wrap`，输出的图片可以作为壁纸或网页背景重复平铺
+use crate::align::{Polygon, Position};
+use crate::rng::Rng;
+use crate::spiral::SpatialIndex;
+
+// 随机位置的尝试次数，第一轮之后仍放不下时按行扫描
+const TRIES: usize = 200;
+// 空间索引格子的边长
+const CELL: u32 = 48;
+// 循环使用图标时最多放置的数量
+const MAX_ITEMS: usize = 400;
+
+// 坐标整体平移一个画布，对边的副本不会出现负数坐标；图标在中间的一块里
+struct Torus {
+    width: u32,
+    height: u32,
+    index: SpatialIndex,
+}
+
+impl Torus {
+    fn new(width: u32, height: u32) -> Torus {
+        Torus {
+            width,
+            height,
+            index: SpatialIndex::new(width * 3, height * 3, CELL),
+        }
+    }
+
+    fn overlaps(&self, polygon: &Polygon, (x, y): (u32, u32)) -> bool {
+        self.index.overlaps(polygon, (x + self.width, y + self.height))
+    }
+
+    // 登记多边形和它在周围八块里的副本
+    fn insert(&mut self, polygon: &Polygon, (x, y): (u32, u32)) {
+        for dy in [0, self.height, self.height * 2] {
+            for dx in [0, self.width, self.width * 2] {
+                self.index.insert(polygon.clone(), (x + dx, y + dy));
+            }
+        }
+    }
+}
+
+/// 在 `width` x `height` 的环面上随机放置图标，左上角在画布内，其余部分可以跨过边缘。
+/// `obstacles` 为保留区域；`repeat` 为 true 时循环使用图标，直到一轮都放不下。
+/// 返回 (图标下标, 位置)，放不下的图标不出现在结果中
+pub fn tile(
+    items: &[Position],
+    width: u32,
+    height: u32,
+    step: u32,
+    seed: u64,
+    obstacles: &[(Polygon, (u32, u32))],
+    repeat: bool,
+) -> Vec<(usize, Position)> {
+    let step = step.max(1) as usize;
+    let mut rng = Rng::new(seed);
+    let mut torus = Torus::new(width, height);
+    for (polygon, offset) in obstacles {
+        torus.insert(polygon, *offset);
+    }
+    let mut rs = Vec::new();
+    let mut place = |index: usize, scan: bool, torus: &mut Torus, rs: &mut Vec<(usize, Position)>| -> bool {
+        let item = &items[index];
+        let polygon = item.icon.scaled_polygon();
+        let (w, h) = item.icon.scaled_size();
+        // 比画布大的图标会和自己的副本重叠
+        if w == 0 || h == 0 || w > width || h > height {
+            return false;
+        }
+        let found = (0..TRIES)
+            .map(|_| (rng.below(width), rng.below(height)))
+            .find(|&offset| !torus.overlaps(&polygon, offset));
+        let found = found.or_else(|| {
+            if !scan {
+                return None;
+            }
+            (0..height)
+                .step_by(step)
+                .flat_map(|y| (0..width).step_by(step).map(move |x| (x, y)))
+                .find(|&offset| !torus.overlaps(&polygon, offset))
+        });
+        let Some((x, y)) = found else {
+            return false;
+        };
+        torus.insert(&polygon, (x, y));
+        rs.push((
+            index,
+            Position {
+                x,
+                y,
+                icon: item.icon.clone(),
+            },
+        ));
+        true
+    };
+    for index in 0..items.len() {
+        place(index, true, &mut torus, &mut rs);
+    }
+    // 之后只随机尝试，一轮都放不下时说明已经填满
+    while repeat && !items.is_empty() && rs.len() < MAX_ITEMS {
+        let mut any = false;
+        for index in 0..items.len() {
+            if rs.len() >= MAX_ITEMS {
+                break;
+            }
+            any |= place(index, false, &mut torus, &mut rs);
+        }
+        if !any {
+            break;
+        }
+    }
+    rs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::align::{polygons_overlap, Icon};
+
+    fn square(size: u32) -> Polygon {
+        vec![(0, 0), (size, 0), (size, size), (0, size)]
+    }
+
+    #[test]
+    fn collisions_wrap_around_edges() {
+        let mut torus = Torus::new(100, 100);
+        // 右下角的图标从左上角出来
+        torus.insert(&square(20), (90, 90));
+        assert!(torus.overlaps(&square(20), (0, 0)));
+        assert!(torus.overlaps(&square(20), (85, 0)));
+        assert!(!torus.overlaps(&square(20), (20, 20)));
+        assert!(!torus.overlaps(&square(20), (50, 50)));
+    }
+
+    #[test]
+    fn tiles_do_not_overlap_across_edges() {
+        let items: Vec<Position> = (0..3).map(|_| Position::new(Icon::new(20, 20, square(20), 1.0))).collect();
+        let rs = tile(&items, 100, 80, 4, 9, &[], true);
+        assert!(rs.len() > items.len());
+        // 在 3 x 3 的画布上展开后两两比较
+        for (i, (_, a)) in rs.iter().enumerate() {
+            for (_, b) in &rs[i + 1..] {
+                for dy in [0, 80, 160] {
+                    for dx in [0, 100, 200] {
+                        let pa = (a.x + 100, a.y + 80);
+                        let pb = (b.x + dx, b.y + dy);
+                        assert!(!polygons_overlap(&square(20), &square(20), pa, pb), "{:?} {:?}", pa, pb);
+                    }
+                }
+            }
+        }
+    }
+}
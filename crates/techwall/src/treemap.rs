@@ -0,0 +1,356 @@
+//! 矩形树图：每个技术占一个格子，面积与权重成正比，适合展示代码量占比
+use std::fmt::Write;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use usvg::fontdb;
+
+use crate::compose::{Layer, Source};
+use crate::frame::Region;
+use crate::overlay::{paint_attrs, Align, Text};
+use crate::png::svg_options;
+
+// 文字小于这个字号时不显示
+const MIN_LABEL: f32 = 9.0;
+const MAX_LABEL: f32 = 28.0;
+
+/// 格子，画布坐标
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Cell {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Cell {
+    // 四周各缩进 `d`
+    fn inset(self, d: f32) -> Cell {
+        let dx = d.min(self.width / 2.0);
+        let dy = d.min(self.height / 2.0);
+        Cell {
+            x: self.x + dx,
+            y: self.y + dy,
+            width: self.width - dx * 2.0,
+            height: self.height - dy * 2.0,
+        }
+    }
+
+    fn intersects(&self, r: &Region) -> bool {
+        self.x < (r.x + r.width) as f32
+            && self.x + self.width > r.x as f32
+            && self.y < (r.y + r.height) as f32
+            && self.y + self.height > r.y as f32
+    }
+
+    /// 扣除保留区域后剩下的最大矩形，没有相交的区域时为自身
+    pub fn free(self, regions: &[Region]) -> Cell {
+        let mut candidates = vec![self];
+        for r in regions.iter().filter(|r| r.width > 0 && r.height > 0) {
+            let (rx0, ry0) = (r.x as f32, r.y as f32);
+            let (rx1, ry1) = ((r.x + r.width) as f32, (r.y + r.height) as f32);
+            candidates = candidates
+                .into_iter()
+                .flat_map(|c| {
+                    if !c.intersects(r) {
+                        return vec![c];
+                    }
+                    // 区域左、右、上、下四侧剩下的部分
+                    let (x1, y1) = (c.x + c.width, c.y + c.height);
+                    vec![
+                        Cell { width: rx0 - c.x, ..c },
+                        Cell { x: rx1, width: x1 - rx1, ..c },
+                        Cell { height: ry0 - c.y, ..c },
+                        Cell { y: ry1, height: y1 - ry1, ..c },
+                    ]
+                })
+                .filter(|c| c.width > 0.0 && c.height > 0.0)
+                .collect();
+        }
+        candidates
+            .into_iter()
+            .max_by(|a, b| (a.width * a.height).total_cmp(&(b.width * b.height)))
+            .unwrap_or(Cell { width: 0.0, height: 0.0, ..self })
+    }
+}
+
+fn default_gap() -> f32 {
+    4.0
+}
+
+fn default_padding() -> f32 {
+    0.12
+}
+
+fn default_color() -> String {
+    "#000000".to_string()
+}
+
+/// 树图的外观
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Treemap {
+    /// 格子之间的间距
+    #[serde(default = "default_gap")]
+    pub gap: f32,
+    /// 图标到格子边缘的距离，格子短边的比例
+    #[serde(default = "default_padding")]
+    pub padding: f32,
+    /// 格子的填充色，为空时不画格子
+    #[serde(default)]
+    pub fill: Option<String>,
+    #[serde(default)]
+    pub radius: f32,
+    /// 在格子底部显示名字
+    #[serde(default)]
+    pub label: bool,
+    /// 显示权重的百分比
+    #[serde(default)]
+    pub percent: bool,
+    /// 文字颜色
+    #[serde(default = "default_color")]
+    pub color: String,
+    #[serde(default)]
+    pub family: Option<String>,
+}
+
+impl Default for Treemap {
+    fn default() -> Self {
+        Treemap {
+            gap: default_gap(),
+            padding: default_padding(),
+            fill: None,
+            radius: 0.0,
+            label: false,
+            percent: false,
+            color: default_color(),
+            family: None,
+        }
+    }
+}
+
+/// 按权重把矩形切成接近正方形的格子（squarified treemap）。
+/// 返回的格子与 `weights` 一一对应，权重不大于 0 的格子为空
+pub fn squarify(weights: &[f32], area: Cell) -> Vec<Cell> {
+    let mut cells = vec![Cell::default(); weights.len()];
+    let total: f32 = weights.iter().filter(|&&w| w > 0.0).sum();
+    if total <= 0.0 || area.width <= 0.0 || area.height <= 0.0 {
+        return cells;
+    }
+    let mut order: Vec<usize> = (0..weights.len()).filter(|&i| weights[i] > 0.0).collect();
+    order.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]));
+    let unit = area.width * area.height / total;
+    let areas: Vec<f32> = order.iter().map(|&i| weights[i] * unit).collect();
+
+    let mut rest = area;
+    let mut start = 0;
+    while start < order.len() {
+        // 沿短边排一行，直到再加一个会让最差的长宽比变差
+        let side = rest.width.min(rest.height);
+        let mut end = start + 1;
+        while end < order.len() && worst(&areas[start..=end], side) <= worst(&areas[start..end], side) {
+            end += 1;
+        }
+        let sum: f32 = areas[start..end].iter().sum();
+        // 最后一行占满剩余区域，避免浮点误差留下缝隙
+        let last = end == order.len();
+        let mut offset = 0.0;
+        if rest.width >= rest.height {
+            let w = if last { rest.width } else { sum / rest.height };
+            for (k, &i) in order[start..end].iter().enumerate() {
+                let h = areas[start + k] / w;
+                cells[i] = Cell {
+                    x: rest.x,
+                    y: rest.y + offset,
+                    width: w,
+                    height: if last && end - start == k + 1 { rest.height - offset } else { h },
+                };
+                offset += h;
+            }
+            rest.x += w;
+            rest.width -= w;
+        } else {
+            let h = if last { rest.height } else { sum / rest.width };
+            for (k, &i) in order[start..end].iter().enumerate() {
+                let w = areas[start + k] / h;
+                cells[i] = Cell {
+                    x: rest.x + offset,
+                    y: rest.y,
+                    width: if last && end - start == k + 1 { rest.width - offset } else { w },
+                    height: h,
+                };
+                offset += w;
+            }
+            rest.y += h;
+            rest.height -= h;
+        }
+        start = end;
+    }
+    cells
+}
+
+// 一行格子中最差的长宽比
+fn worst(row: &[f32], side: f32) -> f32 {
+    let sum: f32 = row.iter().sum();
+    let max = row.iter().copied().fold(0.0, f32::max);
+    let min = row.iter().copied().fold(f32::INFINITY, f32::min);
+    let (s2, sum2) = (side * side, sum * sum);
+    (s2 * max / sum2).max(sum2 / (s2 * min))
+}
+
+impl Treemap {
+    /// 格子扣除间距后的区域
+    pub fn cell(&self, cell: Cell) -> Cell {
+        cell.inset(self.gap / 2.0)
+    }
+
+    fn label_size(&self, cell: Cell) -> f32 {
+        (cell.height * 0.14).min(MAX_LABEL)
+    }
+
+    /// 放图标的区域，扣除内边距和底部的文字
+    pub fn icon_area(&self, cell: Cell) -> Cell {
+        let cell = self.cell(cell);
+        let mut area = cell.inset(cell.width.min(cell.height) * self.padding.max(0.0));
+        if self.label || self.percent {
+            let size = self.label_size(cell);
+            if size >= MIN_LABEL {
+                area.height = (area.height - size * 1.25).max(0.0);
+            }
+        }
+        area
+    }
+
+    /// 所有格子画在一个矢量图层里，放在图标下面
+    pub fn cells_layer(&self, cells: &[Cell], width: u32, height: u32) -> Result<Option<Layer>> {
+        let Some(fill) = &self.fill else {
+            return Ok(None);
+        };
+        let paint = paint_attrs("fill", fill)?;
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height
+        )?;
+        for cell in cells.iter().map(|&c| self.cell(c)).filter(|c| c.width > 0.0 && c.height > 0.0) {
+            write!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" {}/>"#,
+                cell.x,
+                cell.y,
+                cell.width,
+                cell.height,
+                self.radius.max(0.0),
+                paint
+            )?;
+        }
+        svg.push_str("</svg>");
+        let tree = usvg::Tree::from_str(&svg, &svg_options())?;
+        Ok(Some(Layer {
+            source: Arc::new(Source::Vector(Box::new(tree))),
+            x: 0.0,
+            y: 0.0,
+            width: width as f32,
+            height: height as f32,
+            clip: None,
+            rotation: 0.0,
+        }))
+    }
+
+    /// 格子底部的名字和百分比，格子太小时返回 None
+    pub fn label_layer(
+        &self,
+        cell: Cell,
+        name: &str,
+        share: f32,
+        fonts: &Arc<fontdb::Database>,
+    ) -> Result<Option<Layer>> {
+        let cell = self.cell(cell);
+        let percent = match share * 100.0 {
+            p if p < 1.0 => "<1%".to_string(),
+            p => format!("{:.0}%", p),
+        };
+        let content = match (self.label, self.percent) {
+            (true, true) => format!("{} {}", name, percent),
+            (true, false) => name.to_string(),
+            (false, true) => percent,
+            (false, false) => return Ok(None),
+        };
+        let mut text = Text {
+            text: content,
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            size: self.label_size(cell),
+            family: self.family.clone(),
+            weight: 600,
+            color: self.color.clone(),
+            align: Align::Center,
+            line_height: 1.25,
+            shadow: None,
+            stroke: None,
+        };
+        if text.size < MIN_LABEL {
+            return Ok(None);
+        }
+        let mut layer = text.layer(fonts)?;
+        // 太宽时按比例缩小字号
+        let room = cell.width * 0.92;
+        if layer.width > room {
+            text.size *= room / layer.width;
+            if text.size < MIN_LABEL {
+                return Ok(None);
+            }
+            layer = text.layer(fonts)?;
+        }
+        let bottom = cell.y + cell.height - cell.width.min(cell.height) * self.padding.max(0.0);
+        layer.x = cell.x + (cell.width - layer.width) / 2.0;
+        layer.y = bottom - layer.height;
+        Ok(Some(layer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squarify_tiles_the_area() {
+        let area = Cell { x: 10.0, y: 20.0, width: 300.0, height: 200.0 };
+        let weights = [6.0, 6.0, 4.0, 3.0, 2.0, 2.0, 1.0, 0.0];
+        let cells = squarify(&weights, area);
+        assert_eq!(cells.len(), weights.len());
+        assert_eq!(cells[7], Cell::default());
+        let total: f32 = weights.iter().sum();
+        for (cell, w) in cells.iter().zip(weights).filter(|(_, w)| *w > 0.0) {
+            // 面积与权重成正比，格子都在区域内
+            let expected = area.width * area.height * w / total;
+            assert!((cell.width * cell.height - expected).abs() < 1.0, "{:?}", cell);
+            assert!(cell.x >= area.x - 0.01 && cell.x + cell.width <= area.x + area.width + 0.01);
+            assert!(cell.y >= area.y - 0.01 && cell.y + cell.height <= area.y + area.height + 0.01);
+        }
+        // 格子互不重叠，面积之和等于区域，所以正好铺满
+        for (i, a) in cells.iter().enumerate() {
+            for b in &cells[i + 1..] {
+                let dx = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+                let dy = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+                assert!(dx <= 0.01 || dy <= 0.01, "{:?} {:?}", a, b);
+            }
+        }
+        let sum: f32 = cells.iter().map(|c| c.width * c.height).sum();
+        assert!((sum - area.width * area.height).abs() < 1.0);
+    }
+
+    #[test]
+    fn free_avoids_regions() {
+        let cell = Cell { x: 0.0, y: 0.0, width: 100.0, height: 50.0 };
+        assert_eq!(cell.free(&[]), cell);
+        // 保留区域占据右边 30 像素
+        let region = Region { x: 70, y: 0, width: 50, height: 80 };
+        assert_eq!(cell.free(&[region]), Cell { width: 70.0, ..cell });
+        // 完全被覆盖
+        let region = Region { x: 0, y: 0, width: 100, height: 50 };
+        assert_eq!(cell.free(&[region]).width * cell.free(&[region]).height, 0.0);
+    }
+}
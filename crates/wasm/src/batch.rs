@@ -0,0 +1,165 @@
+//! 供 Web Worker 使用的批量栅格化接口。
+//!
+//! 每次 `run` 只处理有限个输入，调用之间 worker 可以让出事件循环以接收取消消息：
+//!
+//! ```js
+//! const batch = new Batch();
+//! files.forEach(f => batch.push(f.name, f.data));
+//! const token = batch.token();
+//! self.onmessage = e => { if (e.data === 'cancel') token.cancel(); };
+//! while (!batch.run(4, p => self.postMessage(p))) {
+//!     await new Promise(r => setTimeout(r));
+//! }
+//! ```
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use js_sys::{Function, Reflect, Uint8Array};
+use serde::Serialize;
+use techwall::meta::Meta;
+use wasm_bindgen::prelude::{wasm_bindgen, JsError};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::to_js;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export interface BatchProgress {
+    index: number;
+    total: number;
+    name: string;
+    meta?: Meta;
+    data?: Uint8Array;
+    error?: string;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "(progress: BatchProgress) => boolean | void")]
+    pub type JsProgressCallback;
+}
+
+#[derive(Serialize)]
+struct Progress<'a> {
+    index: usize,
+    total: usize,
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta: Option<&'a Meta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// 可以在消息处理中调用的取消标记，和所属的 `Batch` 共享状态
+#[wasm_bindgen]
+pub struct CancelToken(Rc<Cell<bool>>);
+
+#[wasm_bindgen]
+impl CancelToken {
+    #[wasm_bindgen]
+    pub fn cancel(&self) {
+        self.0.set(true);
+    }
+    #[wasm_bindgen]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.get()
+    }
+}
+
+#[wasm_bindgen]
+pub struct Batch {
+    pending: VecDeque<(String, Vec<u8>)>,
+    total: usize,
+    done: usize,
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl Batch {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Batch {
+        Batch {
+            pending: VecDeque::new(),
+            total: 0,
+            done: 0,
+            cancelled: Rc::new(Cell::new(false)),
+        }
+    }
+    /// 加入一个 SVG 输入
+    #[wasm_bindgen]
+    pub fn push(&mut self, name: &str, data: &Uint8Array) {
+        self.pending.push_back((name.to_string(), data.to_vec()));
+        self.total += 1;
+    }
+    #[wasm_bindgen]
+    pub fn token(&self) -> CancelToken {
+        CancelToken(self.cancelled.clone())
+    }
+    #[wasm_bindgen]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+    #[wasm_bindgen]
+    pub fn done(&self) -> usize {
+        self.done
+    }
+    /// 最多处理 `limit` 个输入，每完成一个调用一次 `callback`。
+    /// `callback` 返回 `false` 时取消剩余任务。全部完成或已取消时返回 true
+    #[wasm_bindgen]
+    pub fn run(&mut self, limit: usize, callback: &JsProgressCallback) -> Result<bool, JsError> {
+        let callback: &Function = callback.unchecked_ref();
+        for _ in 0..limit.max(1) {
+            if self.cancelled.get() {
+                self.pending.clear();
+                break;
+            }
+            let Some((name, data)) = self.pending.pop_front() else {
+                break;
+            };
+            let index = self.done;
+            self.done += 1;
+
+            let progress: JsValue = match Meta::from_svg(&name, data) {
+                Ok((meta, content)) => {
+                    let progress = Progress {
+                        index,
+                        total: self.total,
+                        name: &name,
+                        meta: Some(&meta),
+                        error: None,
+                    };
+                    let value: JsValue = to_js(&progress)?;
+                    let data: Uint8Array = content.as_slice().into();
+                    Reflect::set(&value, &"data".into(), &data)
+                        .map_err(|_| JsError::new("failed to set progress data"))?;
+                    value
+                }
+                Err(e) => to_js(&Progress {
+                    index,
+                    total: self.total,
+                    name: &name,
+                    meta: None,
+                    error: Some(e.to_string()),
+                })?,
+            };
+            let rs = callback
+                .call1(&JsValue::NULL, &progress)
+                .map_err(|_| JsError::new("progress callback failed"))?;
+            if rs == JsValue::FALSE {
+                self.cancelled.set(true);
+            }
+        }
+        if self.cancelled.get() {
+            self.pending.clear();
+        }
+        Ok(self.pending.is_empty())
+    }
+}
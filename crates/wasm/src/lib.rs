@@ -1,77 +1,723 @@
-use js_sys::Uint8Array;
-use wasm_bindgen::prelude::wasm_bindgen;
-// use techwall::png::Point;
+use js_sys::{Uint32Array, Uint8Array};
+use serde::Serialize;
+use techwall::banner::Placement;
+use techwall::catalog::Category;
+use techwall::animate::encode_gif;
+use techwall::background::{Background, Fit};
+use techwall::badge::{Badge, Style as BadgeStyle};
+use techwall::compose::{Compositor, Source};
+use techwall::container::{Container, Style};
+use techwall::mask::ShapeMask;
+use techwall::meta::{Meta, MetaData};
+use techwall::optimize::Optimize;
+use techwall::overlay::{Avatar, Text};
+use techwall::recolor::Recolor;
+use techwall::resolve::Resolver;
+use techwall::search::search;
+use techwall::sizing::{Curve, Sizing};
+use wasm_bindgen::prelude::{wasm_bindgen, JsError};
+use wasm_bindgen::{JsCast, JsValue};
+
+mod batch;
+pub use batch::{Batch, CancelToken};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export interface Vector {
+    x: number;
+    y: number;
+}
+
+export interface Meta {
+    name: string;
+    width: number;
+    height: number;
+    scale: number;
+    polygon: Vector[];
+    mono?: boolean;
+    svg?: string;
+}
+
+export type Category = "language" | "framework" | "database" | "cloud" | "tool" | "other";
+
+export interface Variant {
+    name: string;
+    kind: "default" | "icon" | "logo" | "wordmark";
+}
+
+export interface CatalogEntry {
+    name: string;
+    display: string;
+    aliases: string[];
+    category: Category;
+    score: number;
+    popularity: number;
+    variants: Variant[];
+}
+
+export interface SearchHit {
+    name: string;
+    display: string;
+    category: Category;
+    score: number;
+    icon: string;
+    kind: "exact" | "alias" | "prefix" | "contains" | "fuzzy";
+    quality: number;
+}
+
+export interface MetaData {
+    items: Meta[];
+    catalog?: { entries: CatalogEntry[] };
+}
+
+export interface Preference {
+    mark?: "any" | "symbol" | "wordmark";
+    tone?: "any" | "color" | "mono";
+    shape?: "any" | "square" | "wide";
+    /** 技术名 -> 图标文件 */
+    overrides?: Record<string, string>;
+}
+
+export interface Region {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+export interface Frame {
+    insets?: { top?: number; right?: number; bottom?: number; left?: number };
+    /** 留白以内区域的圆角半径 */
+    radius?: number;
+    /** 不放置图标的区域，例如名字或头像 */
+    keep_out?: Region[];
+}
+
+export interface Outline {
+    color: string;
+    width: number;
+}
+
+export interface TextOverlay {
+    text: string;
+    x: number;
+    y: number;
+    /** 超过该宽度时换行 */
+    width?: number;
+    size?: number;
+    family?: string;
+    weight?: number;
+    color?: string;
+    align?: "left" | "center" | "right";
+    line_height?: number;
+    shadow?: { color: string; dx?: number; dy?: number; blur?: number };
+    stroke?: Outline;
+}
+
+export interface AvatarOverlay {
+    x: number;
+    y: number;
+    size: number;
+    border?: Outline;
+}
+
+export interface ContainerStyle {
+    fill?: string;
+    stroke?: Outline;
+}
+
+export interface Treemap {
+    /** 格子之间的间距 */
+    gap?: number;
+    /** 图标到格子边缘的距离，格子短边的比例 */
+    padding?: number;
+    /** 格子的填充色，为空时不画格子 */
+    fill?: string;
+    radius?: number;
+    label?: boolean;
+    percent?: boolean;
+    color?: string;
+    family?: string;
+}
+
+export interface Hex {
+    /** 格子的外接圆半径，为空时按图标数量自动计算 */
+    radius?: number;
+    gap?: number;
+    /** 图标到格子边缘的距离，格子半径的比例 */
+    padding?: number;
+    /** 颜色、图标主色 "dominant" 或主色的浅色 "tint" */
+    fill?: string;
+    border?: Outline;
+}
+
+export interface Orbit {
+    /** 轨道数，0 为自动 */
+    rings?: number;
+    /** 按权重 "weight" 或分组 "group" 分配轨道 */
+    by?: "weight" | "group";
+    /** 中心图标的直径，画框短边的比例 */
+    center?: number;
+}
+
+export interface Shelves {
+    /** 分区内紧密排列 "grid" 或落下堆积 "pile" */
+    pack?: "grid" | "pile";
+    /** 标题字号，0 为不显示标题 */
+    size?: number;
+    weight?: number;
+    color?: string;
+    family?: string;
+    /** 分区之间的间距 */
+    gap?: number;
+    /** 标题下方的分隔线 */
+    divider?: Outline;
+}
+
+export interface Metrics {
+    /** 图标覆盖的面积占排布区域的比例 */
+    coverage: number;
+    /** 最大的空白矩形 */
+    largest_empty: Region;
+    /** 每个图标到最近图标的距离 */
+    gaps: { min: number; max: number; mean: number; deviation: number };
+    /** 重心的偏移，-1 到 1 */
+    balance_x: number;
+    balance_y: number;
+    /** 相邻的图标中颜色相近的比例 */
+    same_color: number;
+}
+
+export interface Optimize {
+    objective?: "empty" | "balance" | "gaps" | "color" | "all";
+    iterations?: number;
+    /** 同时旋转图标，角度不超过正负 30 度 */
+    rotate?: boolean;
+}
+
+export interface Placement {
+    name: string;
+    x: number;
+    y: number;
+    scale: number;
+    /** 绕图标中心顺时针旋转的角度 */
+    angle?: number;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Vector")]
+    pub type JsVector;
+    #[wasm_bindgen(typescript_type = "Vector[]")]
+    pub type JsVectorList;
+    #[wasm_bindgen(typescript_type = "Meta")]
+    pub type JsMeta;
+    #[wasm_bindgen(typescript_type = "MetaData")]
+    pub type JsMetaData;
+    #[wasm_bindgen(typescript_type = "Preference")]
+    pub type JsPreference;
+    #[wasm_bindgen(typescript_type = "Frame")]
+    pub type JsFrame;
+    #[wasm_bindgen(typescript_type = "TextOverlay")]
+    pub type JsTextOverlay;
+    #[wasm_bindgen(typescript_type = "AvatarOverlay")]
+    pub type JsAvatarOverlay;
+    #[wasm_bindgen(typescript_type = "ContainerStyle")]
+    pub type JsContainerStyle;
+    #[wasm_bindgen(typescript_type = "Treemap")]
+    pub type JsTreemap;
+    #[wasm_bindgen(typescript_type = "Hex")]
+    pub type JsHex;
+    #[wasm_bindgen(typescript_type = "Orbit")]
+    pub type JsOrbit;
+    #[wasm_bindgen(typescript_type = "Shelves")]
+    pub type JsShelves;
+    #[wasm_bindgen(typescript_type = "Metrics")]
+    pub type JsMetrics;
+    #[wasm_bindgen(typescript_type = "Optimize")]
+    pub type JsOptimize;
+    #[wasm_bindgen(typescript_type = "Placement[]")]
+    pub type JsPlacementList;
+    #[wasm_bindgen(typescript_type = "CatalogEntry[]")]
+    pub type JsEntryList;
+    #[wasm_bindgen(typescript_type = "SearchHit[]")]
+    pub type JsHitList;
+    #[wasm_bindgen(typescript_type = "string[]")]
+    pub type JsStringList;
+    #[wasm_bindgen(typescript_type = "Uint8Array[]")]
+    pub type JsPngList;
+}
+
+// 转成普通的 JS 对象，而不是需要逐个调用 getter 的 wasm 对象
+pub(crate) fn to_js<T: Serialize + ?Sized, R: JsCast>(value: &T) -> Result<R, JsError> {
+    let value: JsValue = serde_wasm_bindgen::to_value(value)?;
+    Ok(value.unchecked_into())
+}
+
+fn js_error(e: anyhow::Error) -> JsError {
+    JsError::new(&e.to_string())
+}
 
 #[wasm_bindgen]
 pub struct SVGResult {
-    rect: (u32, u32),
+    meta: Meta,
     data: Vec<u8>,
-    polygon: Vec<(u32, u32)>,
 }
 
 #[wasm_bindgen]
-pub struct Point {
-    x: u32,
-    y: u32,
-}
-impl Point {
-    pub fn new(x: u32, y: u32) -> Point {
-        Point { x, y }
+impl SVGResult {
+    /// 栅格化后的 PNG
+    #[wasm_bindgen]
+    pub fn data(&self) -> Uint8Array {
+        let slice = self.data.as_slice();
+        slice.into()
+    }
+    #[wasm_bindgen]
+    pub fn rect(&self) -> Result<JsVector, JsError> {
+        to_js(&techwall::meta::Point::from((self.meta.width, self.meta.height)))
+    }
+    /// 可直接交给 `Matter.Bodies.fromVertices` 的顶点
+    #[wasm_bindgen]
+    pub fn polygon(&self) -> Result<JsVectorList, JsError> {
+        to_js(&self.meta.polygon)
+    }
+    /// 交错排列的顶点坐标 `[x0, y0, x1, y1, ...]`
+    #[wasm_bindgen]
+    pub fn polygon_flat(&self) -> Uint32Array {
+        let flat: Vec<u32> = self.meta.polygon.iter().flat_map(|p| [p.x, p.y]).collect();
+        flat.as_slice().into()
+    }
+    /// 与 `resource/meta` 中条目结构相同
+    #[wasm_bindgen]
+    pub fn meta(&self) -> Result<JsMeta, JsError> {
+        to_js(&self.meta)
     }
 }
+
 #[wasm_bindgen]
-impl Point {
+pub fn load_svg(name: &str, s: &Uint8Array) -> Result<SVGResult, JsError> {
+    let (meta, data) = Meta::from_svg(name, s.to_vec()).map_err(js_error)?;
+    Ok(SVGResult { meta, data })
+}
+
+/// `resource/meta` 中的图标目录
+#[wasm_bindgen]
+pub struct IconCatalog {
+    meta: MetaData,
+}
+
+#[wasm_bindgen]
+impl IconCatalog {
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: JsMetaData) -> Result<IconCatalog, JsError> {
+        let meta: MetaData = serde_wasm_bindgen::from_value(data.into())?;
+        Ok(IconCatalog { meta })
+    }
     #[wasm_bindgen]
-    pub fn x(&self) -> u32 {
-        self.x
+    pub fn entries(&self) -> Result<JsEntryList, JsError> {
+        to_js(&self.meta.catalog.entries)
     }
     #[wasm_bindgen]
-    pub fn y(&self) -> u32 {
-        self.y
+    pub fn by_category(&self, category: &str) -> Result<JsEntryList, JsError> {
+        let Some(category) = Category::parse(category) else {
+            return Err(JsError::new(&format!("invalid category: {}", category)));
+        };
+        let entries: Vec<_> = self.meta.catalog.by_category(category).collect();
+        to_js(&entries)
     }
-}
-impl From<(u32, u32)> for Point {
-    fn from(value: (u32, u32)) -> Self {
-        Point::new(value.0, value.1)
+    /// 模糊搜索，支持 "postgres"、"k8s"、"golang"、"c#" 等别名和拼写错误
+    #[wasm_bindgen]
+    pub fn search(&self, query: &str, limit: usize) -> Result<JsHitList, JsError> {
+        to_js(&search(&self.meta.catalog, query, limit))
+    }
+    /// 按偏好选出技术对应的图标文件，找不到时返回 undefined
+    #[wasm_bindgen]
+    pub fn resolve(&self, tech: &str, preference: Option<JsPreference>) -> Result<Option<String>, JsError> {
+        let mut resolver = Resolver::new(&self.meta.catalog, &self.meta);
+        if let Some(preference) = preference {
+            resolver.preference = serde_wasm_bindgen::from_value(preference.into())?;
+        }
+        Ok(resolver.resolve(tech).map(|s| s.to_string()))
+    }
+    /// 按热度选出的默认图标文件名
+    #[wasm_bindgen]
+    pub fn default_selection(&self, count: usize) -> Result<JsStringList, JsError> {
+        to_js(&self.meta.catalog.default_selection(count))
     }
 }
 
+/// 排布与合成，和命令行 `crawler banner` 走同一套流程，输出一致
+#[wasm_bindgen]
+pub struct Banner {
+    inner: techwall::banner::Banner,
+    meta: MetaData,
+    placements: Option<Vec<Placement>>,
+}
 
-impl SVGResult {
-    fn _new(data: Vec<u8>, rect: (u32, u32), polygon: Vec<(u32, u32)>) -> SVGResult {
-        SVGResult { data, rect, polygon }
+impl Banner {
+    fn add_source(&mut self, name: &str, source: Source, scale: f32) -> Result<(), JsError> {
+        let Some(meta) = self.meta.find(name) else {
+            return Err(JsError::new(&format!("icon not found: {}", name)));
+        };
+        self.inner.add(meta.clone(), source, scale);
+        self.placements = None;
+        Ok(())
+    }
+    fn current(&mut self) -> &[Placement] {
+        self.placements.get_or_insert_with(|| self.inner.layout())
     }
 }
 
 #[wasm_bindgen]
-impl SVGResult {
+impl Banner {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32) -> Banner {
+        Banner {
+            inner: techwall::banner::Banner::new(width, height),
+            meta: MetaData::default(),
+            placements: None,
+        }
+    }
+    /// 载入解压后的 `resource/meta` 内容
     #[wasm_bindgen]
-    pub fn data(&self) -> Uint8Array {
-        let slice = self.data.as_slice();
-        slice.into()
+    pub fn load_meta(&mut self, data: JsMetaData) -> Result<(), JsError> {
+        self.meta = serde_wasm_bindgen::from_value(data.into())?;
+        Ok(())
     }
+    /// 纯色、渐变或图案，例如 "#101018"、"linear:135:#1e1e2e:#89b4fa"、"dots:#ffffff33:#101018"
     #[wasm_bindgen]
-    pub fn rect(&self) -> Point {
-        Point::from(self.rect)
+    pub fn set_background(&mut self, spec: &str) -> Result<(), JsError> {
+        let Some(background) = Background::parse(spec) else {
+            return Err(JsError::new(&format!("invalid background: {}", spec)));
+        };
+        self.inner.background = Some(background);
+        Ok(())
     }
-    
-
+    /// PNG 或 JPG 背景图，`fit` 为 "cover"、"contain" 或 "tile"
     #[wasm_bindgen]
-    pub fn polygon(&self) -> Vec<Point> {
-        self.polygon.iter().map(|f|Point::from(*f)).collect()
+    pub fn set_background_image(&mut self, data: &Uint8Array, fit: &str, blur: f32) -> Result<(), JsError> {
+        let Some(fit) = Fit::parse(fit) else {
+            return Err(JsError::new(&format!("invalid fit: {}", fit)));
+        };
+        self.inner.background = Some(Background::image(&data.to_vec(), fit, blur).map_err(js_error)?);
+        Ok(())
+    }
+    #[wasm_bindgen]
+    pub fn set_arrange(&mut self, arrange: &str) -> Result<(), JsError> {
+        let Some(arrange) = techwall::banner::Arrange::parse(arrange) else {
+            return Err(JsError::new(&format!("invalid arrange: {}", arrange)));
+        };
+        self.inner.arrange = arrange;
+        self.placements = None;
+        Ok(())
+    }
+    #[wasm_bindgen]
+    pub fn set_frame(&mut self, frame: JsFrame) -> Result<(), JsError> {
+        self.inner.frame = serde_wasm_bindgen::from_value(frame.into())?;
+        self.placements = None;
+        Ok(())
+    }
+    /// 文字使用的 TTF/OTF 字体，浏览器中没有系统字体，需要先加载
+    #[wasm_bindgen]
+    pub fn load_font(&mut self, data: &Uint8Array) {
+        self.inner.load_font(data.to_vec());
+    }
+    #[wasm_bindgen]
+    pub fn add_text(&mut self, text: JsTextOverlay) -> Result<(), JsError> {
+        let text: Text = serde_wasm_bindgen::from_value(text.into())?;
+        self.inner.add_text(&text).map_err(js_error)?;
+        self.placements = None;
+        Ok(())
+    }
+    /// 圆形头像，`data` 为 PNG 或 JPG
+    #[wasm_bindgen]
+    pub fn add_avatar(&mut self, avatar: JsAvatarOverlay, data: &Uint8Array) -> Result<(), JsError> {
+        let avatar: Avatar = serde_wasm_bindgen::from_value(avatar.into())?;
+        self.inner.add_avatar(&avatar, &data.to_vec()).map_err(js_error)?;
+        self.placements = None;
+        Ok(())
+    }
+    /// 形状排布的目标："heart"、"star" 等内置形状，"text:JD" 或 "path:M0 0 L100 0 L50 80 Z"
+    #[wasm_bindgen]
+    pub fn set_shape(&mut self, spec: &str) -> Result<(), JsError> {
+        let (width, height) = (self.inner.width, self.inner.height);
+        let shape = if let Some(text) = spec.strip_prefix("text:") {
+            self.inner.text_shape(text).map_err(js_error)?
+        } else if let Some(d) = spec.strip_prefix("path:") {
+            ShapeMask::from_path(d, width, height).map_err(js_error)?
+        } else {
+            let Some(shape) = ShapeMask::builtin(spec, width, height) else {
+                return Err(JsError::new(&format!("invalid shape: {}", spec)));
+            };
+            shape
+        };
+        self.inner.shape = Some(shape);
+        self.placements = None;
+        Ok(())
+    }
+    /// 用 SVG 或带透明度的图片作为形状
+    #[wasm_bindgen]
+    pub fn set_shape_image(&mut self, data: &Uint8Array, svg: bool) -> Result<(), JsError> {
+        let (width, height) = (self.inner.width, self.inner.height);
+        let data = data.to_vec();
+        let shape = match svg {
+            true => ShapeMask::from_svg(&data, width, height),
+            false => ShapeMask::from_image(&data, width, height),
+        };
+        self.inner.shape = Some(shape.map_err(js_error)?);
+        self.placements = None;
+        Ok(())
+    }
+    /// 堆积时的容器："jar"、"bowl" 等内置容器或 "path:M0 0 L100 0 L100 100 L0 100 Z"
+    #[wasm_bindgen]
+    pub fn set_container(&mut self, spec: &str, style: Option<JsContainerStyle>) -> Result<(), JsError> {
+        let style: Style = match style {
+            Some(style) => serde_wasm_bindgen::from_value(style.into())?,
+            None => Style::default(),
+        };
+        let (width, height) = (self.inner.width, self.inner.height);
+        let container = match spec.strip_prefix("path:") {
+            Some(d) => Container::from_path(d, &style, width, height),
+            None => match Container::builtin(spec, &style, width, height) {
+                Some(container) => container,
+                None => return Err(JsError::new(&format!("invalid container: {}", spec))),
+            },
+        };
+        self.inner.container = Some(container.map_err(js_error)?);
+        self.placements = None;
+        Ok(())
+    }
+    /// SVG 中填充的部分作为容器内部
+    #[wasm_bindgen]
+    pub fn set_container_svg(&mut self, data: &Uint8Array) -> Result<(), JsError> {
+        let (width, height) = (self.inner.width, self.inner.height);
+        let container = Container::from_svg(&data.to_vec(), width, height).map_err(js_error)?;
+        self.inner.container = Some(container);
+        self.placements = None;
+        Ok(())
+    }
+    /// 形状排布和平铺时循环使用图标直到填满
+    #[wasm_bindgen]
+    pub fn set_repeat(&mut self, repeat: bool) {
+        self.inner.repeat = repeat;
+        self.placements = None;
+    }
+    #[wasm_bindgen]
+    pub fn set_seed(&mut self, seed: u32) {
+        self.inner.seed = seed as u64;
+        self.placements = None;
+    }
+    /// "original"、"grayscale"、"auto-invert"、"mono:#ffffff" 或 "duotone:#1e1e2e:#89b4fa"
+    #[wasm_bindgen]
+    pub fn set_recolor(&mut self, spec: &str) -> Result<(), JsError> {
+        let Some(recolor) = Recolor::parse(spec) else {
+            return Err(JsError::new(&format!("invalid recolor: {}", spec)));
+        };
+        self.inner.recolor = recolor;
+        Ok(())
+    }
+    /// 按权重缩放图标，`curve` 为 "linear"、"sqrt"、"log" 或 "rank:3"
+    #[wasm_bindgen]
+    pub fn set_sizing(&mut self, curve: &str, min: f32, max: f32) -> Result<(), JsError> {
+        let Some(curve) = Curve::parse(curve) else {
+            return Err(JsError::new(&format!("invalid sizing: {}", curve)));
+        };
+        self.inner.sizing = Some(Sizing { curve, min, max });
+        self.placements = None;
+        Ok(())
+    }
+    #[wasm_bindgen]
+    pub fn set_weight(&mut self, name: &str, weight: f32) -> Result<(), JsError> {
+        if !self.inner.set_weight(name, weight) {
+            return Err(JsError::new(&format!("icon not added: {}", name)));
+        }
+        self.placements = None;
+        Ok(())
+    }
+    /// 目录中的热度 (0 到 1)，所有图标都没有权重时代替权重
+    #[wasm_bindgen]
+    pub fn set_popularity(&mut self, name: &str, popularity: f32) -> Result<(), JsError> {
+        if !self.inner.set_popularity(name, popularity) {
+            return Err(JsError::new(&format!("icon not added: {}", name)));
+        }
+        self.placements = None;
+        Ok(())
+    }
+    /// 树图中显示的名字，默认为图标名
+    #[wasm_bindgen]
+    pub fn set_label(&mut self, name: &str, label: &str) -> Result<(), JsError> {
+        if !self.inner.set_label(name, label) {
+            return Err(JsError::new(&format!("icon not added: {}", name)));
+        }
+        Ok(())
+    }
+    /// 树图格子和文字的样式
+    #[wasm_bindgen]
+    pub fn set_treemap(&mut self, treemap: JsTreemap) -> Result<(), JsError> {
+        self.inner.treemap = serde_wasm_bindgen::from_value(treemap.into())?;
+        self.placements = None;
+        Ok(())
+    }
+    /// 六边形格子的大小、填充和描边
+    #[wasm_bindgen]
+    pub fn set_hex(&mut self, hex: JsHex) -> Result<(), JsError> {
+        self.inner.hex = serde_wasm_bindgen::from_value(hex.into())?;
+        self.placements = None;
+        Ok(())
+    }
+    /// 图标的分组，例如目录中的分类名 "language"；环绕排布的轨道和书架的分区按分组划分
+    #[wasm_bindgen]
+    pub fn set_group(&mut self, name: &str, group: &str) -> Result<(), JsError> {
+        if !self.inner.set_group(name, group) {
+            return Err(JsError::new(&format!("icon not added: {}", name)));
+        }
+        self.placements = None;
+        Ok(())
+    }
+    /// 环绕排布的轨道数、分配方式和中心图标大小
+    #[wasm_bindgen]
+    pub fn set_orbit(&mut self, orbit: JsOrbit) -> Result<(), JsError> {
+        self.inner.orbit = serde_wasm_bindgen::from_value(orbit.into())?;
+        self.placements = None;
+        Ok(())
+    }
+    /// 书架排布的分区方式和标题样式，分区由 `set_group` 的分组决定
+    #[wasm_bindgen]
+    pub fn set_shelves(&mut self, shelves: JsShelves) -> Result<(), JsError> {
+        self.inner.shelves = serde_wasm_bindgen::from_value(shelves.into())?;
+        self.placements = None;
+        Ok(())
+    }
+    #[wasm_bindgen]
+    pub fn add_png(&mut self, name: &str, data: &Uint8Array, scale: f32) -> Result<(), JsError> {
+        let source = Source::from_png(&data.to_vec()).map_err(js_error)?;
+        self.add_source(name, source, scale)
+    }
+    #[wasm_bindgen]
+    pub fn add_svg(&mut self, name: &str, data: &Uint8Array, scale: f32) -> Result<(), JsError> {
+        let source = Source::from_svg(&data.to_vec()).map_err(js_error)?;
+        self.add_source(name, source, scale)
+    }
+    #[wasm_bindgen]
+    pub fn layout(&mut self) -> Result<JsPlacementList, JsError> {
+        to_js(self.current())
+    }
+    /// 当前排布的质量指标
+    #[wasm_bindgen]
+    pub fn metrics(&mut self) -> Result<JsMetrics, JsError> {
+        let placements = self.current().to_vec();
+        to_js(&self.inner.metrics(&placements))
+    }
+    /// 在当前排布上优化选定的指标，之后的渲染使用优化后的排布
+    #[wasm_bindgen]
+    pub fn optimize(&mut self, config: JsOptimize) -> Result<JsPlacementList, JsError> {
+        let config: Optimize = serde_wasm_bindgen::from_value(config.into())?;
+        let placements = self.current().to_vec();
+        let (optimized, _, _) = self.inner.optimize(&placements, &config).map_err(js_error)?;
+        to_js(self.placements.insert(optimized))
+    }
+    #[wasm_bindgen]
+    pub fn render_png(&mut self) -> Result<Uint8Array, JsError> {
+        let placements = self.current().to_vec();
+        let content = self.inner.compose(&placements).to_png().map_err(js_error)?;
+        Ok(content.as_slice().into())
+    }
+    #[wasm_bindgen]
+    pub fn render_svg(&mut self) -> Result<String, JsError> {
+        let placements = self.current().to_vec();
+        self.inner.compose(&placements).to_svg().map_err(js_error)
+    }
+    /// 环绕排布的轨道旋转一周的动画，`delay` 为每帧的毫秒数
+    #[wasm_bindgen]
+    pub fn render_gif(&self, frames: u32, delay: u32) -> Result<Uint8Array, JsError> {
+        let compositors: Vec<Compositor> =
+            self.inner.orbit_frames(frames).iter().map(|p| self.inner.compose(p)).collect();
+        let content = encode_gif(&compositors, delay).map_err(js_error)?;
+        Ok(content.as_slice().into())
     }
-
 }
 
-// #[wasm_bindgen]
-// pub fn load_svg (s: &Uint8Array) -> SVGResult {
-//     let data = s.to_vec();
-//     let pix = crawler::convert::convert_svg_to_png(data).unwrap();
-    
-//     let w = pix.width() / 10;
-//     let h = pix.height() / 10;
-//     let content = pix.encode_png().unwrap();
-//     let polygon = techwall::png::load(&content, (w as usize,h as usize)).unwrap();
+/// shields 风格的徽章，底色取图标主色，名字由调用方提供（例如目录中的显示名）
+#[wasm_bindgen]
+pub struct Badges {
+    inner: techwall::badge::Badges,
+}
 
-//     SVGResult::new(content, (w, h), polygon)
-// }
\ No newline at end of file
+#[wasm_bindgen]
+impl Badges {
+    /// `style` 为 "flat"、"flat-square" 或 "for-the-badge"
+    #[wasm_bindgen(constructor)]
+    pub fn new(style: &str) -> Result<Badges, JsError> {
+        let Some(style) = BadgeStyle::parse(style) else {
+            return Err(JsError::new(&format!("invalid badge style: {}", style)));
+        };
+        Ok(Badges {
+            inner: techwall::badge::Badges::new(style),
+        })
+    }
+    /// 浏览器中没有系统字体，需要先加载
+    #[wasm_bindgen]
+    pub fn load_font(&mut self, data: &Uint8Array) {
+        self.inner.load_font(data.to_vec());
+    }
+    /// 每行的最大宽度，0 为不换行
+    #[wasm_bindgen]
+    pub fn set_max_width(&mut self, width: u32) {
+        self.inner.max_width = width;
+    }
+    #[wasm_bindgen]
+    pub fn set_gap(&mut self, gap: u32) {
+        self.inner.gap = gap;
+    }
+    #[wasm_bindgen]
+    pub fn set_scale(&mut self, scale: f32) {
+        self.inner.scale = scale;
+    }
+    #[wasm_bindgen]
+    pub fn add_png(&mut self, name: &str, label: &str, data: &Uint8Array) -> Result<(), JsError> {
+        let source = Source::from_png(&data.to_vec()).map_err(js_error)?;
+        self.inner.push(Badge::new(name, label, source));
+        Ok(())
+    }
+    #[wasm_bindgen]
+    pub fn add_svg(&mut self, name: &str, label: &str, data: &Uint8Array) -> Result<(), JsError> {
+        let source = Source::from_svg(&data.to_vec()).map_err(js_error)?;
+        self.inner.push(Badge::new(name, label, source));
+        Ok(())
+    }
+    /// 所有徽章合成一张 SVG
+    #[wasm_bindgen]
+    pub fn render_svg(&mut self) -> Result<String, JsError> {
+        self.inner.compose().map_err(js_error)?.to_svg().map_err(js_error)
+    }
+    #[wasm_bindgen]
+    pub fn render_png(&mut self) -> Result<Uint8Array, JsError> {
+        let content = self.inner.compose().map_err(js_error)?.to_png().map_err(js_error)?;
+        Ok(content.as_slice().into())
+    }
+    /// 每个徽章单独一张 SVG，顺序与添加顺序相同
+    #[wasm_bindgen]
+    pub fn render_svg_each(&mut self) -> Result<JsStringList, JsError> {
+        let svgs = self
+            .inner
+            .compose_each()
+            .map_err(js_error)?
+            .iter()
+            .map(|c| c.to_svg())
+            .collect::<anyhow::Result<Vec<String>>>()
+            .map_err(js_error)?;
+        to_js(&svgs)
+    }
+    /// 每个徽章单独一张 PNG，顺序与添加顺序相同
+    #[wasm_bindgen]
+    pub fn render_png_each(&mut self) -> Result<JsPngList, JsError> {
+        // serde 会把字节转成数字数组，这里直接构造 Uint8Array
+        let list = js_sys::Array::new();
+        for compositor in self.inner.compose_each().map_err(js_error)? {
+            let content = compositor.to_png().map_err(js_error)?;
+            list.push(&Uint8Array::from(content.as_slice()));
+        }
+        Ok(list.unchecked_into())
+    }
+}